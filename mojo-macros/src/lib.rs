@@ -0,0 +1,349 @@
+//! # Mojo Macros
+//!
+//! Procedural macros for defining Mojo game states.
+//!
+//! ```ignore
+//! use mojo_sdk::mojo_state;
+//!
+//! #[mojo_state(type_id = 1, seed = "board")]
+//! pub struct GameBoard {
+//!     cells: [u8; 9],
+//!     current_player: u8,
+//!     winner: u8,
+//! }
+//! ```
+//!
+//! `#[mojo_state]` adds `#[repr(C)]` and `#[derive(Clone, Copy, MojoState)]` to the struct.
+//! `#[derive(MojoState)]` can also be used directly on a `#[repr(C)]` struct that derives
+//! `Clone` and `Copy`.
+//!
+//! Every named field gets a `patch_<field>` constructor, e.g. `GameBoard::patch_winner(1)`,
+//! building the segment that rewrites only that field of states following the schema of the
+//! struct, and a typed `field_<field>()` handle for the atomic arithmetic of
+//! `mojo_sdk::World::increment`.
+//!
+//! The type id, layout version and a hash of the struct layout are recorded in the header of
+//! every state written, reads check them before deserializing. States of an older version are
+//! upgraded through `mojo_sdk::Migrate` when the struct opts in with `migrate`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Fields, ItemStruct, LitInt, LitStr, Type,
+};
+
+/// Derive `MojoState`, `bytemuck::Pod` and `bytemuck::Zeroable` for a `#[repr(C)]` struct
+///
+/// Every field has to be `Pod` and the struct may not contain padding bytes, both are
+/// checked at compile time. Named fields get a `patch_<field>(value)` constructor for
+/// `mojo_sdk::PatchSegment` and a `field_<field>()` constructor for `mojo_sdk::StateField`.
+///
+/// # Attributes
+/// * `#[mojo(type_id = 1)]` - state type identifier, exposed as `Self::TYPE_ID` and recorded
+///   in the state header, 0 when omitted
+/// * `#[mojo(seed = "board")]` - state name, exposed as `Self::SEED` along with typed
+///   `pda`, `read` and `write` helpers
+/// * `#[mojo(version = 1)]` - layout version of the state, raised with every layout change,
+///   0 when omitted
+/// * `#[mojo(migrate)]` - upgrade states of older versions through the `mojo_sdk::Migrate`
+///   implementation of the struct
+#[proc_macro_derive(MojoState, attributes(mojo))]
+pub fn derive_mojo_state(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_mojo_state(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Turn a plain struct into a Mojo state
+///
+/// Adds `#[repr(C)]` and `#[derive(Clone, Copy, MojoState)]`, any arguments are forwarded
+/// as `#[mojo(...)]` attributes, e.g. `#[mojo_state(type_id = 1, seed = "board")]`.
+#[proc_macro_attribute]
+pub fn mojo_state(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = TokenStream2::from(args);
+    let item = parse_macro_input!(item as ItemStruct);
+
+    let mojo_attr = if args.is_empty() {
+        quote!()
+    } else {
+        quote!(#[mojo(#args)])
+    };
+
+    quote! {
+        #[repr(C)]
+        #[derive(Clone, Copy, ::mojo_sdk::MojoState)]
+        #mojo_attr
+        #item
+    }
+    .into()
+}
+
+/// Options parsed from `#[mojo(...)]` attributes
+#[derive(Default)]
+struct MojoOptions {
+    type_id: Option<LitInt>,
+    seed: Option<LitStr>,
+    version: Option<LitInt>,
+    migrate: bool,
+}
+
+impl MojoOptions {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = MojoOptions::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("mojo")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("type_id") {
+                    let type_id: LitInt = meta.value()?.parse()?;
+                    // reject anything that does not fit the on-chain u8 identifier
+                    type_id.base10_parse::<u8>()?;
+                    options.type_id = Some(type_id);
+                    Ok(())
+                } else if meta.path.is_ident("seed") {
+                    let seed: LitStr = meta.value()?.parse()?;
+                    if seed.value().is_empty() {
+                        return Err(meta.error("seed must not be empty"));
+                    }
+                    options.seed = Some(seed);
+                    Ok(())
+                } else if meta.path.is_ident("version") {
+                    let version: LitInt = meta.value()?.parse()?;
+                    // the on-chain schema version is a u8 as well
+                    version.base10_parse::<u8>()?;
+                    options.version = Some(version);
+                    Ok(())
+                } else if meta.path.is_ident("migrate") {
+                    options.migrate = true;
+                    Ok(())
+                } else {
+                    Err(meta.error(
+                        "unsupported mojo attribute, expected `type_id`, `seed`, `version` or `migrate`",
+                    ))
+                }
+            })?;
+        }
+
+        Ok(options)
+    }
+}
+
+fn has_stable_repr(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+        .any(|attr| {
+            let mut stable = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("C") || meta.path.is_ident("transparent") {
+                    stable = true;
+                }
+                Ok(())
+            });
+            stable
+        })
+}
+
+fn expand_mojo_state(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "MojoState cannot be derived for generic types",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "MojoState can only be derived for structs",
+            ))
+        }
+    };
+
+    if !has_stable_repr(&input.attrs) {
+        return Err(syn::Error::new_spanned(
+            name,
+            "MojoState requires #[repr(C)], use #[mojo_state] to add it automatically",
+        ));
+    }
+
+    let field_types: Vec<&Type> = fields.iter().map(|field| &field.ty).collect();
+    let options = MojoOptions::from_attrs(&input.attrs)?;
+
+    let padding_message = format!(
+        "MojoState `{}` contains padding bytes, reorder its fields or add explicit padding fields",
+        name
+    );
+
+    // the schema hash changes with the struct name, field names, order or types
+    let layout = format!(
+        "{}{{{}}}",
+        name,
+        fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let ty = &field.ty;
+                let field_name = field
+                    .ident
+                    .as_ref()
+                    .map_or_else(|| index.to_string(), |ident| ident.to_string());
+                format!(
+                    "{}:{}",
+                    field_name,
+                    quote!(#ty).to_string().replace(' ', "")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    let state_type_id = match &options.type_id {
+        Some(type_id) => quote!(#type_id),
+        None => quote!(0),
+    };
+    let schema_version = match &options.version {
+        Some(version) => quote!(#version),
+        None => quote!(0),
+    };
+    let migrate_from = options.migrate.then(|| {
+        quote! {
+            fn migrate_from(
+                schema_version: u8,
+                data: &[u8],
+            ) -> ::core::result::Result<Self, ::mojo_sdk::MojoSDKError> {
+                <Self as ::mojo_sdk::Migrate>::from_version(schema_version, data)
+            }
+        }
+    });
+
+    let type_id = options.type_id.as_ref().map(|type_id| {
+        quote! {
+            /// State type identifier of this state
+            pub const TYPE_ID: u8 = #type_id;
+        }
+    });
+
+    let seed_helpers = options.seed.as_ref().map(|seed| {
+        quote! {
+            /// State name used to derive the PDA of this state
+            pub const SEED: &'static str = #seed;
+
+            /// Derive the PDA of this state owned by `owner` in `world`
+            pub fn pda(
+                world: &::mojo_sdk::World,
+                owner: &::mojo_sdk::__private::Pubkey,
+                program_id: &::mojo_sdk::__private::Pubkey,
+            ) -> ::mojo_sdk::__private::Pubkey {
+                world.state_pda(Self::SEED, owner, program_id)
+            }
+
+            /// Read this state owned by `owner` from `world`
+            pub fn read(
+                world: &::mojo_sdk::World,
+                client: &::mojo_sdk::SdkClient,
+                owner: &::mojo_sdk::__private::Pubkey,
+            ) -> ::core::result::Result<Self, ::mojo_sdk::MojoSDKError> {
+                world.read_delegated_state(client, Self::SEED, owner)
+            }
+
+            /// Write only the `segments` of this state owned by `owner` in `world`
+            pub fn patch(
+                world: &::mojo_sdk::World,
+                client: &::mojo_sdk::SdkClient,
+                owner: &::mojo_sdk::__private::Keypair,
+                segments: &[::mojo_sdk::PatchSegment],
+            ) -> ::core::result::Result<(), ::mojo_sdk::MojoSDKError> {
+                world.patch_state(client, Self::SEED, owner, segments)
+            }
+
+            /// Write this state to `world`, creating it on first write
+            pub fn write(
+                self,
+                world: &::mojo_sdk::World,
+                client: &::mojo_sdk::SdkClient,
+                owner: &::mojo_sdk::__private::Keypair,
+            ) -> ::core::result::Result<(), ::mojo_sdk::MojoSDKError> {
+                world.write_state(client, Self::SEED, owner, self)
+            }
+        }
+    });
+
+    // tuple structs have no field names to build field helpers from
+    let field_helpers: Vec<TokenStream2> = match fields {
+        Fields::Named(named) => named
+            .named
+            .iter()
+            .filter_map(|field| {
+                let ident = field.ident.as_ref()?;
+                let ty = &field.ty;
+                let patch = format_ident!("patch_{}", ident);
+                let patch_doc = format!("Patch segment rewriting only the `{}` field", ident);
+                let field = format_ident!("field_{}", ident);
+                let field_doc = format!("Typed handle on the `{}` field", ident);
+                Some(quote! {
+                    #[doc = #patch_doc]
+                    pub fn #patch(value: #ty) -> ::mojo_sdk::PatchSegment {
+                        ::mojo_sdk::PatchSegment::value(::core::mem::offset_of!(Self, #ident), &value)
+                            .typed::<Self>()
+                    }
+
+                    #[doc = #field_doc]
+                    pub const fn #field() -> ::mojo_sdk::StateField<Self, #ty> {
+                        ::mojo_sdk::StateField::new(::core::mem::offset_of!(Self, #ident))
+                    }
+                })
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(quote! {
+        // every field has to be plain old data
+        const _: fn() = || {
+            fn assert_pod<T: ::mojo_sdk::__private::bytemuck::Pod>() {}
+            #(assert_pod::<#field_types>();)*
+        };
+
+        // the struct must not contain uninitialized padding bytes
+        const _: () = ::core::assert!(
+            ::core::mem::size_of::<#name>() == 0 #(+ ::core::mem::size_of::<#field_types>())*,
+            #padding_message
+        );
+
+        unsafe impl ::mojo_sdk::__private::bytemuck::Zeroable for #name {}
+        unsafe impl ::mojo_sdk::__private::bytemuck::Pod for #name {}
+
+        impl ::mojo_sdk::MojoState for #name {
+            const STATE_TYPE_ID: u8 = #state_type_id;
+            const SCHEMA_VERSION: u8 = #schema_version;
+            const SCHEMA_HASH: [u8; 8] = ::mojo_sdk::schema_hash(#layout);
+
+            fn serialize(&self) -> ::core::result::Result<Vec<u8>, ::mojo_sdk::MojoSDKError> {
+                Ok(::mojo_sdk::__private::bytemuck::bytes_of(self).to_vec())
+            }
+
+            fn deserialize(data: &[u8]) -> ::core::result::Result<Self, ::mojo_sdk::MojoSDKError> {
+                ::mojo_sdk::__private::bytemuck::try_pod_read_unaligned(data).map_err(|e| {
+                    ::mojo_sdk::MojoSDKError::Deserialization(format!(
+                        "Failed to deserialize {}: {}",
+                        stringify!(#name),
+                        e
+                    ))
+                })
+            }
+
+            #migrate_from
+        }
+
+        impl #name {
+            #type_id
+            #seed_helpers
+            #(#field_helpers)*
+        }
+    })
+}
@@ -0,0 +1,55 @@
+use pinocchio::program_error::ProgramError;
+
+/// Errors returned by the Mojo program as `ProgramError::Custom(code)`
+///
+/// The codes are part of the program interface and are decoded by `mojo-sdk`,
+/// never renumber a variant, only append new ones. They start at 6000, clear of the
+/// custom codes of the programs the Mojo program calls into.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MojoError {
+    /// The account does not match the PDA derived from the instruction seeds
+    InvalidPda = 6000,
+    /// The account or the supplied state does not have the expected size
+    InvalidAccountSize = 6001,
+    /// The creator did not sign the transaction
+    MissingSigner = 6002,
+    /// The account to create already holds data
+    AlreadyInitialized = 6003,
+    /// The account is not delegated to an ephemeral rollup
+    NotDelegated = 6004,
+    /// The instruction discriminator is not known to the program
+    UnknownInstruction = 6005,
+    /// The instruction data is too short or malformed
+    InvalidInstructionData = 6006,
+    /// The account to update has not been created yet
+    UninitializedAccount = 6007,
+    /// The state belongs to another world
+    WorldMismatch = 6008,
+    /// The delegation ran past its lifetime and can only be undelegated
+    DelegationExpired = 6009,
+    /// The signer may not act on this account
+    Unauthorized = 6010,
+    /// The account is delegated to an ephemeral rollup
+    AccountDelegated = 6011,
+    /// A patch segment or arithmetic field reaches past the end of the state
+    PatchOutOfBounds = 6012,
+    /// The state was written since the version the update expected
+    VersionConflict = 6013,
+    /// An arithmetic result does not fit its field
+    ArithmeticOverflow = 6014,
+    /// The access list of the shared state holds no more writers
+    AclFull = 6015,
+    /// The session key is past its expiry slot
+    SessionExpired = 6016,
+    /// The session key used up the actions it was allowed
+    SessionLimitReached = 6017,
+    /// The migration does not move the state to a newer schema of its type
+    InvalidSchema = 6018,
+}
+
+impl From<MojoError> for ProgramError {
+    fn from(error: MojoError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
@@ -0,0 +1,120 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    errors::MojoError,
+    instructions::check_state_write,
+    state::{GenIxHandler, StateHeader, ACTION_ARITHMETIC},
+};
+
+/// Size of an operation header: [op u8][integer type u8][offset u32 LE]
+const OP_HEADER_LEN: usize = 1 + 1 + 4;
+
+/// Applies checked arithmetic to integer fields of a state in place
+///
+/// Every operation reads the little endian integer at its offset, combines it with the
+/// operand and writes the result back, so concurrent writers never lose each other's
+/// updates. Results that do not fit the field fail the whole instruction.
+///
+/// Operations: 0 add, 1 sub, 2 min, 3 max, 4 set
+///
+/// Integer types: 0 u8, 1 u16, 2 u32, 3 u64, 4 i8, 5 i16, 6 i32, 7 i64
+///
+/// Accounts: [owner or their session key, state, world, session of the key,
+/// access list and membership proof of a shared state..]
+///
+/// Instruction data: [GenIxHandler]([op u8][integer type u8][offset u32 LE][operand])*
+pub fn process_arithmetic(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [creator, account_to_update, world, auth_accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let (mojo_ser_data, mut ops) = GenIxHandler::parse(data)?;
+    if ops.is_empty() {
+        return Err(MojoError::InvalidInstructionData.into());
+    }
+
+    check_state_write(
+        creator,
+        account_to_update,
+        world,
+        auth_accounts,
+        &mojo_ser_data.seeds,
+        None,
+        ACTION_ARITHMETIC,
+    )?;
+
+    let mut account_data = account_to_update.try_borrow_mut_data()?;
+    let (header_bytes, state) = account_data.split_at_mut(StateHeader::LEN);
+    bytemuck::from_bytes_mut::<StateHeader>(header_bytes).record_write(Clock::get()?.slot);
+
+    while !ops.is_empty() {
+        if ops.len() < OP_HEADER_LEN {
+            return Err(MojoError::InvalidInstructionData.into());
+        }
+        let (header, rest) = ops.split_at(OP_HEADER_LEN);
+        let (width, signed) = integer_type(header[1])?;
+        let offset = u32::from_le_bytes([header[2], header[3], header[4], header[5]]) as usize;
+
+        if rest.len() < width {
+            return Err(MojoError::InvalidInstructionData.into());
+        }
+        let (operand, rest) = rest.split_at(width);
+
+        let field = offset
+            .checked_add(width)
+            .and_then(|end| state.get_mut(offset..end))
+            .ok_or(MojoError::PatchOutOfBounds)?;
+
+        let current = read_integer(field, signed);
+        let operand = read_integer(operand, signed);
+        let result = match header[0] {
+            0 => current.checked_add(operand),
+            1 => current.checked_sub(operand),
+            2 => Some(current.min(operand)),
+            3 => Some(current.max(operand)),
+            4 => Some(operand),
+            _ => return Err(MojoError::InvalidInstructionData.into()),
+        };
+
+        let (min, max) = integer_range(width, signed);
+        let result = result
+            .filter(|result| (min..=max).contains(result))
+            .ok_or(MojoError::ArithmeticOverflow)?;
+        field.copy_from_slice(&result.to_le_bytes()[..width]);
+
+        ops = rest;
+    }
+
+    Ok(())
+}
+
+/// Width in bytes and signedness of an integer type tag
+fn integer_type(tag: u8) -> Result<(usize, bool), ProgramError> {
+    if tag > 7 {
+        return Err(MojoError::InvalidInstructionData.into());
+    }
+    Ok((1 << (tag % 4), tag >= 4))
+}
+
+/// Widen a little endian integer of up to 8 bytes
+fn read_integer(bytes: &[u8], signed: bool) -> i128 {
+    let negative = signed && bytes[bytes.len() - 1] & 0x80 != 0;
+    let mut wide = if negative { [0xff; 16] } else { [0; 16] };
+    wide[..bytes.len()].copy_from_slice(bytes);
+    i128::from_le_bytes(wide)
+}
+
+/// Smallest and largest value an integer of `width` bytes holds
+fn integer_range(width: usize, signed: bool) -> (i128, i128) {
+    let bits = width as u32 * 8;
+    if signed {
+        (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+    } else {
+        (0, (1i128 << bits) - 1)
+    }
+}
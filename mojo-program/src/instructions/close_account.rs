@@ -0,0 +1,186 @@
+use ephemeral_rollups_pinocchio::consts::DELEGATION_PROGRAM_ID;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey, ProgramResult};
+
+use crate::{
+    errors::MojoError,
+    state::{
+        check_pda, check_world_pda, read_acl, read_world_header, GenIxHandler, StateHeader,
+        WorldHeader, ACL_SEED,
+    },
+};
+
+/// Closes a state or a world, zeroing its data and sending its rent to the recipient
+///
+/// A state is closed by its owner or by the creator of its world, who can only send the rent
+/// of a player's state back to that player. A world is closed by its creator together with the
+/// states passed after the recipient, it is only closed once none of its states are left, so a
+/// large world can be emptied over several transactions. Delegated accounts are refused. A
+/// shared state is only closed along with its access list.
+///
+/// Accounts: [signer, account, world (the account itself when closing a world), recipient,
+/// states of the world to close along with it, each shared one followed by its access list and
+/// each state of another player by that player... | access list of a shared state]
+///
+/// Instruction data: [GenIxHandler]
+pub fn process_close_account(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [signer, account, world, recipient, children @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !signer.is_signer() {
+        return Err(MojoError::MissingSigner.into());
+    }
+    if account.data_is_empty() {
+        return Err(MojoError::UninitializedAccount.into());
+    }
+    if recipient.key() == account.key() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (mojo_ser_data, _) = GenIxHandler::parse(data)?;
+    let seeds_data = &mojo_ser_data.seeds;
+
+    // a delegated world is owned by the delegation program, or flagged as such on the rollup
+    check_not_delegated(world)?;
+    let world_header = read_world_header(world)?;
+    if world_header.is_delegated() {
+        return Err(MojoError::AccountDelegated.into());
+    }
+
+    if account.key() == world.key() {
+        let seeds: [&[u8]; 2] = [seeds_data, world_header.creator.as_ref()];
+        check_pda(world.key(), &seeds, world_header.bump)?;
+        if signer.key() != &world_header.creator {
+            return Err(MojoError::Unauthorized.into());
+        }
+
+        let mut closed = 0u64;
+        let mut rest = children;
+        while let [child, tail @ ..] = rest {
+            // a state passed twice would be counted twice but closed once
+            let seen = &children[..children.len() - rest.len()];
+            if seen.iter().any(|account| account.key() == child.key()) {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let header = read_state_header(child)?;
+            if &header.world != world.key() {
+                return Err(MojoError::WorldMismatch.into());
+            }
+
+            rest = tail;
+            let refund = if &header.owner == world.key() {
+                let [acl, tail @ ..] = rest else {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                };
+                close_acl(acl, child, recipient)?;
+                rest = tail;
+                recipient
+            } else if &header.owner == signer.key() {
+                recipient
+            } else {
+                // the rent of a player's state goes back to the player
+                let [owner, tail @ ..] = rest else {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                };
+                if owner.key() != &header.owner {
+                    return Err(MojoError::Unauthorized.into());
+                }
+                rest = tail;
+                owner
+            };
+            close_into(child, refund)?;
+            closed += 1;
+        }
+
+        let state_count = world_header
+            .state_count()
+            .checked_sub(closed)
+            .ok_or(MojoError::ArithmeticOverflow)?;
+        if state_count > 0 {
+            set_state_count(world, state_count)?;
+        } else {
+            close_into(world, recipient)?;
+        }
+        return Ok(());
+    }
+
+    let header = read_state_header(account)?;
+    let seeds: [&[u8]; 3] = [seeds_data, world.key().as_ref(), header.owner.as_ref()];
+    check_pda(account.key(), &seeds, header.bump)?;
+    if signer.key() != &header.owner && signer.key() != &world_header.creator {
+        return Err(MojoError::Unauthorized.into());
+    }
+    // the creator closes a player's state only to refund that player
+    let is_player_state = &header.owner != world.key();
+    if is_player_state && signer.key() != &header.owner && recipient.key() != &header.owner {
+        return Err(MojoError::Unauthorized.into());
+    }
+
+    if &header.owner == world.key() {
+        let [acl, ..] = children else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        close_acl(acl, account, recipient)?;
+    }
+
+    check_world_pda(world, &world_header)?;
+    let state_count = world_header
+        .state_count()
+        .checked_sub(1)
+        .ok_or(MojoError::ArithmeticOverflow)?;
+    set_state_count(world, state_count)?;
+    close_into(account, recipient)
+}
+
+/// Close the access list of the shared `state`, a shared state never goes without it
+fn close_acl(acl: &AccountInfo, state: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+    let acl_seeds: [&[u8]; 2] = [ACL_SEED, state.key().as_ref()];
+    if &pubkey::find_program_address(&acl_seeds, &crate::ID).0 != acl.key() {
+        return Err(MojoError::InvalidPda.into());
+    }
+    read_acl(acl, state.key())?;
+    close_into(acl, recipient)
+}
+
+/// Refuses accounts that live on an ephemeral rollup or do not belong to this program
+fn check_not_delegated(account: &AccountInfo) -> ProgramResult {
+    if account.is_owned_by(&DELEGATION_PROGRAM_ID) {
+        return Err(MojoError::AccountDelegated.into());
+    }
+    if !account.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
+/// Header of a state account that is not delegated
+fn read_state_header(state: &AccountInfo) -> Result<StateHeader, ProgramError> {
+    check_not_delegated(state)?;
+
+    let state_data = state.try_borrow_data()?;
+    if state_data.len() < StateHeader::LEN {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+    let header = *bytemuck::from_bytes::<StateHeader>(&state_data[..StateHeader::LEN]);
+    if header.is_delegated() {
+        return Err(MojoError::AccountDelegated.into());
+    }
+    Ok(header)
+}
+
+fn set_state_count(world: &AccountInfo, state_count: u64) -> ProgramResult {
+    let mut world_data = world.try_borrow_mut_data()?;
+    bytemuck::from_bytes_mut::<WorldHeader>(&mut world_data[..WorldHeader::LEN])
+        .set_state_count(state_count);
+    Ok(())
+}
+
+/// Zero the data of `account` and move all of its lamports to `recipient`
+pub(crate) fn close_into(account: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+    account.try_borrow_mut_data()?.fill(0);
+
+    *recipient.try_borrow_mut_lamports()? += account.lamports();
+    *account.try_borrow_mut_lamports()? = 0;
+
+    account.close()
+}
@@ -3,23 +3,27 @@ use ephemeral_rollups_pinocchio::{
     instruction::commit_accounts,
 };
 
-use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey, ProgramResult};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
 use pinocchio_log::log;
 
-use crate::state::GenIxHandler;
+use crate::{
+    errors::MojoError,
+    state::{check_pda, is_world_delegated, read_pda_bump, GenIxHandler},
+};
 
 pub fn process_commit_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
     // need to discuss , how to handle magic context and magic program
-    let [creator, creator_account, magic_context, magic_program, rest] = accounts else {
+    let [creator, creator_account, world, magic_context, magic_program, _rest @ ..] = accounts
+    else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
     // checking if creator is the signer
     if !creator.is_signer() {
-        return Err(ProgramError::MissingRequiredSignature);
+        return Err(MojoError::MissingSigner.into());
     }
 
     // validating magic_context has correct key
@@ -34,29 +38,32 @@ pub fn process_commit_instruction(
 
     // checking that creator_account pda should not be empty
     if creator_account.data_is_empty() {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(MojoError::UninitializedAccount.into());
     }
 
     // parse GenIxHandler from instruction data
-    if instruction_data.len() < GenIxHandler::LEN {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    let mojo_data = &instruction_data[0..GenIxHandler::LEN];
-    let mojo_ser_data = bytemuck::try_pod_read_unaligned::<GenIxHandler>(mojo_data)
-        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let (mojo_ser_data, _) = GenIxHandler::parse(instruction_data)?;
 
+    // a world is derived from [seed hash, creator], its states from [seed hash, world, creator]
     let seeds_data = &mojo_ser_data.seeds;
-    // let seed_bump = [bump];
-    let seeds = &[seeds_data, creator.key().as_ref()];
+    let world_seeds: [&[u8]; 2] = [seeds_data, creator.key().as_ref()];
+    let state_seeds: [&[u8]; 3] = [seeds_data, world.key().as_ref(), creator.key().as_ref()];
+    let is_world = creator_account.key() == world.key();
+    let seeds: &[&[u8]] = if is_world { &world_seeds } else { &state_seeds };
 
-    let (derived_pda, bump) = pubkey::find_program_address(seeds, &crate::id());
+    check_pda(
+        creator_account.key(),
+        seeds,
+        read_pda_bump(creator_account, is_world)?,
+    )?;
 
-    if creator_account.key() != &derived_pda {
-        return Err(ProgramError::InvalidSeeds);
+    // only a world living on the rollup can be committed
+    if is_world && !is_world_delegated(creator_account)? {
+        return Err(MojoError::NotDelegated.into());
     }
 
     {
-        let mut some_fist_account = creator_account.try_borrow_mut_data().unwrap();
+        let some_fist_account = creator_account.try_borrow_data()?;
 
         log!("current data is {}", some_fist_account.as_ref());
     }
@@ -0,0 +1,92 @@
+use ephemeral_rollups_pinocchio::{
+    consts::{MAGIC_CONTEXT_ID, MAGIC_PROGRAM_ID},
+    instruction::{commit_accounts, commit_and_undelegate_accounts},
+};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    errors::MojoError,
+    state::{
+        check_pda, is_delegation_expired, is_world_delegated, owner_signed_batch,
+        parse_seed_hashes, read_delegation_header, read_parent_world, read_pda_bump,
+        set_delegation, split_batch_accounts,
+    },
+};
+
+/// Commits several delegated PDAs of one world in a single instruction, handing them back to
+/// the base layer when `undelegate` is set
+///
+/// The owner of every PDA signs, either as the signer or as a co-signer after the PDAs, the world
+/// creator signs for shared states. Expired delegations can be undelegated by anyone.
+///
+/// Accounts: [signer, world, magic context, magic program, pda for each seed hash, co-signing
+/// owners...]
+///
+/// Instruction data: [count: u8][count x seed hash]
+pub fn process_commit_batch(
+    accounts: &[AccountInfo],
+    data: &[u8],
+    undelegate: bool,
+) -> ProgramResult {
+    let [signer, world, magic_context, magic_program, rest @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !signer.is_signer() {
+        return Err(MojoError::MissingSigner.into());
+    }
+    if magic_context.key() != &MAGIC_CONTEXT_ID || magic_program.key() != &MAGIC_PROGRAM_ID {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (seed_hashes, _) = parse_seed_hashes(data)?;
+    let (pdas, co_signers) = split_batch_accounts(rest, seed_hashes.len(), 1)?;
+
+    let world_creator = read_parent_world(world)?.creator;
+    let now = Clock::get()?.unix_timestamp;
+
+    for (seed_hash, pda) in seed_hashes.iter().zip(pdas) {
+        if pda.data_is_empty() {
+            return Err(MojoError::UninitializedAccount.into());
+        }
+
+        let is_world = pda.key() == world.key();
+        let (owner, delegated_until) = read_delegation_header(pda, is_world)?;
+        if is_world && !is_world_delegated(pda)? {
+            return Err(MojoError::NotDelegated.into());
+        }
+
+        // a world is derived from [seed hash, creator], its states from [seed hash, world, owner]
+        let world_seeds: [&[u8]; 2] = [seed_hash, owner.as_ref()];
+        let state_seeds: [&[u8]; 3] = [seed_hash, world.key().as_ref(), owner.as_ref()];
+        let seeds: &[&[u8]] = if is_world { &world_seeds } else { &state_seeds };
+
+        check_pda(pda.key(), seeds, read_pda_bump(pda, is_world)?)?;
+
+        let authorized =
+            owner_signed_batch(&owner, signer, co_signers, world.key(), &world_creator)
+                || (undelegate && is_delegation_expired(delegated_until, now));
+        if !authorized {
+            return Err(MojoError::Unauthorized.into());
+        }
+
+        // the accounts are committed back to the base layer as no longer delegated
+        if undelegate {
+            set_delegation(pda, is_world, false, 0)?;
+        }
+    }
+
+    if undelegate {
+        commit_and_undelegate_accounts(signer, pdas, magic_context, magic_program)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+    } else {
+        commit_accounts(signer, pdas, magic_context, magic_program)?;
+    }
+
+    Ok(())
+}
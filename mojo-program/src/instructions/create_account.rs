@@ -1,61 +1,147 @@
 use pinocchio::{
     account_info::AccountInfo,
     instruction::Signer,
-    pubkey, seeds,
-    sysvars::{rent::Rent, Sysvar},
+    program_error::ProgramError,
+    pubkey::{self, Pubkey},
+    seeds,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
     ProgramResult,
 };
 
 use pinocchio_system::instructions::CreateAccount;
 
-use crate::state::GenIxHandler;
+use crate::{
+    errors::MojoError,
+    state::{
+        check_world_account, check_world_pda, read_world_header, GenIxHandler, StateHeader,
+        StateSchema, WorldHeader,
+    },
+};
 
+/// Creates the state of the signing owner in a world
+///
+/// The rent is paid by the payer following the rent sysvar when there is one, so a sponsor can
+/// onboard players holding no SOL, otherwise by the owner. States can not be created in a
+/// world delegated from the base layer, they are created before delegating it.
+///
+/// Accounts: [owner, state, world, system program, rent sysvar, payer, optional]
+///
+/// Instruction data: [GenIxHandler][schema flag u8][StateSchema, if flagged][state]
 pub fn create_state_account(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     // get all accounts
-    let [creator, account_to_create, _system_program, _rent_sysvar @ ..] = accounts else {
-        return Err(pinocchio::program_error::ProgramError::NotEnoughAccountKeys);
+    let [creator, account_to_create, world, _system_program, rest @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    let mojo_data = &data[0..GenIxHandler::LEN];
-    let mojo_ser_data = bytemuck::from_bytes::<GenIxHandler>(mojo_data);
+    let (mojo_ser_data, payload) = GenIxHandler::parse(data)?;
+    let (schema, state_data) = StateSchema::split(payload)?;
 
     // checks
     // check that maker is a signer ✅
-    assert!(&creator.is_signer(), "Creator should be a signer");
+    if !creator.is_signer() {
+        return Err(MojoError::MissingSigner.into());
+    }
+    let payer = resolve_payer(creator, rest.get(1))?;
     // check that account_to_create is empty
-    assert!(
-        &account_to_create.data_is_empty(),
-        "Account should be empty"
-    );
+    if !account_to_create.data_is_empty() {
+        return Err(MojoError::AlreadyInitialized.into());
+    }
+    // check that the parent world exists
+    check_world_account(world)?;
+    // check that the state matches the size it declares
+    if state_data.len() != mojo_ser_data.state_size() {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
 
+    // state PDAs are bound to their parent world: [seed hash, world, creator]
+    // the canonical bump is only searched here, the header keeps it for later instructions
     let seeds_data = &mojo_ser_data.seeds;
-    // let seed_bump = [bump];
-    let seeds = &[seeds_data, creator.key().as_ref()];
+    let seeds = &[seeds_data, world.key().as_ref(), creator.key().as_ref()];
 
     let (derived_pda, bump) = pubkey::find_program_address(seeds, &crate::id());
+    if &derived_pda != account_to_create.key() {
+        return Err(MojoError::InvalidPda.into());
+    }
+
+    init_state_account(
+        payer,
+        account_to_create,
+        world,
+        creator.key(),
+        seeds_data,
+        bump,
+        &schema.unwrap_or_default(),
+        state_data,
+    )
+}
+
+/// The account paying for an instruction: `payer` when one was passed, `owner` otherwise
+pub(crate) fn resolve_payer<'a>(
+    owner: &'a AccountInfo,
+    payer: Option<&'a AccountInfo>,
+) -> Result<&'a AccountInfo, ProgramError> {
+    let payer = payer.unwrap_or(owner);
+    if !payer.is_signer() {
+        return Err(MojoError::MissingSigner.into());
+    }
+    Ok(payer)
+}
+
+/// Creates `account` as the state of `owner` in `world`, funded by `payer`
+///
+/// `account` must be the PDA of [seed hash, world, owner] with `bump`, the world counts it, so
+/// no state is created in a world delegated from the base layer.
+/// The header records the type of `schema`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn init_state_account(
+    payer: &AccountInfo,
+    account_to_create: &AccountInfo,
+    world: &AccountInfo,
+    owner: &Pubkey,
+    seeds_data: &[u8; 32],
+    bump: u8,
+    schema: &StateSchema,
+    state_data: &[u8],
+) -> ProgramResult {
+    // the world counts its states, which it can not while the delegation program owns it
+    if !world.is_owned_by(&crate::ID) {
+        return Err(MojoError::AccountDelegated.into());
+    }
+    // it has to be the genuine world PDA
+    check_world_pda(world, &read_world_header(world)?)?;
+
     let bump_binding = [bump];
-    // let seeds = seeds!(seed1, seed2, seed3, seed4, seed5, &seed_bump);
-    let signer_seeds = seeds!(seeds_data, creator.key().as_ref(), &bump_binding);
+    let signer_seeds = seeds!(
+        seeds_data,
+        world.key().as_ref(),
+        owner.as_ref(),
+        &bump_binding
+    );
     let signers: [Signer<'_, '_>; 1] = [Signer::from(&signer_seeds[..])];
 
-    assert_eq!(
-        &derived_pda,
-        account_to_create.key(),
-        "You provided the wrong user pda"
-    );
+    // the state is prefixed with a header binding it to its world and owner
+    let space = StateHeader::LEN + state_data.len();
 
     CreateAccount {
-        from: creator,
-        lamports: Rent::get()?.minimum_balance(usize::from_le_bytes(mojo_ser_data.size)),
+        from: payer,
+        lamports: Rent::get()?.minimum_balance(space),
         owner: &crate::ID,
-        space: u64::from_le_bytes(mojo_ser_data.size),
-        to: &*account_to_create,
+        space: space as u64,
+        to: account_to_create,
     }
     .invoke_signed(&signers)?;
 
-    let mut some_fist_account = account_to_create.try_borrow_mut_data().unwrap();
+    let header = StateHeader::new(world.key(), owner, bump, schema, Clock::get()?.slot);
+
+    let mut some_fist_account = account_to_create.try_borrow_mut_data()?;
 
     // this will modify the account state
-    some_fist_account.copy_from_slice(&data[GenIxHandler::LEN..]);
+    some_fist_account[..StateHeader::LEN].copy_from_slice(bytemuck::bytes_of(&header));
+    some_fist_account[StateHeader::LEN..].copy_from_slice(state_data);
+
+    // keep track of how many states live under the world
+    let mut world_data = world.try_borrow_mut_data()?;
+    let world_header = bytemuck::from_bytes_mut::<WorldHeader>(&mut world_data[..WorldHeader::LEN]);
+    world_header.set_state_count(world_header.state_count() + 1);
     Ok(())
 }
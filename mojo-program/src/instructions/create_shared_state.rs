@@ -0,0 +1,96 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    pubkey, seeds,
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::{
+    errors::MojoError,
+    instructions::init_state_account,
+    state::{
+        check_world_account, read_world_header, AccessMode, GenIxHandler, StateAcl, StateSchema,
+        ACL_SEED,
+    },
+};
+
+/// Creates a shared state, owned by its world rather than a player, along with its access list
+///
+/// Only the world creator creates shared states, the access mode decides who else writes them
+/// and the writers are managed with the add and remove writer instructions.
+///
+/// Accounts: [world creator, state, world, access list, system program]
+///
+/// Instruction data: [GenIxHandler][access mode u8][schema flag u8][StateSchema, if flagged][state]
+pub fn process_create_shared_state(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [creator, account_to_create, world, acl, _system_program, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let (mojo_ser_data, payload) = GenIxHandler::parse(data)?;
+    let (mode, payload) = payload
+        .split_first()
+        .ok_or(MojoError::InvalidInstructionData)?;
+    let mode = AccessMode::try_from(*mode)?;
+    let (schema, state_data) = StateSchema::split(payload)?;
+
+    if !creator.is_signer() {
+        return Err(MojoError::MissingSigner.into());
+    }
+    if !account_to_create.data_is_empty() || !acl.data_is_empty() {
+        return Err(MojoError::AlreadyInitialized.into());
+    }
+    check_world_account(world)?;
+    if creator.key() != &read_world_header(world)?.creator {
+        return Err(MojoError::Unauthorized.into());
+    }
+    if state_data.len() != mojo_ser_data.state_size() {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+
+    // shared states stand in the world's own name: [seed hash, world, world]
+    let seeds_data = &mojo_ser_data.seeds;
+    let seeds: [&[u8]; 3] = [seeds_data, world.key().as_ref(), world.key().as_ref()];
+    let (derived_pda, bump) = pubkey::find_program_address(&seeds, &crate::id());
+    if &derived_pda != account_to_create.key() {
+        return Err(MojoError::InvalidPda.into());
+    }
+
+    let acl_seeds: [&[u8]; 2] = [ACL_SEED, account_to_create.key().as_ref()];
+    let (acl_pda, acl_bump) = pubkey::find_program_address(&acl_seeds, &crate::id());
+    if &acl_pda != acl.key() {
+        return Err(MojoError::InvalidPda.into());
+    }
+
+    init_state_account(
+        creator,
+        account_to_create,
+        world,
+        world.key(),
+        seeds_data,
+        bump,
+        &schema.unwrap_or_default(),
+        state_data,
+    )?;
+
+    let bump_binding = [acl_bump];
+    let signer_seeds = seeds!(ACL_SEED, account_to_create.key().as_ref(), &bump_binding);
+    CreateAccount {
+        from: creator,
+        lamports: Rent::get()?.minimum_balance(StateAcl::LEN),
+        owner: &crate::ID,
+        space: StateAcl::LEN as u64,
+        to: acl,
+    }
+    .invoke_signed(&[Signer::from(&signer_seeds[..])])?;
+
+    let mut acl_data = acl.try_borrow_mut_data()?;
+    let acl = bytemuck::from_bytes_mut::<StateAcl>(&mut acl_data);
+    acl.world = *world.key();
+    acl.state = *account_to_create.key();
+    acl.mode = mode as u8;
+    Ok(())
+}
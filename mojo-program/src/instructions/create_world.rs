@@ -0,0 +1,89 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    pubkey, seeds,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::{
+    errors::MojoError,
+    state::{GenIxHandler, WorldHeader, WORLD_MAGIC},
+};
+
+/// Creates a World PDA laid out as [WorldHeader][initial world state]
+///
+/// Instruction data: [GenIxHandler][config: 8 bytes][initial world state]
+pub fn create_world_account(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    // get all accounts
+    let [creator, world_account, _system_program, _rent_sysvar @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // checking if creator is the signer
+    if !creator.is_signer() {
+        return Err(MojoError::MissingSigner.into());
+    }
+
+    // a world can only be created once
+    if !world_account.data_is_empty() {
+        return Err(MojoError::AlreadyInitialized.into());
+    }
+
+    // parse GenIxHandler and the world config from instruction data
+    let (mojo_ser_data, rest) = GenIxHandler::parse(data)?;
+    let (config, world_state) = rest
+        .split_first_chunk::<8>()
+        .ok_or(MojoError::InvalidInstructionData)?;
+    let config = *config;
+
+    let state_size = mojo_ser_data.state_size();
+    if world_state.len() != state_size {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+
+    // the canonical bump is only searched here, the header keeps it for later instructions
+    let seeds_data = &mojo_ser_data.seeds;
+    let seeds = &[seeds_data, creator.key().as_ref()];
+
+    let (derived_pda, bump) = pubkey::find_program_address(seeds, &crate::id());
+    if world_account.key() != &derived_pda {
+        return Err(MojoError::InvalidPda.into());
+    }
+
+    let bump_binding = [bump];
+    let signer_seeds = seeds!(seeds_data, creator.key().as_ref(), &bump_binding);
+    let signers: [Signer<'_, '_>; 1] = [Signer::from(&signer_seeds[..])];
+
+    let space = WorldHeader::LEN + state_size;
+
+    CreateAccount {
+        from: creator,
+        lamports: Rent::get()?.minimum_balance(space),
+        owner: &crate::ID,
+        space: space as u64,
+        to: world_account,
+    }
+    .invoke_signed(&signers)?;
+
+    let header = WorldHeader {
+        magic: WORLD_MAGIC,
+        creator: *creator.key(),
+        name_hash: *seeds_data,
+        created_at: Clock::get()?.unix_timestamp.to_le_bytes(),
+        state_count: 0u64.to_le_bytes(),
+        config,
+        is_delegated: 0,
+        delegated_until: 0i64.to_le_bytes(),
+        bump,
+    };
+
+    let mut world_data = world_account.try_borrow_mut_data()?;
+    world_data[..WorldHeader::LEN].copy_from_slice(bytemuck::bytes_of(&header));
+    world_data[WorldHeader::LEN..].copy_from_slice(world_state);
+
+    Ok(())
+}
@@ -1,4 +1,10 @@
-use crate::state::GenIxHandler;
+use crate::{
+    errors::MojoError,
+    instructions::resolve_payer,
+    state::{
+        check_pda, read_parent_world, read_pda_bump, set_delegation, DelegationArgs, GenIxHandler,
+    },
+};
 use ephemeral_rollups_pinocchio::pda::delegation_metadata_pda_from_delegated_account;
 use ephemeral_rollups_pinocchio::{
     consts::{BUFFER, DELEGATION_PROGRAM_ID},
@@ -10,8 +16,6 @@ use pinocchio::{
     account_info::AccountInfo,
     instruction::{Seed, Signer},
     program_error::ProgramError,
-    pubkey,
-    pubkey::find_program_address,
     seeds,
     sysvars::{rent::Rent, Sysvar},
     ProgramResult,
@@ -26,46 +30,60 @@ pub fn process_delegate_account(
     instruction_data: &[u8],
 ) -> ProgramResult {
     // 0xAbim: Here goes the accounts to be delegated.
-    // 0. [] The creator acts as the payer
+    // 0. [] The creator, paying unless a payer follows the delegation program
     // 1. [] the account pda (is_writable)
-    // 2. [] the owner' program
-    // 3. [] the buffer account
-    // 4. [] the delegation record
-    // 5. [] the delegation metadata
-    // 6. [] System Program + ...Other essential accounts...
+    // 2. [] the parent world pda (the account pda itself when delegating a world)
+    // 3. [] the owner' program
+    // 4. [] the buffer account
+    // 5. [] the delegation record
+    // 6. [] the delegation metadata
+    // 7. [] System Program + ...Other essential accounts...
+    // 9. [] The payer of the delegation accounts, optional
+    //
+    // Instruction data: [GenIxHandler][DelegationArgs, optional]
 
-    let [creator, creator_account, owner_program, buffer_account, delegation_record, delegation_metadata, validator, rest @ ..] =
+    let [creator, creator_account, world, owner_program, buffer_account, delegation_record, delegation_metadata, rest @ ..] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    // Read GenIxHandler from instruction data
-    if instruction_data.len() < GenIxHandler::LEN {
-        return Err(ProgramError::InvalidAccountData);
+    // checking if creator is the signer
+    if !creator.is_signer() {
+        return Err(MojoError::MissingSigner.into());
     }
+    // rest holds the system and the delegation program ahead of the payer
+    let payer = resolve_payer(creator, rest.get(2))?;
 
-    let mojo_data = &instruction_data[0..GenIxHandler::LEN];
-    let mojo_ser_data = bytemuck::from_bytes::<GenIxHandler>(mojo_data);
+    // Read GenIxHandler and the delegation settings from instruction data
+    let (mojo_ser_data, args_data) = GenIxHandler::parse(instruction_data)?;
+    let args = DelegationArgs::parse(args_data)?;
 
+    // a world is derived from [seed hash, creator], its states from [seed hash, world, creator]
     let seeds_data = &mojo_ser_data.seeds;
-    // let seed_bump = [bump];
-    let seeds = &[seeds_data, creator.key().as_ref()];
+    let world_seeds: [&[u8]; 2] = [seeds_data, creator.key().as_ref()];
+    let state_seeds: [&[u8]; 3] = [seeds_data, world.key().as_ref(), creator.key().as_ref()];
+    let is_world = creator_account.key() == world.key();
+    let seeds: &[&[u8]] = if is_world { &world_seeds } else { &state_seeds };
 
-    let (derived_pda, bump) = pubkey::find_program_address(seeds, &crate::id());
+    let bump = read_pda_bump(creator_account, is_world)?;
+    check_pda(creator_account.key(), seeds, bump)?;
 
-    if creator_account.key() != &derived_pda {
-        return Err(ProgramError::InvalidSeeds);
-    }
+    // settings left out of the instruction fall back to the world config
+    let world_header = read_parent_world(world)?;
+    let (commit_frequency_ms, delegated_until) = args.resolve(&world_header)?;
+
+    // the expiry is carried into the rollup along with the rest of the account data
+    set_delegation(creator_account, is_world, true, delegated_until)?;
 
     let config = DelegateConfig {
-        commit_frequency_ms: 30000, // 30 seconds
-        validator: Some(*validator.key()),
+        commit_frequency_ms,
+        validator: args.validator(),
         ..Default::default()
     };
 
     let delegate_accounts = [
-        creator,
+        payer,
         creator_account,
         owner_program,
         buffer_account,
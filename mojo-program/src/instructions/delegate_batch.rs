@@ -0,0 +1,97 @@
+use ephemeral_rollups_pinocchio::{instruction::delegate_account, types::DelegateConfig};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    errors::MojoError,
+    state::{
+        check_pda, owner_signed_batch, parse_seed_hashes, read_delegation_header,
+        read_parent_world, read_pda_bump, set_delegation, split_batch_accounts, DelegationArgs,
+    },
+};
+
+/// Accounts every PDA of a batch delegation brings along
+const ACCOUNTS_PER_DELEGATION: usize = 4;
+
+/// Delegates several PDAs of one world to the ephemeral rollup in a single instruction
+///
+/// Each PDA is a state of the world or the world itself. The owner of every PDA signs, either as
+/// the signer or as a co-signer after the PDAs, the world creator signs for shared states. The
+/// signer pays for the delegations.
+///
+/// Accounts: [signer, world, owner program, system program, delegation program,
+/// (pda, buffer, delegation record, delegation metadata) for each seed hash, co-signing
+/// owners...]
+///
+/// Instruction data: [count: u8][count x seed hash][DelegationArgs, optional]
+pub fn process_delegate_batch(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [signer, world, owner_program, _system_program, _delegation_program, rest @ ..] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !signer.is_signer() {
+        return Err(MojoError::MissingSigner.into());
+    }
+
+    let (seed_hashes, args_data) = parse_seed_hashes(data)?;
+    let args = DelegationArgs::parse(args_data)?;
+    let (delegations, co_signers) =
+        split_batch_accounts(rest, seed_hashes.len(), ACCOUNTS_PER_DELEGATION)?;
+
+    // every PDA of the batch shares the settings resolved against the world config
+    let world_header = read_parent_world(world)?;
+    let (commit_frequency_ms, delegated_until) = args.resolve(&world_header)?;
+
+    for (seed_hash, delegation) in seed_hashes
+        .iter()
+        .zip(delegations.chunks_exact(ACCOUNTS_PER_DELEGATION))
+    {
+        let [pda, buffer, delegation_record, delegation_metadata] = delegation else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let is_world = pda.key() == world.key();
+        let (owner, _delegated_until) = read_delegation_header(pda, is_world)?;
+        if !owner_signed_batch(
+            &owner,
+            signer,
+            co_signers,
+            world.key(),
+            &world_header.creator,
+        ) {
+            return Err(MojoError::Unauthorized.into());
+        }
+
+        // a world is derived from [seed hash, creator], its states from [seed hash, world, owner]
+        let world_seeds: [&[u8]; 2] = [seed_hash, owner.as_ref()];
+        let state_seeds: [&[u8]; 3] = [seed_hash, world.key().as_ref(), owner.as_ref()];
+        let seeds: &[&[u8]] = if is_world { &world_seeds } else { &state_seeds };
+
+        let bump = read_pda_bump(pda, is_world)?;
+        check_pda(pda.key(), seeds, bump)?;
+
+        // the expiry is carried into the rollup along with the rest of the account data
+        set_delegation(pda, is_world, true, delegated_until)?;
+
+        let config = DelegateConfig {
+            commit_frequency_ms,
+            validator: args.validator(),
+            ..Default::default()
+        };
+        delegate_account(
+            &[
+                signer,
+                pda,
+                owner_program,
+                buffer,
+                delegation_record,
+                delegation_metadata,
+            ],
+            seeds,
+            bump,
+            config,
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    pubkey, seeds,
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::{
+    errors::MojoError,
+    instructions::close_into,
+    state::{
+        check_world_account, is_session_account, is_world_delegated, SessionToken, SESSION_MAGIC,
+        SESSION_SEED,
+    },
+};
+
+/// Authorizes a session key to write the states of the owner in a world
+///
+/// An owner holds one session per world, creating it again rotates the key and restarts its
+/// action count. The owner pays the rent of the session.
+///
+/// Sessions stay on the base layer, only unlimited sessions may be created in a delegated
+/// world since a limited one could not count its writes on the ephemeral rollup.
+///
+/// Accounts: [owner, session, world, system program]
+///
+/// Instruction data: [session key][expires at slot u64 LE][max actions u64 LE, 0 for no
+/// limit][allowed actions u8]
+pub fn process_create_session(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [owner, session, world, _system_program, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let (session_key, args) = data
+        .split_first_chunk::<32>()
+        .ok_or(MojoError::InvalidInstructionData)?;
+    let (expires_at, args) = args
+        .split_first_chunk::<8>()
+        .ok_or(MojoError::InvalidInstructionData)?;
+    let (max_actions, args) = args
+        .split_first_chunk::<8>()
+        .ok_or(MojoError::InvalidInstructionData)?;
+    let &[allowed_actions] = args else {
+        return Err(MojoError::InvalidInstructionData.into());
+    };
+
+    if !owner.is_signer() {
+        return Err(MojoError::MissingSigner.into());
+    }
+    check_world_account(world)?;
+    if u64::from_le_bytes(*max_actions) > 0 && is_world_delegated(world)? {
+        return Err(MojoError::AccountDelegated.into());
+    }
+
+    let seeds: [&[u8]; 3] = [SESSION_SEED, world.key().as_ref(), owner.key().as_ref()];
+    let (derived_pda, bump) = pubkey::find_program_address(&seeds, &crate::id());
+    if &derived_pda != session.key() {
+        return Err(MojoError::InvalidPda.into());
+    }
+
+    if session.data_is_empty() {
+        let bump_binding = [bump];
+        let signer_seeds = seeds!(
+            SESSION_SEED,
+            world.key().as_ref(),
+            owner.key().as_ref(),
+            &bump_binding
+        );
+        CreateAccount {
+            from: owner,
+            lamports: Rent::get()?.minimum_balance(SessionToken::LEN),
+            owner: &crate::ID,
+            space: SessionToken::LEN as u64,
+            to: session,
+        }
+        .invoke_signed(&[Signer::from(&signer_seeds[..])])?;
+    } else if !is_session_account(session) {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+
+    let mut session_data = session.try_borrow_mut_data()?;
+    let token = bytemuck::from_bytes_mut::<SessionToken>(&mut session_data);
+    token.magic = SESSION_MAGIC;
+    token.world = *world.key();
+    token.owner = *owner.key();
+    token.session_key = *session_key;
+    token.expires_at = *expires_at;
+    token.max_actions = *max_actions;
+    token.set_actions_used(0);
+    token.allowed_actions = allowed_actions;
+    token.bump = bump;
+    Ok(())
+}
+
+/// Revokes a session, returning its rent to the owner
+///
+/// Either the owner or the session key itself may revoke it.
+///
+/// Accounts: [owner or session key, session, owner]
+pub fn process_revoke_session(accounts: &[AccountInfo]) -> ProgramResult {
+    let [signer, session, owner, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !signer.is_signer() {
+        return Err(MojoError::MissingSigner.into());
+    }
+    if !session.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if !is_session_account(session) {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+
+    let token = *bytemuck::from_bytes::<SessionToken>(&session.try_borrow_data()?);
+    let bump = [token.bump];
+    let seeds: [&[u8]; 4] = [
+        SESSION_SEED,
+        token.world.as_ref(),
+        owner.key().as_ref(),
+        &bump,
+    ];
+    if &pubkey::create_program_address(&seeds, &crate::ID)? != session.key() {
+        return Err(MojoError::InvalidPda.into());
+    }
+    if signer.key() != owner.key() && signer.key() != &token.session_key {
+        return Err(MojoError::Unauthorized.into());
+    }
+
+    close_into(session, owner)
+}
@@ -0,0 +1,44 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    errors::MojoError,
+    state::{read_acl, read_parent_world, StateAcl},
+};
+
+/// Adds a writer to or removes one from the access list of a shared state
+///
+/// Only the world creator manages the list. Adding a listed writer or removing one that
+/// is not listed changes nothing.
+///
+/// Accounts: [world creator, shared state, world, access list]
+///
+/// Instruction data: [writer pubkey]
+pub fn process_manage_writer(accounts: &[AccountInfo], data: &[u8], add: bool) -> ProgramResult {
+    let [creator, state, world, acl, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let writer: &[u8; 32] = data
+        .try_into()
+        .map_err(|_| MojoError::InvalidInstructionData)?;
+
+    if !creator.is_signer() {
+        return Err(MojoError::MissingSigner.into());
+    }
+    // the list lives on the base layer even while its world is delegated
+    if creator.key() != &read_parent_world(world)?.creator {
+        return Err(MojoError::Unauthorized.into());
+    }
+    if &read_acl(acl, state.key())?.world != world.key() {
+        return Err(MojoError::WorldMismatch.into());
+    }
+
+    let mut acl_data = acl.try_borrow_mut_data()?;
+    let acl = bytemuck::from_bytes_mut::<StateAcl>(&mut acl_data);
+    if add {
+        acl.add_writer(writer)
+    } else {
+        acl.remove_writer(writer);
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    errors::MojoError,
+    instructions::{check_state_resize, realloc_state, resolve_payer},
+    state::{GenIxHandler, StateHeader, StateSchema},
+};
+
+/// Moves a state to a newer schema version of its type
+///
+/// The owner rewrites the whole state as the new schema lays it out, the account is resized
+/// to fit it and the header records the schema along with the write. Untyped states may adopt
+/// any schema, typed states only newer versions of their own type. The rent difference is
+/// settled with the payer like a resize, delegated states have to be undelegated first.
+///
+/// Accounts: [owner, state, world, system program, payer, optional]
+///
+/// Instruction data: [GenIxHandler][SCHEMA_PRESENT][StateSchema][migrated state]
+pub fn process_migrate_account(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [owner, account_to_migrate, world, _system_program, rest @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let (mojo_ser_data, payload) = GenIxHandler::parse(data)?;
+    let (Some(schema), state_data) = StateSchema::split(payload)? else {
+        return Err(MojoError::InvalidInstructionData.into());
+    };
+
+    let header = check_state_resize(owner, account_to_migrate, world, &mojo_ser_data.seeds)?;
+    if !header.schema().migrates_to(&schema) {
+        return Err(MojoError::InvalidSchema.into());
+    }
+    let payer = resolve_payer(owner, rest.first())?;
+    realloc_state(account_to_migrate, payer, state_data.len())?;
+
+    let mut account_data = account_to_migrate.try_borrow_mut_data()?;
+    let (header_bytes, state) = account_data.split_at_mut(StateHeader::LEN);
+    let header = bytemuck::from_bytes_mut::<StateHeader>(header_bytes);
+    header.set_schema(&schema);
+    header.record_write(Clock::get()?.slot);
+    state.copy_from_slice(state_data);
+
+    Ok(())
+}
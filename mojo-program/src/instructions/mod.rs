@@ -13,6 +13,44 @@ pub use commit::*;
 pub mod undelegate_account;
 pub use undelegate_account::*;
 
+pub mod create_world;
+pub use create_world::*;
+
+pub mod undelegate_callback;
+pub use undelegate_callback::*;
+
+pub mod delegate_batch;
+pub use delegate_batch::*;
+
+pub mod commit_batch;
+pub use commit_batch::*;
+
+pub mod close_account;
+pub use close_account::*;
+
+pub mod resize_account;
+pub use resize_account::*;
+
+pub mod patch_account;
+pub use patch_account::*;
+
+pub mod arithmetic;
+pub use arithmetic::*;
+
+pub mod create_shared_state;
+pub use create_shared_state::*;
+
+pub mod manage_writers;
+pub use manage_writers::*;
+
+pub mod manage_session;
+pub use manage_session::*;
+
+pub mod migrate_account;
+pub use migrate_account::*;
+
+use crate::errors::MojoError;
+
 #[repr(u8)]
 pub enum MojoInstructions {
     // Initialize,
@@ -21,6 +59,20 @@ pub enum MojoInstructions {
     Commit,
     UpdateDelegatedAccount,
     UndelegateAccount,
+    CreateWorld,
+    DelegateBatch,
+    CommitBatch,
+    UndelegateBatch,
+    CloseAccount,
+    ResizeAccount,
+    PatchAccount,
+    Arithmetic,
+    CreateSharedState,
+    AddWriter,
+    RemoveWriter,
+    CreateSession,
+    RevokeSession,
+    MigrateAccount,
 }
 
 impl TryFrom<&u8> for MojoInstructions {
@@ -34,7 +86,21 @@ impl TryFrom<&u8> for MojoInstructions {
             2 => Ok(MojoInstructions::Commit),
             3 => Ok(MojoInstructions::UpdateDelegatedAccount),
             4 => Ok(MojoInstructions::UndelegateAccount),
-            _ => Err(pinocchio::program_error::ProgramError::InvalidInstructionData),
+            5 => Ok(MojoInstructions::CreateWorld),
+            6 => Ok(MojoInstructions::DelegateBatch),
+            7 => Ok(MojoInstructions::CommitBatch),
+            8 => Ok(MojoInstructions::UndelegateBatch),
+            9 => Ok(MojoInstructions::CloseAccount),
+            10 => Ok(MojoInstructions::ResizeAccount),
+            11 => Ok(MojoInstructions::PatchAccount),
+            12 => Ok(MojoInstructions::Arithmetic),
+            13 => Ok(MojoInstructions::CreateSharedState),
+            14 => Ok(MojoInstructions::AddWriter),
+            15 => Ok(MojoInstructions::RemoveWriter),
+            16 => Ok(MojoInstructions::CreateSession),
+            17 => Ok(MojoInstructions::RevokeSession),
+            18 => Ok(MojoInstructions::MigrateAccount),
+            _ => Err(MojoError::UnknownInstruction.into()),
         }
     }
 }
@@ -0,0 +1,80 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    errors::MojoError,
+    instructions::check_state_write,
+    state::{GenIxHandler, StateHeader, StateSchema, ACTION_PATCH},
+};
+
+/// Size of a segment header: [offset u32 LE][len u16 LE]
+const SEGMENT_HEADER_LEN: usize = 4 + 2;
+
+/// Writes one or more byte ranges into a state instead of replacing all of it
+///
+/// Offsets are relative to the start of the state, the header stays program managed and
+/// only records the write. Every segment has to fit inside the account, the size of
+/// the GenIxHandler is not used. The schema, when given, has to be the stored one.
+///
+/// Accounts: [owner or their session key, state, world, session of the key,
+/// access list and membership proof of a shared state..]
+///
+/// Instruction data: [GenIxHandler][schema flag u8][StateSchema, if flagged]
+/// ([offset u32 LE][len u16 LE][len bytes])*
+pub fn process_patch_account(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [creator, account_to_patch, world, auth_accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let (mojo_ser_data, payload) = GenIxHandler::parse(data)?;
+    let (schema, mut segments) = StateSchema::split(payload)?;
+    if segments.is_empty() {
+        return Err(MojoError::InvalidInstructionData.into());
+    }
+
+    check_state_write(
+        creator,
+        account_to_patch,
+        world,
+        auth_accounts,
+        &mojo_ser_data.seeds,
+        schema.as_ref(),
+        ACTION_PATCH,
+    )?;
+
+    let mut account_data = account_to_patch.try_borrow_mut_data()?;
+    let (header_bytes, state) = account_data.split_at_mut(StateHeader::LEN);
+    bytemuck::from_bytes_mut::<StateHeader>(header_bytes).record_write(Clock::get()?.slot);
+
+    while !segments.is_empty() {
+        let (offset, bytes, rest) = parse_segment(segments)?;
+        let end = offset
+            .checked_add(bytes.len())
+            .filter(|end| *end <= state.len())
+            .ok_or(MojoError::PatchOutOfBounds)?;
+        state[offset..end].copy_from_slice(bytes);
+        segments = rest;
+    }
+
+    Ok(())
+}
+
+/// Split the next segment off `data`
+fn parse_segment(data: &[u8]) -> Result<(usize, &[u8], &[u8]), ProgramError> {
+    if data.len() < SEGMENT_HEADER_LEN {
+        return Err(MojoError::InvalidInstructionData.into());
+    }
+    let (header, rest) = data.split_at(SEGMENT_HEADER_LEN);
+    let offset = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let len = u16::from_le_bytes([header[4], header[5]]) as usize;
+
+    if rest.len() < len {
+        return Err(MojoError::InvalidInstructionData.into());
+    }
+    let (bytes, rest) = rest.split_at(len);
+    Ok((offset, bytes, rest))
+}
@@ -0,0 +1,124 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::instructions::Transfer;
+
+use crate::{
+    errors::MojoError,
+    instructions::resolve_payer,
+    state::{check_pda, check_world_account, GenIxHandler, StateHeader, StateSchema},
+};
+
+/// Resizes a state account to the size carried by the GenIxHandler
+///
+/// Growing tops the rent up from the payer, shrinking refunds the excess rent to them. The
+/// payer is the owner unless one follows the system program. The new state is written when
+/// it follows the handler, otherwise added bytes are zeroed. Either way the state version is
+/// bumped. Delegated states can not be resized. The schema, when given, has to be the stored
+/// one: moving to a newer schema goes through the migrate instruction instead.
+///
+/// Accounts: [owner, state, world, system program, payer, optional]
+///
+/// Instruction data: [GenIxHandler][schema flag u8][StateSchema of the new state, if flagged]
+/// [new state, optional]
+pub fn process_resize_account(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [owner, account_to_resize, world, _system_program, rest @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let (mojo_ser_data, payload) = GenIxHandler::parse(data)?;
+    let (schema, state_data) = StateSchema::split(payload)?;
+
+    let new_size = mojo_ser_data.state_size();
+    if !state_data.is_empty() && state_data.len() != new_size {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+
+    let header = check_state_resize(owner, account_to_resize, world, &mojo_ser_data.seeds)?;
+    if schema.is_some_and(|schema| schema != header.schema()) {
+        return Err(MojoError::InvalidSchema.into());
+    }
+    let payer = resolve_payer(owner, rest.first())?;
+    realloc_state(account_to_resize, payer, new_size)?;
+
+    let mut account_data = account_to_resize.try_borrow_mut_data()?;
+    let (header_bytes, state) = account_data.split_at_mut(StateHeader::LEN);
+    let header = bytemuck::from_bytes_mut::<StateHeader>(header_bytes);
+    header.record_write(Clock::get()?.slot);
+    if !state_data.is_empty() {
+        state.copy_from_slice(state_data);
+    }
+
+    Ok(())
+}
+
+/// Checks shared by the instructions changing the size of a state, returns its header
+///
+/// The owner signed and the state is their undelegated PDA in `world`
+pub(crate) fn check_state_resize(
+    owner: &AccountInfo,
+    account: &AccountInfo,
+    world: &AccountInfo,
+    seeds_data: &[u8; 32],
+) -> Result<StateHeader, ProgramError> {
+    if !owner.is_signer() {
+        return Err(MojoError::MissingSigner.into());
+    }
+    if account.data_is_empty() {
+        return Err(MojoError::UninitializedAccount.into());
+    }
+    if !account.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    check_world_account(world)?;
+
+    let account_data = account.try_borrow_data()?;
+    if account_data.len() < StateHeader::LEN {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+    let header = *bytemuck::from_bytes::<StateHeader>(&account_data[..StateHeader::LEN]);
+
+    let seeds: [&[u8]; 3] = [seeds_data, world.key().as_ref(), owner.key().as_ref()];
+    check_pda(account.key(), &seeds, header.bump)?;
+    if &header.world != world.key() {
+        return Err(MojoError::WorldMismatch.into());
+    }
+    // the rent of a delegated state lives on the rollup
+    if header.is_delegated() {
+        return Err(MojoError::AccountDelegated.into());
+    }
+    Ok(header)
+}
+
+/// Reallocates `account` to hold `new_size` state bytes after its header
+///
+/// Growing tops the rent up from `payer`, shrinking refunds the excess rent to them
+pub(crate) fn realloc_state(
+    account: &AccountInfo,
+    payer: &AccountInfo,
+    new_size: usize,
+) -> ProgramResult {
+    let space = StateHeader::LEN + new_size;
+    let rent_lamports = Rent::get()?.minimum_balance(space);
+    let lamports = account.lamports();
+
+    if rent_lamports > lamports {
+        Transfer {
+            from: payer,
+            to: account,
+            lamports: rent_lamports - lamports,
+        }
+        .invoke()?;
+    }
+
+    account.resize(space)?;
+
+    if lamports > rent_lamports {
+        *account.try_borrow_mut_lamports()? -= lamports - rent_lamports;
+        *payer.try_borrow_mut_lamports()? += lamports - rent_lamports;
+    }
+    Ok(())
+}
@@ -1,41 +1,66 @@
 use pinocchio::{
-    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey,
-    pubkey::find_program_address, seeds, ProgramResult,
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    seeds,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
 };
 use pinocchio_log::log;
 
-use crate::state::GenIxHandler;
+use crate::{
+    errors::MojoError,
+    state::{
+        check_pda, is_delegation_expired, is_world_delegated, read_delegation_header,
+        read_pda_bump, set_delegation, GenIxHandler,
+    },
+};
 
 pub fn process_undelegate_account(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     log!("i was here");
-    let [creator, mojo_account_pda, magic_context, magic_program] = accounts else {
+    let [creator, mojo_account_pda, world, magic_context, magic_program] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    // 0xAbim: Validate creator is a signer
+    // 0xAbim: Validate creator is a signer, it pays for the undelegation
     if !creator.is_signer() {
-        return Err(ProgramError::MissingRequiredSignature);
+        return Err(MojoError::MissingSigner.into());
+    }
+    // check that mojo_account_pda has been created
+    if mojo_account_pda.data_is_empty() {
+        return Err(MojoError::UninitializedAccount.into());
     }
-    // check that account_to_create is empty
-    assert!(
-        !&mojo_account_pda.data_is_empty(),
-        "Account should be empty"
-    );
 
-    let mojo_data = &data[0..GenIxHandler::LEN];
-    let mojo_ser_data = bytemuck::from_bytes::<GenIxHandler>(mojo_data);
+    let (mojo_ser_data, _) = GenIxHandler::parse(data)?;
+    let is_world = mojo_account_pda.key() == world.key();
 
-    let seeds_data = &mojo_ser_data.seeds;
-    // let seed_bump = [bump];
-    let seeds = &[seeds_data, creator.key().as_ref()];
+    // the PDA is derived from its owner, who is not necessarily the signer
+    let (owner, delegated_until) = read_delegation_header(mojo_account_pda, is_world)?;
+    if is_world && !is_world_delegated(mojo_account_pda)? {
+        return Err(MojoError::NotDelegated.into());
+    }
 
-    let (derived_pda, bump) = pubkey::find_program_address(seeds, &crate::id());
+    // a world is derived from [seed hash, creator], its states from [seed hash, world, creator]
+    let seeds_data = &mojo_ser_data.seeds;
+    let world_seeds: [&[u8]; 2] = [seeds_data, owner.as_ref()];
+    let state_seeds: [&[u8]; 3] = [seeds_data, world.key().as_ref(), owner.as_ref()];
+    let seeds: &[&[u8]] = if is_world { &world_seeds } else { &state_seeds };
 
-    assert_eq!(
-        &derived_pda,
+    check_pda(
         mojo_account_pda.key(),
-        "You provided the wrong user pda"
-    );
+        seeds,
+        read_pda_bump(mojo_account_pda, is_world)?,
+    )?;
+
+    // anyone may hand an expired delegation back to the base layer, otherwise only the owner
+    if creator.key() != &owner
+        && !is_delegation_expired(delegated_until, Clock::get()?.unix_timestamp)
+    {
+        return Err(MojoError::Unauthorized.into());
+    }
+
+    // the account is committed back to the base layer as no longer delegated
+    set_delegation(mojo_account_pda, is_world, false, 0)?;
 
     ephemeral_rollups_pinocchio::instruction::commit_and_undelegate_accounts(
         creator,
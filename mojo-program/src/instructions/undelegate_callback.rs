@@ -0,0 +1,174 @@
+use ephemeral_rollups_pinocchio::consts::{BUFFER, DELEGATION_PROGRAM_ID};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    pubkey, seeds,
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::instructions::{Allocate, Assign, CreateAccount, Transfer};
+
+use crate::{
+    errors::MojoError,
+    state::{check_pda, StateHeader, WorldHeader},
+};
+
+/// Discriminator the delegation program prefixes its undelegate callback with
+pub const UNDELEGATE_CALLBACK_DISCRIMINATOR: [u8; 8] = [196, 28, 41, 206, 48, 37, 51, 167];
+
+/// Worlds are derived from 2 seeds, states from 3
+const MAX_SEEDS: usize = 3;
+
+/// Called by the delegation program when a delegated account returns to the base layer
+///
+/// The delegated account comes back empty and owned by the system program, it is recreated
+/// under the PDA seeds it was delegated with and refilled with the committed data from the
+/// buffer. The buffer has to be the delegation program PDA [BUFFER, delegated account], so
+/// only data committed from the rollup is ever restored.
+///
+/// Instruction data: [borsh Vec<Vec<u8>> of the PDA seeds, without bump]
+pub fn process_undelegate_callback(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [delegated_account, buffer, payer, _system_program, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // the buffer is a delegation program PDA, only the delegation program can sign for it
+    if !buffer.is_signer() {
+        return Err(MojoError::MissingSigner.into());
+    }
+    if !buffer.is_owned_by(&DELEGATION_PROGRAM_ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let buffer_seeds: [&[u8]; 2] = [BUFFER, delegated_account.key().as_ref()];
+    let (buffer_pda, _bump) = pubkey::find_program_address(&buffer_seeds, &DELEGATION_PROGRAM_ID);
+    if &buffer_pda != buffer.key() {
+        return Err(MojoError::InvalidPda.into());
+    }
+    if !delegated_account.data_is_empty() {
+        return Err(MojoError::AlreadyInitialized.into());
+    }
+
+    let (seeds_buf, seeds_count) = parse_seeds(data)?;
+    let seeds = &seeds_buf[..seeds_count];
+
+    // the committed data still carries the header and the bump recorded at creation
+    let bump = committed_bump(&buffer.try_borrow_data()?, seeds_count)?;
+    check_pda(delegated_account.key(), seeds, bump)?;
+
+    let bump_binding = [bump];
+    match *seeds {
+        [hash, creator] => {
+            let signer_seeds = seeds!(hash, creator, &bump_binding);
+            recreate_account(
+                delegated_account,
+                buffer,
+                payer,
+                Signer::from(&signer_seeds[..]),
+            )?;
+        }
+        [hash, world, creator] => {
+            let signer_seeds = seeds!(hash, world, creator, &bump_binding);
+            recreate_account(
+                delegated_account,
+                buffer,
+                payer,
+                Signer::from(&signer_seeds[..]),
+            )?;
+        }
+        _ => return Err(MojoError::InvalidInstructionData.into()),
+    }
+
+    // restore the state as it was committed from the rollup
+    delegated_account
+        .try_borrow_mut_data()?
+        .copy_from_slice(&buffer.try_borrow_data()?);
+
+    Ok(())
+}
+
+/// PDA bump in the header of committed world (2 seeds) or state (3 seeds) data
+fn committed_bump(data: &[u8], seeds_count: usize) -> Result<u8, ProgramError> {
+    let bump = match seeds_count {
+        2 => data
+            .get(..WorldHeader::LEN)
+            .map(|header| bytemuck::from_bytes::<WorldHeader>(header).bump),
+        3 => data
+            .get(..StateHeader::LEN)
+            .map(|header| bytemuck::from_bytes::<StateHeader>(header).bump),
+        _ => return Err(MojoError::InvalidInstructionData.into()),
+    };
+    bump.ok_or(MojoError::InvalidAccountSize.into())
+}
+
+/// Recreate `account` as a program owned PDA sized like the committed buffer
+fn recreate_account(
+    account: &AccountInfo,
+    buffer: &AccountInfo,
+    payer: &AccountInfo,
+    signer: Signer,
+) -> ProgramResult {
+    let space = buffer.data_len();
+    let rent_lamports = Rent::get()?.minimum_balance(space);
+
+    // lamports left on the account by the delegation program are kept
+    if account.lamports() == 0 {
+        return CreateAccount {
+            from: payer,
+            to: account,
+            lamports: rent_lamports,
+            space: space as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[signer]);
+    }
+
+    if account.lamports() < rent_lamports {
+        Transfer {
+            from: payer,
+            to: account,
+            lamports: rent_lamports - account.lamports(),
+        }
+        .invoke()?;
+    }
+
+    let signers = [signer];
+    Allocate {
+        account,
+        space: space as u64,
+    }
+    .invoke_signed(&signers)?;
+    Assign {
+        account,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&signers)
+}
+
+/// Decode a borsh `Vec<Vec<u8>>` of at most `MAX_SEEDS` seeds
+fn parse_seeds(data: &[u8]) -> Result<([&[u8]; MAX_SEEDS], usize), ProgramError> {
+    let (count, mut rest) = read_u32(data)?;
+    if count > MAX_SEEDS {
+        return Err(MojoError::InvalidInstructionData.into());
+    }
+
+    let mut seeds: [&[u8]; MAX_SEEDS] = [&[]; MAX_SEEDS];
+    for seed in seeds.iter_mut().take(count) {
+        let (len, tail) = read_u32(rest)?;
+        if tail.len() < len {
+            return Err(MojoError::InvalidInstructionData.into());
+        }
+        let (value, tail) = tail.split_at(len);
+        *seed = value;
+        rest = tail;
+    }
+
+    Ok((seeds, count))
+}
+
+fn read_u32(data: &[u8]) -> Result<(usize, &[u8]), ProgramError> {
+    let (len, rest) = data
+        .split_first_chunk::<4>()
+        .ok_or(MojoError::InvalidInstructionData)?;
+    Ok((u32::from_le_bytes(*len) as usize, rest))
+}
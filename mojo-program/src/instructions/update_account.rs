@@ -1,51 +1,147 @@
 use pinocchio::{
     account_info::AccountInfo,
-    instruction::Signer,
-    msg,
-    pubkey::{self, Pubkey},
-    seeds,
-    sysvars::{rent::Rent, Sysvar},
+    sysvars::{clock::Clock, Sysvar},
     ProgramResult,
 };
 
 use pinocchio_log::log;
 
-use crate::state::GenIxHandler;
+use crate::{
+    errors::MojoError,
+    state::{
+        check_pda, check_shared_writer, check_world_account, is_delegation_expired,
+        is_session_account, use_session, GenIxHandler, StateHeader, StateSchema, ACTION_UPDATE,
+    },
+};
 
+/// Replaces the state after the header and records the write in it
+///
+/// When the expected version follows the state the write only lands if nobody wrote the
+/// state since, otherwise it fails with `VersionConflict`. The schema, when given, has to be
+/// the stored one.
+///
+/// Accounts: [owner or their session key, state, world, session of the key,
+/// access list and membership proof of a shared state..]
+///
+/// Instruction data: [GenIxHandler][schema flag u8][StateSchema, if flagged][state]
+/// [expected version u64 LE, optional]
 pub fn update_delegated_account(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     // get all accounts
-    let [creator, account_to_update, rest @ ..] = accounts else {
+    let [creator, account_to_update, world, auth_accounts @ ..] = accounts else {
         return Err(pinocchio::program_error::ProgramError::NotEnoughAccountKeys);
     };
 
-    let mojo_data = &data[0..GenIxHandler::LEN];
-    let mojo_ser_data = bytemuck::from_bytes::<GenIxHandler>(mojo_data);
+    let (mojo_ser_data, payload) = GenIxHandler::parse(data)?;
+    let (schema, payload) = StateSchema::split(payload)?;
+    let (state_data, expected_version) =
+        split_expected_version(payload, mojo_ser_data.state_size());
 
-    // checks
-    // check that maker is a signer ✅
-    assert!(&creator.is_signer(), "Creator should be a signer");
-    // check that account_to_update is empty
-    assert!(
-        !(&account_to_update.data_is_empty()),
-        "Account should be empty"
-    );
-    // check that owner of account_to_update is this program
-    let seeds_data = &mojo_ser_data.seeds;
-    // let seed_bump = [bump];
-    let seeds = &[seeds_data, creator.key().as_ref()];
-
-    let (derived_pda, bump) = pubkey::find_program_address(seeds, &crate::id());
-    let bump_binding: [u8; 1] = [bump];
-    assert_eq!(
-        &derived_pda,
-        account_to_update.key(),
-        "You provided the wrong user pda"
-    );
-
-    let mut some_fist_account = account_to_update.try_borrow_mut_data().unwrap();
+    check_state_write(
+        creator,
+        account_to_update,
+        world,
+        auth_accounts,
+        &mojo_ser_data.seeds,
+        schema.as_ref(),
+        ACTION_UPDATE,
+    )?;
+
+    let mut some_fist_account = account_to_update.try_borrow_mut_data()?;
 
     log!("current data is {}", some_fist_account.as_ref());
+    // the header is program managed, only the state after it is replaced
+    if some_fist_account.len() != StateHeader::LEN + state_data.len() {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+    let (header_bytes, state) = some_fist_account.split_at_mut(StateHeader::LEN);
+    let header = bytemuck::from_bytes_mut::<StateHeader>(header_bytes);
+    if expected_version.is_some_and(|expected| expected != header.version()) {
+        return Err(MojoError::VersionConflict.into());
+    }
+    header.record_write(Clock::get()?.slot);
     // this will modify the account state
-    some_fist_account.copy_from_slice(&data[GenIxHandler::LEN..]);
+    state.copy_from_slice(state_data);
+    Ok(())
+}
+
+/// Split the state carried by an update from the version it expects, if any
+fn split_expected_version(payload: &[u8], state_size: usize) -> (&[u8], Option<u64>) {
+    match payload.split_last_chunk::<8>() {
+        Some((state_data, version)) if state_data.len() == state_size => {
+            (state_data, Some(u64::from_le_bytes(*version)))
+        }
+        _ => (payload, None),
+    }
+}
+
+/// Checks shared by the instructions writing into a state
+///
+/// The owner signed, the state exists as their PDA in `world`, follows `schema` when one is
+/// given and its delegation, if any, has not expired. A session key of the owner signs in their place when its session leads
+/// `rest`. A shared state is owned by its world instead, its access list follows in `rest`
+/// and decides who may write it.
+pub(crate) fn check_state_write(
+    creator: &AccountInfo,
+    account_to_update: &AccountInfo,
+    world: &AccountInfo,
+    rest: &[AccountInfo],
+    seeds_data: &[u8; 32],
+    schema: Option<&StateSchema>,
+    action: u8,
+) -> ProgramResult {
+    // check that maker is a signer ✅
+    if !creator.is_signer() {
+        return Err(MojoError::MissingSigner.into());
+    }
+    // check that account_to_update has been created
+    if account_to_update.data_len() < StateHeader::LEN {
+        return Err(MojoError::UninitializedAccount.into());
+    }
+    // check that the parent world exists, delegated or not
+    check_world_account(world)?;
+
+    let header = {
+        let account_data = account_to_update.try_borrow_data()?;
+        *bytemuck::from_bytes::<StateHeader>(&account_data[..StateHeader::LEN])
+    };
+    if &header.world != world.key() {
+        return Err(MojoError::WorldMismatch.into());
+    }
+    // a write laid out for another schema would corrupt the state
+    if schema.is_some_and(|schema| *schema != header.schema()) {
+        return Err(MojoError::InvalidSchema.into());
+    }
+    let shared = &header.owner == world.key();
+
+    // a session key writes on behalf of the player who authorized it
+    let clock = Clock::get()?;
+    let (authority, rest) = match rest {
+        [session, rest @ ..] if is_session_account(session) => (
+            use_session(
+                session,
+                creator,
+                world.key(),
+                action,
+                clock.slot,
+                header.is_delegated(),
+            )?,
+            rest,
+        ),
+        _ => (*creator.key(), rest),
+    };
+
+    // check that account_to_update is the state PDA of this world
+    let owner = if shared { world.key() } else { &authority };
+    let seeds: [&[u8]; 3] = [seeds_data, world.key().as_ref(), owner.as_ref()];
+
+    check_pda(account_to_update.key(), &seeds, header.bump)?;
+    if shared {
+        check_shared_writer(&authority, account_to_update, world, rest)?;
+    }
+
+    // a delegation past its lifetime only accepts being undelegated
+    if is_delegation_expired(header.delegated_until(), clock.unix_timestamp) {
+        return Err(MojoError::DelegationExpired.into());
+    }
     Ok(())
 }
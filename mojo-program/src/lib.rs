@@ -1,8 +1,12 @@
-use pinocchio::{account_info::AccountInfo, entrypoint, pubkey::Pubkey, ProgramResult};
+use pinocchio::{
+    account_info::AccountInfo, entrypoint, program_error::ProgramError, pubkey::Pubkey,
+    ProgramResult,
+};
 
-use crate::instructions::MojoInstructions;
+use crate::{errors::MojoError, instructions::MojoInstructions};
 
 mod constants;
+pub mod errors;
 mod instructions;
 mod state;
 mod tests;
@@ -17,11 +21,20 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    assert_eq!(program_id, &ID);
+    if program_id != &ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // the delegation program hands accounts back through an 8 byte discriminator
+    if let Some(data) =
+        instruction_data.strip_prefix(&instructions::UNDELEGATE_CALLBACK_DISCRIMINATOR)
+    {
+        return instructions::process_undelegate_callback(accounts, data);
+    }
 
     let (discriminator, data) = instruction_data
         .split_first()
-        .ok_or(pinocchio::program_error::ProgramError::InvalidInstructionData)?;
+        .ok_or(MojoError::InvalidInstructionData)?;
 
     log!("discri {}", *discriminator);
     match MojoInstructions::try_from(discriminator)? {
@@ -40,7 +53,48 @@ pub fn process_instruction(
         MojoInstructions::UndelegateAccount => {
             instructions::process_undelegate_account(accounts, data)?;
         }
-        _ => return Err(pinocchio::program_error::ProgramError::IncorrectAuthority),
+        MojoInstructions::CreateWorld => {
+            instructions::create_world_account(accounts, data)?;
+        }
+        MojoInstructions::DelegateBatch => {
+            instructions::process_delegate_batch(accounts, data)?;
+        }
+        MojoInstructions::CommitBatch => {
+            instructions::process_commit_batch(accounts, data, false)?;
+        }
+        MojoInstructions::UndelegateBatch => {
+            instructions::process_commit_batch(accounts, data, true)?;
+        }
+        MojoInstructions::CloseAccount => {
+            instructions::process_close_account(accounts, data)?;
+        }
+        MojoInstructions::ResizeAccount => {
+            instructions::process_resize_account(accounts, data)?;
+        }
+        MojoInstructions::PatchAccount => {
+            instructions::process_patch_account(accounts, data)?;
+        }
+        MojoInstructions::Arithmetic => {
+            instructions::process_arithmetic(accounts, data)?;
+        }
+        MojoInstructions::CreateSharedState => {
+            instructions::process_create_shared_state(accounts, data)?;
+        }
+        MojoInstructions::AddWriter => {
+            instructions::process_manage_writer(accounts, data, true)?;
+        }
+        MojoInstructions::RemoveWriter => {
+            instructions::process_manage_writer(accounts, data, false)?;
+        }
+        MojoInstructions::CreateSession => {
+            instructions::process_create_session(accounts, data)?;
+        }
+        MojoInstructions::RevokeSession => {
+            instructions::process_revoke_session(accounts)?;
+        }
+        MojoInstructions::MigrateAccount => {
+            instructions::process_migrate_account(accounts, data)?;
+        }
     }
     Ok(())
 }
@@ -0,0 +1,154 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    errors::MojoError,
+    state::{read_world_header, StateHeader, STATE_MAGIC},
+};
+
+/// Seed prefix of access lists, derived from [ACL_SEED, shared state]
+pub const ACL_SEED: &[u8] = b"acl";
+
+/// Writers an access list holds besides the world creator
+pub const MAX_WRITERS: usize = 16;
+
+/// Who may write a shared state, the world creator always can
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessMode {
+    CreatorOnly = 0,
+    /// The writers listed in the access list
+    Listed = 1,
+    /// Listed writers and any player holding a state in the world
+    AnyMember = 2,
+}
+
+impl TryFrom<u8> for AccessMode {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AccessMode::CreatorOnly),
+            1 => Ok(AccessMode::Listed),
+            2 => Ok(AccessMode::AnyMember),
+            _ => Err(MojoError::InvalidInstructionData.into()),
+        }
+    }
+}
+
+/// Access list of a shared state, a state owned by its world instead of a single player
+///
+/// Account layout: [StateAcl]
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+pub struct StateAcl {
+    pub world: [u8; 32],
+    pub state: [u8; 32],
+    pub mode: u8,
+    pub writer_count: u8,
+    pub writers: [[u8; 32]; MAX_WRITERS],
+}
+
+impl StateAcl {
+    pub const LEN: usize = core::mem::size_of::<StateAcl>();
+
+    pub fn writers(&self) -> &[Pubkey] {
+        &self.writers[..(self.writer_count as usize).min(MAX_WRITERS)]
+    }
+
+    pub fn is_listed(&self, key: &Pubkey) -> bool {
+        self.writers().contains(key)
+    }
+
+    /// Lists `writer`, listing a writer twice is a no-op
+    pub fn add_writer(&mut self, writer: &Pubkey) -> ProgramResult {
+        if self.is_listed(writer) {
+            return Ok(());
+        }
+        let count = self.writers().len();
+        if count == MAX_WRITERS {
+            return Err(MojoError::AclFull.into());
+        }
+        self.writers[count] = *writer;
+        self.writer_count = count as u8 + 1;
+        Ok(())
+    }
+
+    /// Drops `writer` from the list, the last writer takes its slot
+    pub fn remove_writer(&mut self, writer: &Pubkey) {
+        let count = self.writers().len();
+        if let Some(index) = self.writers().iter().position(|listed| listed == writer) {
+            self.writers.swap(index, count - 1);
+            self.writers[count - 1] = [0; 32];
+            self.writer_count = count as u8 - 1;
+        }
+    }
+}
+
+/// Read the access list of the shared `state`
+pub fn read_acl(acl: &AccountInfo, state: &Pubkey) -> Result<StateAcl, ProgramError> {
+    if !acl.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let acl_data = acl.try_borrow_data()?;
+    if acl_data.len() != StateAcl::LEN {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+    let acl = *bytemuck::from_bytes::<StateAcl>(&acl_data);
+    // only the program writes access lists, the stored key binds one to its state
+    if &acl.state != state {
+        return Err(MojoError::InvalidPda.into());
+    }
+    Ok(acl)
+}
+
+/// Checks that `writer` may write the shared `state` of `world`
+///
+/// `rest` holds the access list, followed for `AnyMember` states by a state of the writer
+/// in the world proving their membership
+pub fn check_shared_writer(
+    writer: &Pubkey,
+    state: &AccountInfo,
+    world: &AccountInfo,
+    rest: &[AccountInfo],
+) -> ProgramResult {
+    let [acl, membership @ ..] = rest else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let acl = read_acl(acl, state.key())?;
+
+    if writer == &read_world_header(world)?.creator {
+        return Ok(());
+    }
+    let allowed = match AccessMode::try_from(acl.mode)? {
+        AccessMode::CreatorOnly => false,
+        AccessMode::Listed => acl.is_listed(writer),
+        AccessMode::AnyMember => {
+            acl.is_listed(writer)
+                || membership
+                    .first()
+                    .is_some_and(|proof| holds_state(proof, world.key(), writer))
+        }
+    };
+    if !allowed {
+        return Err(MojoError::Unauthorized.into());
+    }
+    Ok(())
+}
+
+/// Whether `account` is a state of `owner` in `world`
+fn holds_state(account: &AccountInfo, world: &Pubkey, owner: &Pubkey) -> bool {
+    if !account.is_owned_by(&crate::ID) {
+        return false;
+    }
+    let Ok(data) = account.try_borrow_data() else {
+        return false;
+    };
+    if data.len() < StateHeader::LEN {
+        return false;
+    }
+    let header = bytemuck::from_bytes::<StateHeader>(&data[..StateHeader::LEN]);
+    header.magic == STATE_MAGIC && &header.world == world && &header.owner == owner
+}
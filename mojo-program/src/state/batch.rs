@@ -0,0 +1,53 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::errors::MojoError;
+
+/// Split batch instruction data into its seed hashes and the payload following them
+///
+/// Instruction data: [count: u8][count x seed hash: 32 bytes][payload]
+pub fn parse_seed_hashes(data: &[u8]) -> Result<(&[[u8; 32]], &[u8]), ProgramError> {
+    let (count, rest) = data
+        .split_first()
+        .ok_or(MojoError::InvalidInstructionData)?;
+    let hashes_len = *count as usize * 32;
+    if *count == 0 || rest.len() < hashes_len {
+        return Err(MojoError::InvalidInstructionData.into());
+    }
+    let (hashes, payload) = rest.split_at(hashes_len);
+    Ok((bytemuck::cast_slice(hashes), payload))
+}
+
+/// Split the accounts of a batch into the `per_pda` accounts of each of its `count` PDAs and
+/// the owners co-signing for their states after them
+///
+/// Accounts that do not match the seed hashes, too few or trailing ones that do not sign, are
+/// rejected as invalid instruction data
+pub fn split_batch_accounts(
+    accounts: &[AccountInfo],
+    count: usize,
+    per_pda: usize,
+) -> Result<(&[AccountInfo], &[AccountInfo]), ProgramError> {
+    let (pdas, co_signers) = accounts
+        .split_at_checked(count * per_pda)
+        .ok_or(MojoError::InvalidInstructionData)?;
+    if co_signers.iter().any(|co_signer| !co_signer.is_signer()) {
+        return Err(MojoError::InvalidInstructionData.into());
+    }
+    Ok((pdas, co_signers))
+}
+
+/// Whether `owner` consented to a batch acting on their PDA, by signing it or co-signing it
+///
+/// Shared states are owned by their world, the world creator answers for them
+pub fn owner_signed_batch(
+    owner: &Pubkey,
+    signer: &AccountInfo,
+    co_signers: &[AccountInfo],
+    world: &Pubkey,
+    creator: &Pubkey,
+) -> bool {
+    let signed = |key: &Pubkey| {
+        signer.key() == key || co_signers.iter().any(|co_signer| co_signer.key() == key)
+    };
+    signed(owner) || (owner == world && signed(creator))
+}
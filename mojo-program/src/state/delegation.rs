@@ -0,0 +1,137 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    errors::MojoError,
+    state::{read_world_header, set_world_delegated, StateHeader, WorldHeader},
+};
+
+/// Commit frequency used when neither the instruction nor the world config sets one
+pub const DEFAULT_COMMIT_FREQUENCY_MS: u32 = 30_000;
+
+/// Optional delegation settings appended to the delegate instruction data
+///
+/// Zeroed fields fall back to the world config, then to the program defaults.
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+pub struct DelegationArgs {
+    pub commit_frequency_ms: [u8; 4], // u32 as le bytes
+    pub lifetime_secs: [u8; 4],       // u32 as le bytes, 0 delegates without expiry
+    pub validator: [u8; 32],          // all zeroes lets the delegation program pick
+}
+
+impl DelegationArgs {
+    pub const LEN: usize = core::mem::size_of::<DelegationArgs>();
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bytemuck::bytes_of(self).to_vec()
+    }
+
+    /// Read the args following the GenIxHandler, empty data keeps every default
+    pub fn parse(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.is_empty() {
+            return Ok(Self::zeroed());
+        }
+        if data.len() != Self::LEN {
+            return Err(MojoError::InvalidInstructionData.into());
+        }
+        bytemuck::try_pod_read_unaligned::<DelegationArgs>(data)
+            .map_err(|_| MojoError::InvalidInstructionData.into())
+    }
+
+    pub fn commit_frequency_ms(&self) -> u32 {
+        u32::from_le_bytes(self.commit_frequency_ms)
+    }
+
+    pub fn lifetime_secs(&self) -> u32 {
+        u32::from_le_bytes(self.lifetime_secs)
+    }
+
+    pub fn validator(&self) -> Option<Pubkey> {
+        (self.validator != [0u8; 32]).then_some(self.validator)
+    }
+
+    /// Commit frequency and expiry timestamp of a delegation in `world`
+    ///
+    /// Settings left out of the instruction fall back to the world config, then to the
+    /// program defaults
+    pub fn resolve(&self, world: &WorldHeader) -> Result<(u32, i64), ProgramError> {
+        let commit_frequency_ms = [self.commit_frequency_ms(), world.commit_frequency_ms()]
+            .into_iter()
+            .find(|frequency| *frequency != 0)
+            .unwrap_or(DEFAULT_COMMIT_FREQUENCY_MS);
+        let lifetime_secs = match self.lifetime_secs() {
+            0 => world.delegation_lifetime_secs(),
+            lifetime_secs => lifetime_secs,
+        };
+        let delegated_until = match lifetime_secs {
+            0 => 0,
+            lifetime_secs => Clock::get()?.unix_timestamp + lifetime_secs as i64,
+        };
+        Ok((commit_frequency_ms, delegated_until))
+    }
+}
+
+/// Whether a delegation stamped with `delegated_until` has run out at `now`
+pub fn is_delegation_expired(delegated_until: i64, now: i64) -> bool {
+    delegated_until != 0 && now >= delegated_until
+}
+
+/// Owner and delegation expiry of a world or state account, the owner of a world is its creator
+pub fn read_delegation_header(
+    account: &AccountInfo,
+    is_world: bool,
+) -> Result<(Pubkey, i64), ProgramError> {
+    if is_world {
+        let header = read_world_header(account)?;
+        return Ok((header.creator, header.delegated_until()));
+    }
+
+    let state_data = account.try_borrow_data()?;
+    if state_data.len() < StateHeader::LEN {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+    let header = bytemuck::from_bytes::<StateHeader>(&state_data[..StateHeader::LEN]);
+    Ok((header.owner, header.delegated_until()))
+}
+
+/// Canonical PDA bump recorded in the header of a world or state account
+pub fn read_pda_bump(account: &AccountInfo, is_world: bool) -> Result<u8, ProgramError> {
+    if is_world {
+        return Ok(read_world_header(account)?.bump);
+    }
+
+    let state_data = account.try_borrow_data()?;
+    if state_data.len() < StateHeader::LEN {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+    Ok(bytemuck::from_bytes::<StateHeader>(&state_data[..StateHeader::LEN]).bump)
+}
+
+/// Flags a world or state account as delegated and stamps its delegation expiry
+///
+/// The expiry is 0 once an account is back on the base layer
+pub fn set_delegation(
+    account: &AccountInfo,
+    is_world: bool,
+    delegated: bool,
+    delegated_until: i64,
+) -> ProgramResult {
+    if is_world {
+        return set_world_delegated(account, delegated, delegated_until);
+    }
+
+    let mut state_data = account.try_borrow_mut_data()?;
+    if state_data.len() < StateHeader::LEN {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+    bytemuck::from_bytes_mut::<StateHeader>(&mut state_data[..StateHeader::LEN])
+        .set_delegated(delegated, delegated_until);
+    Ok(())
+}
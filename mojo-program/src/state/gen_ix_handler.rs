@@ -1,5 +1,7 @@
 use bytemuck::{Pod, Zeroable};
-use pinocchio::pubkey::Pubkey;
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::errors::MojoError;
 
 #[repr(C)]
 #[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
@@ -14,6 +16,21 @@ impl GenIxHandler {
         bytemuck::bytes_of(self).to_vec()
     }
 
+    /// Split instruction data into the handler and the payload following it
+    pub fn parse(data: &[u8]) -> Result<(Self, &[u8]), ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(MojoError::InvalidInstructionData.into());
+        }
+        let handler = bytemuck::try_pod_read_unaligned::<GenIxHandler>(&data[..Self::LEN])
+            .map_err(|_| MojoError::InvalidInstructionData)?;
+        Ok((handler, &data[Self::LEN..]))
+    }
+
+    /// Size of the state carried by the instruction
+    pub fn state_size(&self) -> usize {
+        u64::from_le_bytes(self.size) as usize
+    }
+
     pub fn get_seed_slices(&self) -> [&[u8]; 5] {
         [
             &self.seeds[0..8],    // string 1
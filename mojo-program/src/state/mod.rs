@@ -1,2 +1,23 @@
 pub mod gen_ix_handler;
 pub use gen_ix_handler::*;
+
+pub mod state_header;
+pub use state_header::*;
+
+pub mod pda;
+pub use pda::*;
+
+pub mod world;
+pub use world::*;
+
+pub mod delegation;
+pub use delegation::*;
+
+pub mod batch;
+pub use batch::*;
+
+pub mod acl;
+pub use acl::*;
+
+pub mod session;
+pub use session::*;
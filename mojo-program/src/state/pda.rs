@@ -0,0 +1,30 @@
+use pinocchio::{
+    pubkey::{self, Pubkey},
+    ProgramResult,
+};
+
+use crate::errors::MojoError;
+
+/// Worlds are derived from 2 seeds, states from 3
+const MAX_SEEDS: usize = 3;
+
+/// Checks that `address` is the PDA of `seeds` with `bump`
+///
+/// Worlds and states record their canonical bump when they are created, so later
+/// instructions hash the address once with `create_program_address` instead of searching the
+/// bump again with `find_program_address`, which pays for every bump it tries.
+pub fn check_pda(address: &Pubkey, seeds: &[&[u8]], bump: u8) -> ProgramResult {
+    if seeds.len() > MAX_SEEDS {
+        return Err(MojoError::InvalidPda.into());
+    }
+
+    let bump_binding = [bump];
+    let mut seeds_with_bump: [&[u8]; MAX_SEEDS + 1] = [&[]; MAX_SEEDS + 1];
+    seeds_with_bump[..seeds.len()].copy_from_slice(seeds);
+    seeds_with_bump[seeds.len()] = &bump_binding;
+
+    match pubkey::create_program_address(&seeds_with_bump[..=seeds.len()], &crate::ID) {
+        Ok(derived_pda) if &derived_pda == address => Ok(()),
+        _ => Err(MojoError::InvalidPda.into()),
+    }
+}
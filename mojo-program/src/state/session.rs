@@ -0,0 +1,112 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::{self, Pubkey},
+};
+
+use crate::errors::MojoError;
+
+/// Seed prefix of sessions, derived from [SESSION_SEED, world, owner]
+pub const SESSION_SEED: &[u8] = b"session";
+
+/// Leads every session account, telling sessions apart from states of the same size
+pub const SESSION_MAGIC: [u8; 4] = *b"MOJS";
+
+/// Write instructions a session key may sign, as bits of `SessionToken::allowed_actions`
+pub const ACTION_UPDATE: u8 = 1 << 0;
+pub const ACTION_PATCH: u8 = 1 << 1;
+pub const ACTION_ARITHMETIC: u8 = 1 << 2;
+
+/// Authorizes an ephemeral session key to write the states of its owner in one world
+///
+/// A player signs once to create the session, the game then signs its moves with the session
+/// key until the expiry slot or the action limit is reached.
+///
+/// Account layout: [SessionToken]
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+pub struct SessionToken {
+    pub magic: [u8; 4],
+    pub world: [u8; 32],
+    pub owner: [u8; 32],
+    pub session_key: [u8; 32],
+    pub expires_at: [u8; 8], // u64 slot as le bytes, the last slot the key is accepted
+    pub max_actions: [u8; 8], // u64 as le bytes, 0 for no limit
+    pub actions_used: [u8; 8], // u64 as le bytes, only counted when limited
+    pub allowed_actions: u8,
+    pub bump: u8,
+}
+
+impl SessionToken {
+    pub const LEN: usize = core::mem::size_of::<SessionToken>();
+
+    pub fn expires_at(&self) -> u64 {
+        u64::from_le_bytes(self.expires_at)
+    }
+
+    pub fn max_actions(&self) -> u64 {
+        u64::from_le_bytes(self.max_actions)
+    }
+
+    pub fn actions_used(&self) -> u64 {
+        u64::from_le_bytes(self.actions_used)
+    }
+
+    pub fn set_actions_used(&mut self, actions_used: u64) {
+        self.actions_used = actions_used.to_le_bytes();
+    }
+}
+
+/// Whether `account` holds a session, as opposed to the access list of a shared state
+pub fn is_session_account(account: &AccountInfo) -> bool {
+    account.is_owned_by(&crate::ID)
+        && account.data_len() == SessionToken::LEN
+        && account
+            .try_borrow_data()
+            .is_ok_and(|data| data[..SESSION_MAGIC.len()] == SESSION_MAGIC)
+}
+
+/// Checks that `signer` holds a live session of `world` allowing `action` and counts the
+/// action, returns the owner the session writes for
+///
+/// The session is bound to its owner and world by its PDA, verified against the stored bump.
+/// The session itself is never delegated, so a limited session can not count writes to a
+/// `delegated` state
+pub fn use_session(
+    session: &AccountInfo,
+    signer: &AccountInfo,
+    world: &Pubkey,
+    action: u8,
+    slot: u64,
+    delegated: bool,
+) -> Result<Pubkey, ProgramError> {
+    let token = *bytemuck::from_bytes::<SessionToken>(&session.try_borrow_data()?);
+
+    let bump = [token.bump];
+    let seeds: [&[u8]; 4] = [SESSION_SEED, world.as_ref(), token.owner.as_ref(), &bump];
+    if &pubkey::create_program_address(&seeds, &crate::ID)? != session.key() {
+        return Err(MojoError::InvalidPda.into());
+    }
+    if &token.session_key != signer.key() || token.allowed_actions & action == 0 {
+        return Err(MojoError::Unauthorized.into());
+    }
+    if slot > token.expires_at() {
+        return Err(MojoError::SessionExpired.into());
+    }
+
+    // unlimited sessions stay read-only, so they work while cloned into an ephemeral rollup
+    let max_actions = token.max_actions();
+    if max_actions > 0 {
+        if delegated {
+            return Err(MojoError::AccountDelegated.into());
+        }
+        if token.actions_used() >= max_actions {
+            return Err(MojoError::SessionLimitReached.into());
+        }
+        let mut session_data = session.try_borrow_mut_data()?;
+        bytemuck::from_bytes_mut::<SessionToken>(&mut session_data)
+            .set_actions_used(token.actions_used() + 1);
+    }
+    Ok(token.owner)
+}
@@ -0,0 +1,146 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::errors::MojoError;
+
+/// Leads every state account, telling states apart from the other accounts of the program
+pub const STATE_MAGIC: [u8; 4] = *b"MOJO";
+
+/// Layout version of the `StateHeader`, bumped whenever its fields change
+pub const STATE_HEADER_VERSION: u8 = 3;
+
+/// Header stored at the start of every state account, ahead of the user payload.
+///
+/// Account layout: [StateHeader][state: variable]
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+pub struct StateHeader {
+    pub magic: [u8; 4],
+    pub header_version: u8,
+    pub state_type_id: u8,    // MojoState type identifier, 0 for untyped states
+    pub schema_version: u8,   // layout version of the type, raised by migrations
+    pub schema_hash: [u8; 8], // hash of the MojoState layout, zeroed for untyped states
+    pub world: [u8; 32],      // parent world pda, states are filtered by world
+    pub owner: [u8; 32],
+    pub is_delegated: u8,
+    pub delegated_until: [u8; 8], // i64 unix timestamp as le bytes, 0 when not expiring
+    pub version: [u8; 8],         // u64 as le bytes, bumped by every write to the state
+    pub last_updated: [u8; 8],    // u64 slot as le bytes of the last write to the state
+    pub bump: u8,                 // canonical bump of the state PDA, found once at creation
+}
+
+impl StateHeader {
+    pub const LEN: usize = core::mem::size_of::<StateHeader>();
+
+    /// Header of a state created at `slot` under the PDA bump `bump`
+    pub fn new(world: &Pubkey, owner: &Pubkey, bump: u8, schema: &StateSchema, slot: u64) -> Self {
+        Self {
+            magic: STATE_MAGIC,
+            header_version: STATE_HEADER_VERSION,
+            state_type_id: schema.state_type_id,
+            schema_version: schema.schema_version,
+            schema_hash: schema.schema_hash,
+            world: *world,
+            owner: *owner,
+            is_delegated: 0,
+            delegated_until: 0i64.to_le_bytes(),
+            version: 0u64.to_le_bytes(),
+            last_updated: slot.to_le_bytes(),
+            bump,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bytemuck::bytes_of(self).to_vec()
+    }
+
+    pub fn is_delegated(&self) -> bool {
+        self.is_delegated != 0
+    }
+
+    pub fn delegated_until(&self) -> i64 {
+        i64::from_le_bytes(self.delegated_until)
+    }
+
+    pub fn version(&self) -> u64 {
+        u64::from_le_bytes(self.version)
+    }
+
+    pub fn last_updated(&self) -> u64 {
+        u64::from_le_bytes(self.last_updated)
+    }
+
+    /// Records a write to the state at `slot`
+    pub fn record_write(&mut self, slot: u64) {
+        self.version = self.version().wrapping_add(1).to_le_bytes();
+        self.last_updated = slot.to_le_bytes();
+    }
+
+    /// The schema the state follows
+    pub fn schema(&self) -> StateSchema {
+        StateSchema {
+            state_type_id: self.state_type_id,
+            schema_version: self.schema_version,
+            schema_hash: self.schema_hash,
+        }
+    }
+
+    /// Records the state now holding the type of `schema`
+    pub fn set_schema(&mut self, schema: &StateSchema) {
+        self.state_type_id = schema.state_type_id;
+        self.schema_version = schema.schema_version;
+        self.schema_hash = schema.schema_hash;
+    }
+
+    /// Flags the state as living on (or returning from) an ephemeral rollup
+    pub fn set_delegated(&mut self, delegated: bool, delegated_until: i64) {
+        self.is_delegated = delegated as u8;
+        self.delegated_until = delegated_until.to_le_bytes();
+    }
+}
+
+/// Flag byte telling that no `StateSchema` follows it in instruction data
+pub const SCHEMA_ABSENT: u8 = 0;
+
+/// Flag byte telling that a `StateSchema` follows it in instruction data
+pub const SCHEMA_PRESENT: u8 = 1;
+
+/// Identifies the MojoState type a state holds
+///
+/// Carried ahead of the state by the instructions writing a whole state, so the header
+/// follows its layout. States written without one are untyped.
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy, Debug, Default, PartialEq)]
+pub struct StateSchema {
+    pub state_type_id: u8,
+    pub schema_version: u8,
+    pub schema_hash: [u8; 8],
+}
+
+impl StateSchema {
+    pub const LEN: usize = core::mem::size_of::<StateSchema>();
+
+    /// Whether a state following this schema may migrate to `next`: untyped states adopt any
+    /// schema, typed ones only newer versions of their type
+    pub fn migrates_to(&self, next: &StateSchema) -> bool {
+        self.state_type_id == 0
+            || (self.state_type_id == next.state_type_id
+                && self.schema_version < next.schema_version)
+    }
+
+    /// Split the schema off a payload led by a schema flag byte, returning what follows it
+    ///
+    /// Payload: [SCHEMA_ABSENT][rest] or [SCHEMA_PRESENT][StateSchema][rest]
+    pub fn split(payload: &[u8]) -> Result<(Option<Self>, &[u8]), ProgramError> {
+        match payload.split_first() {
+            Some((&SCHEMA_ABSENT, rest)) => Ok((None, rest)),
+            Some((&SCHEMA_PRESENT, rest)) => {
+                let (schema, rest) = rest
+                    .split_at_checked(Self::LEN)
+                    .ok_or(MojoError::InvalidInstructionData)?;
+                Ok((Some(*bytemuck::from_bytes::<Self>(schema)), rest))
+            }
+            _ => Err(MojoError::InvalidInstructionData.into()),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use ephemeral_rollups_pinocchio::consts::DELEGATION_PROGRAM_ID;
+
+use crate::{errors::MojoError, state::check_pda};
+
+/// Leads every World account, telling worlds apart from the other accounts of the program
+pub const WORLD_MAGIC: [u8; 4] = *b"MOJW";
+
+/// Fixed header stored at the start of every World account.
+///
+/// Account layout: [WorldHeader][initial world state: variable]
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+pub struct WorldHeader {
+    pub magic: [u8; 4],
+    pub creator: [u8; 32],
+    pub name_hash: [u8; 32], // hash of the world seed input, also the PDA seed
+    pub created_at: [u8; 8], // i64 unix timestamp as le bytes
+    pub state_count: [u8; 8], // u64 as le bytes
+    pub config: [u8; 8],     // world level settings, supplied at creation
+    pub is_delegated: u8,
+    pub delegated_until: [u8; 8], // i64 unix timestamp as le bytes, 0 when not expiring
+    pub bump: u8,                 // canonical bump of the world PDA, found once at creation
+}
+
+impl WorldHeader {
+    pub const LEN: usize = core::mem::size_of::<WorldHeader>();
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bytemuck::bytes_of(self).to_vec()
+    }
+
+    pub fn state_count(&self) -> u64 {
+        u64::from_le_bytes(self.state_count)
+    }
+
+    pub fn set_state_count(&mut self, count: u64) {
+        self.state_count = count.to_le_bytes();
+    }
+
+    pub fn is_delegated(&self) -> bool {
+        self.is_delegated != 0
+    }
+
+    /// Default commit frequency of delegations in this world, config bytes 0..4
+    pub fn commit_frequency_ms(&self) -> u32 {
+        u32::from_le_bytes([
+            self.config[0],
+            self.config[1],
+            self.config[2],
+            self.config[3],
+        ])
+    }
+
+    /// Default delegation lifetime in this world, config bytes 4..8
+    pub fn delegation_lifetime_secs(&self) -> u32 {
+        u32::from_le_bytes([
+            self.config[4],
+            self.config[5],
+            self.config[6],
+            self.config[7],
+        ])
+    }
+
+    pub fn delegated_until(&self) -> i64 {
+        i64::from_le_bytes(self.delegated_until)
+    }
+}
+
+/// Checks that `world` is an initialized World account, before reading it
+///
+/// A delegated world is owned by the delegation program on the base layer, the states that
+/// were not delegated along with it are still written there
+pub fn check_world_account(world: &AccountInfo) -> ProgramResult {
+    if world.data_is_empty() {
+        return Err(MojoError::UninitializedAccount.into());
+    }
+
+    if !world.is_owned_by(&crate::ID) && !world.is_owned_by(&DELEGATION_PROGRAM_ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    if world.data_len() < WorldHeader::LEN {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+
+    // states, access lists and sessions are program owned too
+    if world.try_borrow_data()?[..WORLD_MAGIC.len()] != WORLD_MAGIC {
+        return Err(MojoError::UninitializedAccount.into());
+    }
+
+    Ok(())
+}
+
+/// Checks that `world` is the World PDA of its own header, before the program mutates it
+pub fn check_world_pda(world: &AccountInfo, header: &WorldHeader) -> ProgramResult {
+    let seeds: [&[u8]; 2] = [&header.name_hash, &header.creator];
+    check_pda(world.key(), &seeds, header.bump)
+}
+
+/// Whether the World account is flagged as living on an ephemeral rollup
+pub fn is_world_delegated(world: &AccountInfo) -> Result<bool, ProgramError> {
+    let world_data = world.try_borrow_data()?;
+    if world_data.len() < WorldHeader::LEN {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+    Ok(bytemuck::from_bytes::<WorldHeader>(&world_data[..WorldHeader::LEN]).is_delegated())
+}
+
+/// Read the header of a World account
+pub fn read_world_header(world: &AccountInfo) -> Result<WorldHeader, ProgramError> {
+    let world_data = world.try_borrow_data()?;
+    if world_data.len() < WorldHeader::LEN {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+    let header = bytemuck::try_pod_read_unaligned::<WorldHeader>(&world_data[..WorldHeader::LEN])
+        .map_err(|_| ProgramError::from(MojoError::InvalidAccountSize))?;
+    if header.magic != WORLD_MAGIC {
+        return Err(MojoError::UninitializedAccount.into());
+    }
+    Ok(header)
+}
+
+/// Read the header of the parent world of a delegation
+///
+/// A delegated world is owned by the delegation program on the base layer, which keeps its
+/// data untouched
+pub fn read_parent_world(world: &AccountInfo) -> Result<WorldHeader, ProgramError> {
+    if !world.is_owned_by(&crate::ID) && !world.is_owned_by(&DELEGATION_PROGRAM_ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    read_world_header(world)
+}
+
+/// Flags the World account as living on (or returning from) an ephemeral rollup
+///
+/// `delegated_until` is the unix timestamp the delegation expires at, 0 when it never does
+pub fn set_world_delegated(
+    world: &AccountInfo,
+    delegated: bool,
+    delegated_until: i64,
+) -> ProgramResult {
+    let mut world_data = world.try_borrow_mut_data()?;
+    if world_data.len() < WorldHeader::LEN {
+        return Err(MojoError::InvalidAccountSize.into());
+    }
+    let header = bytemuck::from_bytes_mut::<WorldHeader>(&mut world_data[..WorldHeader::LEN]);
+    header.is_delegated = delegated as u8;
+    header.delegated_until = delegated_until.to_le_bytes();
+    Ok(())
+}
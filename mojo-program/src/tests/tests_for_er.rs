@@ -64,6 +64,7 @@ mod er_tests {
         pub rpc_client: RpcClient,
         pub rpc_er_client: RpcClient,
         pub system_program: Pubkey,
+        pub world: Pubkey,
         pub account_to_create: (Pubkey, u8),
         pub creator: Keypair,
         pub creator_2: Option<Keypair>,
@@ -81,10 +82,22 @@ mod er_tests {
 
         let payer = read_keypair_file("dev_wallet.json").expect("Couldn't find wallet file");
 
-        // Derive the PDA for the escrow account using the maker's public key and a seed value
+        // Derive the parent world PDA from the world name and the creator
+        let world_combined = encode_packed!(b"world", b"birdie world", payer.pubkey().as_ref());
+        let world = Pubkey::find_program_address(
+            &[&compute_hash(&world_combined), payer.pubkey().as_ref()],
+            &PROGRAM_ID,
+        )
+        .0;
+
+        // Derive the state PDA under the world using the maker's public key and a seed value
         let combined = encode_packed!(b"birdie", payer.pubkey().as_ref());
         let account_to_create = Pubkey::find_program_address(
-            &[&compute_hash(&combined), payer.pubkey().as_ref()],
+            &[
+                &compute_hash(&combined),
+                world.as_ref(),
+                payer.pubkey().as_ref(),
+            ],
             &PROGRAM_ID,
         );
 
@@ -115,6 +128,7 @@ mod er_tests {
             rpc_client,
             rpc_er_client,
             system_program,
+            world,
             account_to_create,
             creator: payer,
             account_to_create2: None,
@@ -157,6 +171,7 @@ mod er_tests {
         let accounts = vec![
             AccountMeta::new(creator.pubkey(), true),
             AccountMeta::new(account_to_create.0, false),
+            AccountMeta::new(state.world, false),
             AccountMeta::new(system_program, false),
             AccountMeta::new(Pubkey::new_from_array(RENT_ID.to_bytes()), false),
         ];
@@ -187,6 +202,69 @@ mod er_tests {
         );
     }
 
+    #[test]
+    fn test_create_world_account() {
+        let state = setup();
+
+        let creator = state.creator;
+        let system_program = state.system_program;
+
+        let world_state = MyPosition { x: 0, y: 10 };
+
+        let combined = encode_packed!(b"world", b"birdie world", creator.pubkey().as_ref());
+        let digest = compute_hash(&combined);
+        let world_pda = state.world;
+
+        let mojo_data = crate::state::GenIxHandler {
+            seeds: digest,
+            size: world_state.length().to_le_bytes(),
+        };
+
+        let create_world_ix_data = [
+            vec![crate::instructions::MojoInstructions::CreateWorld as u8],
+            mojo_data.to_bytes(),
+            [0u8; 8].to_vec(), // world config
+            world_state.to_bytes(),
+        ]
+        .concat();
+
+        let instruction = Instruction {
+            program_id: state.owner_program,
+            accounts: vec![
+                AccountMeta::new(creator.pubkey(), true),
+                AccountMeta::new(world_pda, false),
+                AccountMeta::new(system_program, false),
+                AccountMeta::new(Pubkey::new_from_array(RENT_ID.to_bytes()), false),
+            ],
+            data: create_world_ix_data,
+        };
+
+        let recent_blockhash = state.rpc_client.get_latest_blockhash().unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&creator.pubkey()),
+            &[&creator],
+            recent_blockhash,
+        );
+
+        state
+            .rpc_client
+            .send_and_confirm_transaction(&transaction)
+            .expect("failed to send txn");
+
+        let world_account = state.rpc_client.get_account(&world_pda).unwrap();
+        let header = bytemuck::pod_read_unaligned::<crate::state::WorldHeader>(
+            &world_account.data[..crate::state::WorldHeader::LEN],
+        );
+        assert_eq!(header.creator, creator.pubkey().to_bytes());
+        assert_eq!(header.name_hash, digest);
+        assert!(!header.is_delegated());
+        assert_eq!(
+            &world_account.data[crate::state::WorldHeader::LEN..],
+            world_state.to_bytes().as_slice()
+        );
+    }
+
     #[test]
     fn test_delegate_state_account() {
         let mut state = setup();
@@ -214,9 +292,17 @@ mod er_tests {
 
         // const MAX_LEN: usize = 128;
 
+        // a different Validator for speed
+        let delegation_args = crate::state::DelegationArgs {
+            commit_frequency_ms: 30_000u32.to_le_bytes(),
+            lifetime_secs: 0u32.to_le_bytes(),
+            validator: EU_VALIDATOR.to_bytes(),
+        };
+
         let delegate_ix_data = [
             vec![crate::instructions::MojoInstructions::DelegateAccount as u8],
             mojo_data.to_bytes(),
+            delegation_args.to_bytes(),
         ]
         .concat();
 
@@ -230,13 +316,13 @@ mod er_tests {
             accounts: vec![
                 AccountMeta::new(creator.pubkey(), true),   // creator/payer
                 AccountMeta::new(creator_account.0, false), // account to delegate
+                AccountMeta::new_readonly(state.world, false), // parent world
                 AccountMeta::new(owner_program, false),     // owner program
                 AccountMeta::new(buffer_account, false), // buffer PDA (created via invoke_signed)
                 AccountMeta::new(delegation_record, false), // delegation record
                 AccountMeta::new(delegation_metadata, false), // delegation metadata
                 AccountMeta::new(system_program, false), // system program
-                AccountMeta::new(delegation_program_id, false), // system program
-                AccountMeta::new(EU_VALIDATOR, false),   // a different Validator for speed
+                AccountMeta::new(delegation_program_id, false), // delegation program
             ],
             data: delegate_ix_data,
         };
@@ -301,6 +387,7 @@ mod er_tests {
             accounts: vec![
                 AccountMeta::new(creator.pubkey(), true),
                 AccountMeta::new(creator_account.0, false),
+                AccountMeta::new_readonly(state.world, false),
                 AccountMeta::new_readonly(
                     Pubkey::new_from_array(ephemeral_rollups_pinocchio::consts::MAGIC_CONTEXT_ID),
                     false,
@@ -375,6 +462,7 @@ mod er_tests {
             accounts: vec![
                 AccountMeta::new(creator.pubkey(), true),
                 AccountMeta::new(creator_account.0, false),
+                AccountMeta::new_readonly(state.world, false),
                 // AccountMeta::new(creator_account.0, false),
                 // AccountMeta::new(system_program, false),
                 // AccountMeta::new((RENT_ID), false),
@@ -414,7 +502,7 @@ mod er_tests {
         );
     }
 
-    // #[test]
+    #[test]
     fn test_commit_and_undelegate_account() {
         let mut state = setup();
 
@@ -449,10 +537,11 @@ mod er_tests {
             accounts: vec![
                 AccountMeta::new(creator.pubkey(), true),
                 AccountMeta::new(creator_account.0, false),
+                AccountMeta::new_readonly(state.world, false),
                 // AccountMeta::new(creator_account.0, false),
                 // AccountMeta::new(system_program, false),
                 // AccountMeta::new((RENT_ID), false),
-                AccountMeta::new_readonly(
+                AccountMeta::new(
                     Pubkey::new_from_array(ephemeral_rollups_pinocchio::consts::MAGIC_CONTEXT_ID),
                     false,
                 ),
@@ -480,4 +569,221 @@ mod er_tests {
             signature
         );
     }
+
+    /// Waits until `account` is owned by this program again on the base layer
+    fn wait_for_undelegation(rpc_client: &RpcClient, account: &Pubkey) {
+        for _ in 0..30 {
+            if let Ok(acc) = rpc_client.get_account(account) {
+                if acc.owner == PROGRAM_ID {
+                    return;
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_secs(1));
+        }
+        panic!("{} was not handed back to the base layer", account);
+    }
+
+    fn send(rpc_client: &RpcClient, payer: &Keypair, instruction: Instruction) {
+        let recent_blockhash = rpc_client
+            .get_latest_blockhash()
+            .expect("failed to get recent blockhash");
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[payer],
+            recent_blockhash,
+        );
+        rpc_client
+            .send_and_confirm_transaction(&transaction)
+            .expect("failed to send txn");
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let state = setup();
+        let creator = &state.creator;
+
+        // a fresh state under the world so the test can be rerun
+        let combined = encode_packed!(
+            b"round trip",
+            Keypair::new().pubkey().as_ref(),
+            creator.pubkey().as_ref()
+        );
+        let digest = compute_hash(&combined);
+        let state_pda = Pubkey::find_program_address(
+            &[&digest, state.world.as_ref(), creator.pubkey().as_ref()],
+            &PROGRAM_ID,
+        )
+        .0;
+        let delegation_program_id = Pubkey::new_from_array(DELEGATION_PROGRAM_ID);
+        let magic_context =
+            Pubkey::new_from_array(ephemeral_rollups_pinocchio::consts::MAGIC_CONTEXT_ID);
+        let magic_program =
+            Pubkey::new_from_array(ephemeral_rollups_pinocchio::consts::MAGIC_PROGRAM_ID);
+
+        let ix_data = |discriminator: crate::instructions::MojoInstructions,
+                       position: Option<MyPosition>| {
+            let mojo_data = crate::state::GenIxHandler {
+                seeds: digest,
+                size: MyPosition::LEN.to_le_bytes(),
+            };
+            [
+                vec![discriminator as u8],
+                mojo_data.to_bytes(),
+                position.map(|p| p.to_bytes()).unwrap_or_default(),
+            ]
+            .concat()
+        };
+
+        // create on base
+        send(
+            &state.rpc_client,
+            creator,
+            Instruction {
+                program_id: PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(creator.pubkey(), true),
+                    AccountMeta::new(state_pda, false),
+                    AccountMeta::new(state.world, false),
+                    AccountMeta::new_readonly(state.system_program, false),
+                    AccountMeta::new_readonly(Pubkey::new_from_array(RENT_ID.to_bytes()), false),
+                ],
+                data: ix_data(
+                    crate::instructions::MojoInstructions::CreateAccount,
+                    Some(MyPosition { x: 1, y: 1 }),
+                ),
+            },
+        );
+
+        let created = state.rpc_client.get_account(&state_pda).unwrap();
+        assert_eq!(
+            &created.data[crate::state::StateHeader::LEN..],
+            MyPosition { x: 1, y: 1 }.to_bytes().as_slice()
+        );
+
+        // delegate to the EU validator
+        send(
+            &state.rpc_client,
+            creator,
+            Instruction {
+                program_id: PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(creator.pubkey(), true),
+                    AccountMeta::new(state_pda, false),
+                    AccountMeta::new_readonly(state.world, false),
+                    AccountMeta::new_readonly(PROGRAM_ID, false),
+                    AccountMeta::new(
+                        Pubkey::find_program_address(&[BUFFER, state_pda.as_ref()], &PROGRAM_ID).0,
+                        false,
+                    ),
+                    AccountMeta::new(
+                        Pubkey::find_program_address(
+                            &[b"delegation", state_pda.as_ref()],
+                            &delegation_program_id,
+                        )
+                        .0,
+                        false,
+                    ),
+                    AccountMeta::new(
+                        Pubkey::find_program_address(
+                            &[b"delegation-metadata", state_pda.as_ref()],
+                            &delegation_program_id,
+                        )
+                        .0,
+                        false,
+                    ),
+                    AccountMeta::new_readonly(state.system_program, false),
+                    AccountMeta::new_readonly(delegation_program_id, false),
+                ],
+                data: [
+                    ix_data(crate::instructions::MojoInstructions::DelegateAccount, None),
+                    crate::state::DelegationArgs {
+                        commit_frequency_ms: 0u32.to_le_bytes(), // world config or default
+                        lifetime_secs: 0u32.to_le_bytes(),
+                        validator: EU_VALIDATOR.to_bytes(),
+                    }
+                    .to_bytes(),
+                ]
+                .concat(),
+            },
+        );
+
+        let delegated = state.rpc_client.get_account(&state_pda).unwrap();
+        assert_eq!(delegated.owner, delegation_program_id);
+
+        // update on the rollup
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        send(
+            &state.rpc_er_client,
+            creator,
+            Instruction {
+                program_id: PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(creator.pubkey(), true),
+                    AccountMeta::new(state_pda, false),
+                    AccountMeta::new_readonly(state.world, false),
+                ],
+                data: ix_data(
+                    crate::instructions::MojoInstructions::UpdateDelegatedAccount,
+                    Some(MyPosition { x: 2, y: 2 }),
+                ),
+            },
+        );
+
+        let on_rollup = state.rpc_er_client.get_account(&state_pda).unwrap();
+        assert_eq!(
+            &on_rollup.data[crate::state::StateHeader::LEN..],
+            MyPosition { x: 2, y: 2 }.to_bytes().as_slice()
+        );
+
+        // commit and undelegate, the delegation program calls back to restore the PDA
+        send(
+            &state.rpc_er_client,
+            creator,
+            Instruction {
+                program_id: PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(creator.pubkey(), true),
+                    AccountMeta::new(state_pda, false),
+                    AccountMeta::new_readonly(state.world, false),
+                    AccountMeta::new(magic_context, false),
+                    AccountMeta::new_readonly(magic_program, false),
+                ],
+                data: ix_data(
+                    crate::instructions::MojoInstructions::UndelegateAccount,
+                    None,
+                ),
+            },
+        );
+        wait_for_undelegation(&state.rpc_client, &state_pda);
+
+        let restored = state.rpc_client.get_account(&state_pda).unwrap();
+        assert_eq!(
+            &restored.data[crate::state::StateHeader::LEN..],
+            MyPosition { x: 2, y: 2 }.to_bytes().as_slice()
+        );
+
+        // update on base again
+        send(
+            &state.rpc_client,
+            creator,
+            Instruction {
+                program_id: PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(creator.pubkey(), true),
+                    AccountMeta::new(state_pda, false),
+                    AccountMeta::new_readonly(state.world, false),
+                ],
+                data: ix_data(
+                    crate::instructions::MojoInstructions::UpdateDelegatedAccount,
+                    Some(MyPosition { x: 3, y: 3 }),
+                ),
+            },
+        );
+        let updated = state.rpc_client.get_account(&state_pda).unwrap();
+        assert_eq!(
+            &updated.data[crate::state::StateHeader::LEN..],
+            MyPosition { x: 3, y: 3 }.to_bytes().as_slice()
+        );
+    }
 }
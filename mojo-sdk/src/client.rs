@@ -1,26 +1,61 @@
 //! Main SDK client for interacting with the Mojo program
 //!
-use crate::{errors::MojoSDKError, state::MojoState, world::*};
+use crate::{
+    arithmetic::{FieldOp, IntegerField, StateField},
+    delegation::{delegation_record_pda, DelegationOptions, DELEGATION_PROGRAM_ID},
+    errors::MojoSDKError,
+    session::{Session, SessionOptions},
+    shared_state::SharedState,
+    sponsor::PartiallySignedTransaction,
+    state::{MojoState, PatchSegment},
+    subscription::StateSubscription,
+    transport::Transport,
+    types::{AccessMode, WorldHeader},
+    world::*,
+};
 
 use solana_keypair::Keypair;
 use solana_pubkey::{pubkey, Pubkey};
 use solana_rpc_client::rpc_client::RpcClient;
+use solana_signer::Signer;
 
 const PROGRAM_ID: Pubkey = pubkey!("7iMdvW8A4Tw3yxjbXjpx4b8LTW13EQLB4eTmPyqRvxzM");
 
 /// Client Wrapper to interact with the Mojo Solana Program
+///
+/// Holds both the base layer and the ephemeral rollup endpoints and routes every
+/// read and write to the one currently holding the account
 pub struct SdkClient {
-    pub client: RpcClient,
+    /// Base layer endpoint, used for account creation and delegation
+    pub client: Box<dyn Transport>,
+    /// Ephemeral rollup endpoint, used for delegated accounts, commits and undelegation
+    pub er_client: Box<dyn Transport>,
+    /// Base layer websocket endpoint, used for subscriptions
+    pub ws_url: String,
+    /// Ephemeral rollup websocket endpoint, used for subscriptions
+    pub er_ws_url: String,
     pub program_id: Pubkey,
+    /// Pays the fees and rent of every transaction in place of the signing owner, if set
+    sponsor: Option<Keypair>,
+}
+
+/// Where an account currently lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    /// Solana base layer
+    Base,
+    /// MagicBlock ephemeral rollup
+    Ephemeral,
 }
 
 pub enum RpcType {
     Main,
     Dev,
     ERMain,
-    MBDev, // MagicBlock Devnet
-    ERDev, // Ephemeral Rollup Devnet
-    Surf,  // Localnet surfpool
+    MBDev,   // MagicBlock Devnet
+    ERDev,   // Ephemeral Rollup Devnet
+    Surf,    // Localnet surfpool
+    ERLocal, // Localnet ephemeral validator
 }
 
 impl RpcType {
@@ -32,28 +67,159 @@ impl RpcType {
             RpcType::MBDev => "https://devnet-rpc.magicblock.app",
             RpcType::ERDev => "https://devnet.magicblock.app",
             RpcType::Surf => "http://127.0.0.1:8899",
+            RpcType::ERLocal => "http://127.0.0.1:7799",
+        }
+    }
+
+    /// The websocket (pubsub) url of this endpoint
+    pub fn ws_url(&self) -> &str {
+        match self {
+            RpcType::Main => "wss://api.mainnet-beta.solana.com",
+            RpcType::Dev => "wss://api.devnet.solana.com",
+            RpcType::ERMain => "",
+            RpcType::MBDev => "wss://devnet-rpc.magicblock.app",
+            RpcType::ERDev => "wss://devnet.magicblock.app",
+            RpcType::Surf => "ws://127.0.0.1:8900",
+            RpcType::ERLocal => "ws://127.0.0.1:7800",
+        }
+    }
+
+    /// The ephemeral rollup endpoint paired with this endpoint
+    ///
+    /// No public ephemeral rollup is known for mainnet, `ERMain` carries no url
+    pub fn ephemeral(&self) -> RpcType {
+        match self {
+            RpcType::Main | RpcType::ERMain => RpcType::ERMain,
+            RpcType::Dev | RpcType::MBDev | RpcType::ERDev => RpcType::ERDev,
+            RpcType::Surf | RpcType::ERLocal => RpcType::ERLocal,
         }
     }
 }
 
 impl SdkClient {
-    /// Create a new SDK client
+    /// Create a new SDK client, pairing the base layer endpoint with its ephemeral rollup
+    ///
+    /// Fails for `RpcType::Main`, which has no ephemeral rollup to pair with, build a mainnet
+    /// client with [`SdkClient::with_transports`] instead
     ///
     /// # Arguments
-    /// * `rpc_url` - The Solana RPC endpoint URL
-    /// * `program_id` - The Mojo program ID
+    /// * `rpc_type` - The base layer endpoint
     ///
     /// # Example
     /// ```
     /// // use mojo_sdk::{SdkClient, RpcType};
     /// // use solana_sdk::pubkey;
     ///
-    /// // let client = SdkClient::new(RpcType::DEV);
+    /// // let client = SdkClient::new(RpcType::Dev)?;
+    /// ```
+    pub fn new(rpc_type: RpcType) -> Result<Self, MojoSDKError> {
+        let er_rpc_type = rpc_type.ephemeral();
+        Self::new_with_er(rpc_type, er_rpc_type)
+    }
+
+    /// Create a new SDK client with an explicit ephemeral rollup endpoint
+    ///
+    /// Fails when either endpoint has no url
+    ///
+    /// # Arguments
+    /// * `rpc_type` - The base layer endpoint
+    /// * `er_rpc_type` - The ephemeral rollup endpoint
+    ///
+    /// # Example
+    /// ```
+    /// // use mojo_sdk::{SdkClient, RpcType};
+    ///
+    /// // let client = SdkClient::new_with_er(RpcType::Dev, RpcType::ERDev)?;
+    /// ```
+    pub fn new_with_er(rpc_type: RpcType, er_rpc_type: RpcType) -> Result<Self, MojoSDKError> {
+        // routed writes to delegated accounts would fail with an opaque transport error
+        for endpoint in [&rpc_type, &er_rpc_type] {
+            if endpoint.url().is_empty() {
+                return Err(MojoSDKError::InvalidEndpoint(
+                    "no url is known for this endpoint, use SdkClient::with_transports".to_string(),
+                ));
+            }
+        }
+        Ok(Self {
+            client: Box::new(RpcClient::new(rpc_type.url())),
+            er_client: Box::new(RpcClient::new(er_rpc_type.url())),
+            ws_url: rpc_type.ws_url().to_string(),
+            er_ws_url: er_rpc_type.ws_url().to_string(),
+            program_id: PROGRAM_ID,
+            sponsor: None,
+        })
+    }
+
+    /// Create a new SDK client on top of custom transports
+    ///
+    /// Subscriptions still go through the websocket urls, which are left empty
+    ///
+    /// # Example
+    /// ```
+    /// // use mojo_sdk::{SdkClient, LiteSvmTransport};
+    ///
+    /// // let svm = Arc::new(LiteSvmTransport::with_mojo_program(program_id)?);
+    /// // let client = SdkClient::with_transports(Box::new(svm.clone()), Box::new(svm), program_id);
+    /// ```
+    pub fn with_transports(
+        transport: Box<dyn Transport>,
+        er_transport: Box<dyn Transport>,
+        program_id: Pubkey,
+    ) -> Self {
+        Self {
+            client: transport,
+            er_client: er_transport,
+            ws_url: String::new(),
+            er_ws_url: String::new(),
+            program_id,
+            sponsor: None,
+        }
+    }
+
+    /// Let `sponsor` pay the fees and rent of every transaction sent by this client
+    ///
+    /// Owners keep signing for their states, so players holding no SOL can create, write
+    /// and delegate them. Used by game servers onboarding players.
+    ///
+    /// # Example
+    /// ```
+    /// // use mojo_sdk::{SdkClient, RpcType};
+    ///
+    /// // let client = SdkClient::new(RpcType::Dev)?.with_sponsor(server_keypair);
+    /// // client.write_state(&world, "player", &unfunded_player, Position{x:0, y:0})?;
     /// ```
-    pub fn new(rpc_type: RpcType) -> Self {
-        let client = RpcClient::new(rpc_type.url());
-        let program_id = PROGRAM_ID;
-        Self { client, program_id }
+    pub fn with_sponsor(mut self, sponsor: Keypair) -> Self {
+        self.sponsor = Some(sponsor);
+        self
+    }
+
+    /// The sponsor paying for the transactions of this client, if any
+    pub fn sponsor(&self) -> Option<&Keypair> {
+        self.sponsor.as_ref()
+    }
+
+    /// The account paying for a transaction signed by `signer`
+    pub fn payer_for(&self, signer: &Pubkey) -> Pubkey {
+        self.sponsor
+            .as_ref()
+            .map_or(*signer, |sponsor| sponsor.pubkey())
+    }
+
+    /// Create a new SDK client running the Mojo program in-process on LiteSVM
+    ///
+    /// The same LiteSVM instance stands in for both the base layer and the ephemeral
+    /// rollup. Returns the client together with a funded payer keypair.
+    #[cfg(any(test, feature = "litesvm"))]
+    pub fn litesvm() -> Result<(Self, Keypair), MojoSDKError> {
+        use crate::transport::LiteSvmTransport;
+        use std::sync::Arc;
+
+        let svm = Arc::new(LiteSvmTransport::with_mojo_program(PROGRAM_ID)?);
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), 10_000_000_000)?;
+
+        let client = Self::with_transports(Box::new(svm.clone()), Box::new(svm), PROGRAM_ID);
+        Ok((client, payer))
     }
 
     /// Create a new world
@@ -67,7 +233,7 @@ impl SdkClient {
     /// // use mojo_sdk::{SdkClient, RpcType};
     /// // use solana_sdk::pubkey;
     ///
-    /// // let client = SdkClient::new(RpcType::DEV);
+    /// // let client = SdkClient::new(RpcType::Dev)?;
     /// // let world = client.create_world(creator_keypair, "New World", Position{x:0, y:0});
     pub fn create_world<T: MojoState>(
         &self,
@@ -78,6 +244,24 @@ impl SdkClient {
         World::create_world(&self, creator, world_name, initial_world_state)
     }
 
+    /// Open an existing world created by `creator` under `world_name`
+    ///
+    /// # Example
+    /// ```
+    /// // use mojo_sdk::{SdkClient, RpcType};
+    ///
+    /// // let client = SdkClient::new(RpcType::Dev)?;
+    /// // let world = client.open_world(&opponent_pubkey, "New World")?;
+    /// ```
+    pub fn open_world(&self, creator: &Pubkey, world_name: &str) -> Result<World, MojoSDKError> {
+        World::open(self, creator, world_name)
+    }
+
+    /// Open an existing world from its PDA
+    pub fn world_from_pda(&self, world_pda: &Pubkey) -> Result<World, MojoSDKError> {
+        World::from_pda(self, world_pda)
+    }
+
     /// Write state of world
     ///
     /// # Arguments
@@ -90,7 +274,7 @@ impl SdkClient {
     /// // use mojo_sdk::{SdkClient, RpcType};
     /// // use solana_sdk::pubkey;
     ///
-    /// // let client = SdkClient::new(RpcType::DEV);
+    /// // let client = SdkClient::new(RpcType::Dev)?;
     /// // let world = client.write_state(world, "my beast boxer", creator_keypair, Position{x:0, y:0});
     pub fn write_state<T: MojoState>(
         &self, // client
@@ -102,11 +286,72 @@ impl SdkClient {
         world.write_state(&self, state_name, owner, state)
     }
 
+    /// Write a state only if it is still at `expected_version`
+    pub fn write_state_if_version<T: MojoState>(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Keypair,
+        state: T,
+        expected_version: u64,
+    ) -> Result<(), MojoSDKError> {
+        world.write_state_if_version(self, state_name, owner, state, expected_version)
+    }
+
+    /// Add `delta` to an integer field of a state owned by `owner`
+    pub fn increment<T, F: IntegerField>(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Keypair,
+        field: StateField<T, F>,
+        delta: F,
+    ) -> Result<(), MojoSDKError> {
+        world.increment(self, state_name, owner, field, delta)
+    }
+
+    /// Apply `ops` to integer fields of a state owned by `owner`
+    pub fn apply_field_ops(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Keypair,
+        ops: &[FieldOp],
+    ) -> Result<(), MojoSDKError> {
+        world.apply_field_ops(self, state_name, owner, ops)
+    }
+
+    /// Read a state along with its version
+    pub fn read_versioned_state<T: MojoState>(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Pubkey,
+    ) -> Result<(T, u64), MojoSDKError> {
+        world.read_versioned_state(self, state_name, owner)
+    }
+
+    /// Write only the `segments` of a state owned by `owner`
+    pub fn patch_state(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Keypair,
+        segments: &[PatchSegment],
+    ) -> Result<(), MojoSDKError> {
+        world.patch_state(self, state_name, owner, segments)
+    }
+
     /// Read the data stored in the world's PDA
     pub fn read_world<T: MojoState>(&self, world: &World) -> Result<T, MojoSDKError> {
         world.read_world_state(self)
     }
 
+    /// Read the metadata header stored in the world's PDA
+    pub fn read_world_header(&self, world: &World) -> Result<WorldHeader, MojoSDKError> {
+        world.read_header(self)
+    }
+
     /// Read a delegated state PDA owned by `owner`
     pub fn read_delegated_state<T: MojoState>(
         &self,
@@ -117,9 +362,297 @@ impl SdkClient {
         world.read_delegated_state(self, state_name, owner)
     }
 
+    /// List every state account of a world, typed access through [`StateEntry::state`]
+    ///
+    /// # Example
+    /// ```
+    /// // let positions: Vec<Position> = client
+    /// //     .list_states(&world)?
+    /// //     .iter()
+    /// //     .filter_map(|entry| entry.state::<Position>().ok())
+    /// //     .collect();
+    /// ```
+    pub fn list_states(&self, world: &World) -> Result<Vec<StateEntry>, MojoSDKError> {
+        world.list_states(self)
+    }
+
+    /// List every state account of a world owned by `owner`
+    pub fn states_of_owner(
+        &self,
+        world: &World,
+        owner: &Pubkey,
+    ) -> Result<Vec<StateEntry>, MojoSDKError> {
+        world.states_of_owner(self, owner)
+    }
+
+    /// Subscribe to live changes of a state PDA owned by `owner`
+    ///
+    /// # Example
+    /// ```
+    /// // let positions = client.subscribe_state::<Position>(&world, "player_position", &owner)?;
+    /// // // once per frame
+    /// // if let Some(update) = positions.latest() {
+    /// //     player.position = update.state;
+    /// // }
+    /// ```
+    pub fn subscribe_state<T: MojoState + Send + 'static>(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Pubkey,
+    ) -> Result<StateSubscription<T>, MojoSDKError> {
+        world.subscribe_state(self, state_name, owner)
+    }
+
+    /// Delegate the world PDA to an ephemeral rollup validator
+    pub fn delegate_world(
+        &self,
+        world: &World,
+        creator: &Keypair,
+        validator: Pubkey,
+    ) -> Result<(), MojoSDKError> {
+        world.delegate(self, creator, validator)
+    }
+
+    /// Delegate the world PDA to an ephemeral rollup with custom delegation settings
+    pub fn delegate_world_with(
+        &self,
+        world: &World,
+        creator: &Keypair,
+        options: DelegationOptions,
+    ) -> Result<(), MojoSDKError> {
+        world.delegate_with(self, creator, options)
+    }
+
+    /// Commit the world PDA from the ephemeral rollup to the base layer
+    pub fn commit_world(&self, world: &World, creator: &Keypair) -> Result<(), MojoSDKError> {
+        world.commit(self, creator)
+    }
+
+    /// Commit the world PDA and return it to the base layer
+    pub fn undelegate_world(&self, world: &World, creator: &Keypair) -> Result<(), MojoSDKError> {
+        world.undelegate(self, creator)
+    }
+
+    /// Delegate a state PDA owned by `owner` to an ephemeral rollup validator
+    pub fn delegate_state(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Keypair,
+        validator: Pubkey,
+    ) -> Result<(), MojoSDKError> {
+        world.delegate_state(self, state_name, owner, validator)
+    }
+
+    /// Delegate a state PDA owned by `owner` with custom delegation settings
+    pub fn delegate_state_with(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Keypair,
+        options: DelegationOptions,
+    ) -> Result<(), MojoSDKError> {
+        world.delegate_state_with(self, state_name, owner, options)
+    }
+
+    /// Commit a delegated state PDA to the base layer
+    pub fn commit_state(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Keypair,
+    ) -> Result<(), MojoSDKError> {
+        world.commit_state(self, state_name, owner)
+    }
+
+    /// Commit a delegated state PDA and return it to the base layer
+    pub fn undelegate_state(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Keypair,
+    ) -> Result<(), MojoSDKError> {
+        world.undelegate_state(self, state_name, owner)
+    }
+
+    /// Delegate a world and the states named in `states`, given as (state name, owner), the
+    /// owners other than the creator co-sign as `owners`
+    pub fn delegate_all(
+        &self,
+        world: &World,
+        creator: &Keypair,
+        states: &[(&str, Pubkey)],
+        owners: &[&Keypair],
+        options: DelegationOptions,
+    ) -> Result<(), MojoSDKError> {
+        world.delegate_all(self, creator, states, owners, options)
+    }
+
+    /// Commit a world and the states named in `states` to the base layer, co-signed by `owners`
+    pub fn commit_all(
+        &self,
+        world: &World,
+        creator: &Keypair,
+        states: &[(&str, Pubkey)],
+        owners: &[&Keypair],
+    ) -> Result<(), MojoSDKError> {
+        world.commit_all(self, creator, states, owners)
+    }
+
+    /// Commit a world and the states named in `states` and return them to the base layer,
+    /// co-signed by `owners`
+    pub fn undelegate_all(
+        &self,
+        world: &World,
+        creator: &Keypair,
+        states: &[(&str, Pubkey)],
+        owners: &[&Keypair],
+    ) -> Result<(), MojoSDKError> {
+        world.undelegate_all(self, creator, states, owners)
+    }
+
+    /// Resize a state owned by `owner` to hold `size` bytes
+    pub fn resize_state(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Keypair,
+        size: usize,
+    ) -> Result<(), MojoSDKError> {
+        world.resize_state(self, state_name, owner, size)
+    }
+
+    /// Close a world and all of its states, returning the rent to the creator
+    pub fn close_world(&self, world: &World, creator: &Keypair) -> Result<(), MojoSDKError> {
+        world.close(self, creator)
+    }
+
+    /// Close a state owned by `owner`, returning the rent to them
+    pub fn close_state(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Keypair,
+    ) -> Result<(), MojoSDKError> {
+        world.close_state(self, state_name, owner)
+    }
+
+    /// Create a shared state in a world, writable by the players its access list allows
+    ///
+    /// # Example
+    /// ```
+    /// // let board = client.create_shared_state(&world, &creator, "board", AccessMode::Listed, Board::zeroed())?;
+    /// // board.add_writer(&client, &creator, &opponent.pubkey())?;
+    /// ```
+    pub fn create_shared_state<T: MojoState>(
+        &self,
+        world: &World,
+        creator: &Keypair,
+        state_name: &str,
+        mode: AccessMode,
+        initial_state: T,
+    ) -> Result<SharedState, MojoSDKError> {
+        SharedState::create(self, world, creator, state_name, mode, initial_state)
+    }
+
+    /// Get a handle on an existing shared state of a world
+    pub fn shared_state(&self, world: &World, state_name: &str) -> SharedState {
+        SharedState::open(self, world, state_name)
+    }
+
+    /// Authorize a session key to write the states of `owner` in a world
+    ///
+    /// # Example
+    /// ```
+    /// // let session = client.create_session(&world, &player, SessionOptions::new())?;
+    /// // session.write_state(&client, "position", Position { x: 1, y: 1 })?;
+    /// ```
+    pub fn create_session(
+        &self,
+        world: &World,
+        owner: &Keypair,
+        options: SessionOptions,
+    ) -> Result<Session, MojoSDKError> {
+        Session::create(self, world, owner, options)
+    }
+
+    /// Persist a state under the schema of `T`, migrating it from an older schema version
+    pub fn migrate_state<T: MojoState>(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Keypair,
+    ) -> Result<T, MojoSDKError> {
+        world.migrate_state(self, state_name, owner)
+    }
+
+    /// Build a state write signed by `owner` only, for `sponsor` to co-sign and pay
+    pub fn sponsored_write_state<T: MojoState>(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Keypair,
+        sponsor: &Pubkey,
+        state: T,
+    ) -> Result<PartiallySignedTransaction, MojoSDKError> {
+        world.sponsored_write_state(self, state_name, owner, sponsor, state)
+    }
+
+    /// Build a state delegation signed by `owner` only, for `sponsor` to co-sign and pay
+    pub fn sponsored_delegate_state(
+        &self,
+        world: &World,
+        state_name: &str,
+        owner: &Keypair,
+        sponsor: &Pubkey,
+        options: DelegationOptions,
+    ) -> Result<PartiallySignedTransaction, MojoSDKError> {
+        world.sponsored_delegate_state(self, state_name, owner, sponsor, options)
+    }
+
+    /// Revoke the session of `owner` in a world
+    pub fn revoke_session(&self, world: &World, owner: &Keypair) -> Result<(), MojoSDKError> {
+        Session::revoke_by_owner(self, world, owner)
+    }
+
     /// Get a reference to the RPC client
-    pub fn client(&self) -> &RpcClient {
-        &self.client
+    pub fn client(&self) -> &dyn Transport {
+        self.client.as_ref()
+    }
+
+    /// Get a reference to the ephemeral rollup RPC client
+    pub fn er_client(&self) -> &dyn Transport {
+        self.er_client.as_ref()
+    }
+
+    /// Whether `account` is delegated, i.e. has a delegation record owned by the delegation program
+    pub fn is_delegated(&self, account: &Pubkey) -> Result<bool, MojoSDKError> {
+        let record = self.client.get_account(&delegation_record_pda(account))?;
+
+        Ok(record.is_some_and(|record| record.owner == DELEGATION_PROGRAM_ID))
+    }
+
+    /// Find which endpoint currently holds `account`
+    pub fn endpoint_for(&self, account: &Pubkey) -> Result<Endpoint, MojoSDKError> {
+        if self.is_delegated(account)? {
+            Ok(Endpoint::Ephemeral)
+        } else {
+            Ok(Endpoint::Base)
+        }
+    }
+
+    /// Get the RPC client of an endpoint
+    pub fn rpc(&self, endpoint: Endpoint) -> &dyn Transport {
+        match endpoint {
+            Endpoint::Base => self.client(),
+            Endpoint::Ephemeral => self.er_client(),
+        }
+    }
+
+    /// Get the RPC client that currently holds `account`
+    pub fn rpc_for(&self, account: &Pubkey) -> Result<&dyn Transport, MojoSDKError> {
+        Ok(self.rpc(self.endpoint_for(account)?))
     }
 
     /// Get the program ID
@@ -1,5 +1,7 @@
 //! Error types for the Mojo SDK
 
+use solana_instruction::error::InstructionError;
+use solana_transaction_error::TransactionError;
 use thiserror::Error;
 
 /// Errors that can occur when using the Mojo SDK
@@ -11,6 +13,9 @@ pub enum MojoSDKError {
     #[error("Solana SDK error: {0}")]
     SolanaSdk(String),
 
+    #[error("Invalid endpoint: {0}")]
+    InvalidEndpoint(String),
+
     #[error("Serialization error: {0}")]
     Serialization(String),
 
@@ -32,9 +37,153 @@ pub enum MojoSDKError {
     #[error("Account owned by different program: {0}")]
     InvalidAccountOwner(String),
 
-    #[error("Transaction failed: {0}")]
-    TransactionFailed(String),
+    #[error("Subscription error: {0}")]
+    Subscription(String),
+
+    #[error("State version conflict: expected version {expected}")]
+    VersionConflict {
+        expected: u64,
+        /// The version found on chain after the write was rejected, if it could be read
+        found: Option<u64>,
+    },
+
+    #[error("State schema mismatch: {0}")]
+    SchemaMismatch(String),
+
+    #[error("Transaction failed: {message}")]
+    TransactionFailed {
+        message: String,
+        /// The Mojo program error that made the transaction fail, if any
+        cause: Option<MojoProgramError>,
+    },
 
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
+
+impl MojoSDKError {
+    /// Build a `TransactionFailed` error, decoding the Mojo program error out of `error`
+    pub fn transaction_failed(message: String, error: Option<&TransactionError>) -> Self {
+        MojoSDKError::TransactionFailed {
+            message,
+            cause: error.and_then(MojoProgramError::from_transaction_error),
+        }
+    }
+
+    /// The Mojo program error behind a failed transaction
+    pub fn program_error(&self) -> Option<MojoProgramError> {
+        match self {
+            MojoSDKError::TransactionFailed { cause, .. } => *cause,
+            _ => None,
+        }
+    }
+}
+
+/// Errors returned by the Mojo program as custom program error codes
+///
+/// Mirrors `MojoError` in `mojo-program`, the codes are stable and start at 6000 so the
+/// custom errors of other programs (the system program raises 0 and up) are never taken for
+/// Mojo errors
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum MojoProgramError {
+    #[error("account does not match the derived PDA")]
+    InvalidPda = 6000,
+
+    #[error("account or state has the wrong size")]
+    InvalidAccountSize = 6001,
+
+    #[error("creator did not sign the transaction")]
+    MissingSigner = 6002,
+
+    #[error("account is already initialized")]
+    AlreadyInitialized = 6003,
+
+    #[error("account is not delegated")]
+    NotDelegated = 6004,
+
+    #[error("unknown instruction")]
+    UnknownInstruction = 6005,
+
+    #[error("invalid instruction data")]
+    InvalidInstructionData = 6006,
+
+    #[error("account is not initialized")]
+    UninitializedAccount = 6007,
+
+    #[error("state belongs to another world")]
+    WorldMismatch = 6008,
+
+    #[error("delegation expired, it can only be undelegated")]
+    DelegationExpired = 6009,
+
+    #[error("signer is not allowed to act on this account")]
+    Unauthorized = 6010,
+
+    #[error("account is delegated")]
+    AccountDelegated = 6011,
+
+    #[error("patch segment or field is out of the state bounds")]
+    PatchOutOfBounds = 6012,
+
+    #[error("state was written since the expected version")]
+    VersionConflict = 6013,
+
+    #[error("arithmetic result does not fit the field")]
+    ArithmeticOverflow = 6014,
+
+    #[error("access list is full")]
+    AclFull = 6015,
+
+    #[error("session key expired")]
+    SessionExpired = 6016,
+
+    #[error("session key used up its actions")]
+    SessionLimitReached = 6017,
+
+    #[error("migration does not move the state to a newer schema of its type")]
+    InvalidSchema = 6018,
+}
+
+impl MojoProgramError {
+    /// Decode a `ProgramError::Custom` code
+    pub fn from_code(code: u32) -> Option<Self> {
+        Some(match code {
+            6000 => MojoProgramError::InvalidPda,
+            6001 => MojoProgramError::InvalidAccountSize,
+            6002 => MojoProgramError::MissingSigner,
+            6003 => MojoProgramError::AlreadyInitialized,
+            6004 => MojoProgramError::NotDelegated,
+            6005 => MojoProgramError::UnknownInstruction,
+            6006 => MojoProgramError::InvalidInstructionData,
+            6007 => MojoProgramError::UninitializedAccount,
+            6008 => MojoProgramError::WorldMismatch,
+            6009 => MojoProgramError::DelegationExpired,
+            6010 => MojoProgramError::Unauthorized,
+            6011 => MojoProgramError::AccountDelegated,
+            6012 => MojoProgramError::PatchOutOfBounds,
+            6013 => MojoProgramError::VersionConflict,
+            6014 => MojoProgramError::ArithmeticOverflow,
+            6015 => MojoProgramError::AclFull,
+            6016 => MojoProgramError::SessionExpired,
+            6017 => MojoProgramError::SessionLimitReached,
+            6018 => MojoProgramError::InvalidSchema,
+            _ => return None,
+        })
+    }
+
+    /// Get the custom error code
+    pub fn code(&self) -> u32 {
+        *self as u32
+    }
+
+    /// Decode the custom error of a failed instruction
+    pub fn from_transaction_error(error: &TransactionError) -> Option<Self> {
+        match error {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+                Self::from_code(*code)
+            }
+            _ => None,
+        }
+    }
+}
@@ -23,18 +23,34 @@
 //! ```
 //!
 
+// Allow the derive macros to refer to `::mojo_sdk` from within this crate
+extern crate self as mojo_sdk;
+
 // Declare Modules
 mod client;
 mod errors;
 mod sdk;
 mod tests;
+mod transport;
 mod types;
 mod utils;
 
 // Re-export mods
-pub use client::SdkClient;
+pub use client::{Endpoint, RpcType, SdkClient};
 pub use errors::*;
 pub use sdk::*;
+pub use transport::*;
 pub use types::*;
 
+// Re-export macros
+pub use mojo_macros::{mojo_state, MojoState};
+
+/// Items used by code generated from the Mojo macros, not part of the public API
+#[doc(hidden)]
+pub mod __private {
+    pub use bytemuck;
+    pub use solana_keypair::Keypair;
+    pub use solana_pubkey::Pubkey;
+}
+
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,114 @@
+//! Typed integer fields of a state and the atomic operations applied to them on-chain
+//!
+//! The program combines the operand with the value it finds in the account, so concurrent
+//! writers on the ephemeral rollup never lose each other's updates the way a read, modify
+//! and write from the client would.
+
+use std::marker::PhantomData;
+
+/// Operations the program applies to a field, results that do not fit the field fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ArithmeticOp {
+    Add = 0,
+    Sub = 1,
+    Min = 2,
+    Max = 3,
+    Set = 4,
+}
+
+/// Little endian integers the program can apply [`ArithmeticOp`]s to
+pub trait IntegerField: bytemuck::Pod {
+    /// Integer type tag understood by the program
+    const TYPE_TAG: u8;
+}
+
+macro_rules! impl_integer_field {
+    ($($type:ty => $tag:expr),* $(,)?) => {
+        $(impl IntegerField for $type {
+            const TYPE_TAG: u8 = $tag;
+        })*
+    };
+}
+
+impl_integer_field!(u8 => 0, u16 => 1, u32 => 2, u64 => 3, i8 => 4, i16 => 5, i32 => 6, i64 => 7);
+
+/// A field of type `F` at a fixed offset in the state `T`
+///
+/// `#[derive(MojoState)]` generates a `field_<name>()` constructor for every named field
+pub struct StateField<T, F> {
+    offset: usize,
+    _marker: PhantomData<fn() -> (T, F)>,
+}
+
+impl<T, F> Clone for StateField<T, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, F> Copy for StateField<T, F> {}
+
+impl<T, F> StateField<T, F> {
+    /// Field at `offset` bytes from the start of the state
+    ///
+    /// Prefer the derived constructors, the offset is not checked against the layout of `T`
+    pub const fn new(offset: usize) -> Self {
+        Self {
+            offset,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Offset of the field from the start of the state
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<T, F: IntegerField> StateField<T, F> {
+    /// Add `value` to the field
+    pub fn add(self, value: F) -> FieldOp {
+        FieldOp::new(ArithmeticOp::Add, self, value)
+    }
+
+    /// Subtract `value` from the field
+    pub fn sub(self, value: F) -> FieldOp {
+        FieldOp::new(ArithmeticOp::Sub, self, value)
+    }
+
+    /// Lower the field to `value` if it is above it
+    pub fn min(self, value: F) -> FieldOp {
+        FieldOp::new(ArithmeticOp::Min, self, value)
+    }
+
+    /// Raise the field to `value` if it is below it
+    pub fn max(self, value: F) -> FieldOp {
+        FieldOp::new(ArithmeticOp::Max, self, value)
+    }
+
+    /// Overwrite the field with `value`
+    pub fn set(self, value: F) -> FieldOp {
+        FieldOp::new(ArithmeticOp::Set, self, value)
+    }
+}
+
+/// One operation on one integer field, see [`crate::World::apply_field_ops`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldOp {
+    pub op: ArithmeticOp,
+    pub type_tag: u8,
+    pub offset: usize,
+    pub operand: Vec<u8>,
+}
+
+impl FieldOp {
+    fn new<T, F: IntegerField>(op: ArithmeticOp, field: StateField<T, F>, value: F) -> Self {
+        Self {
+            op,
+            type_tag: F::TYPE_TAG,
+            offset: field.offset,
+            operand: bytemuck::bytes_of(&value).to_vec(),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! MagicBlock accounts needed to move Mojo PDAs to and from an ephemeral rollup
+
+use std::time::Duration;
+
+use solana_pubkey::{pubkey, Pubkey};
+
+use crate::types::{derive_pda, DelegationArgs};
+
+/// MagicBlock delegation program, owner of delegated accounts on the base layer
+pub const DELEGATION_PROGRAM_ID: Pubkey = pubkey!("DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh");
+/// MagicBlock magic program, schedules commits on the ephemeral rollup
+pub const MAGIC_PROGRAM_ID: Pubkey = pubkey!("Magic11111111111111111111111111111111111111");
+/// MagicBlock magic context account, holds scheduled commits on the ephemeral rollup
+pub const MAGIC_CONTEXT_ID: Pubkey = pubkey!("MagicContext1111111111111111111111111111111");
+
+/// MagicBlock EU devnet validator
+pub const EU_DEVNET_VALIDATOR: Pubkey = pubkey!("MEUGGrYPxKk17hCr7wpT6s8dtNokZj5U2L57vjYMS8e");
+/// MagicBlock local ER validator
+pub const LOCAL_VALIDATOR: Pubkey = pubkey!("mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev");
+
+const BUFFER_SEED: &[u8] = b"buffer";
+const DELEGATION_RECORD_SEED: &[u8] = b"delegation";
+const DELEGATION_METADATA_SEED: &[u8] = b"delegation-metadata";
+
+/// Delegation record PDA: ["delegation", delegated_account] under the delegation program
+pub fn delegation_record_pda(delegated_account: &Pubkey) -> Pubkey {
+    derive_pda(
+        &[DELEGATION_RECORD_SEED, delegated_account.as_ref()],
+        &DELEGATION_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Delegation metadata PDA: ["delegation-metadata", delegated_account] under the delegation program
+pub fn delegation_metadata_pda(delegated_account: &Pubkey) -> Pubkey {
+    derive_pda(
+        &[DELEGATION_METADATA_SEED, delegated_account.as_ref()],
+        &DELEGATION_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Delegate buffer PDA: ["buffer", delegated_account] under the program owning the account
+pub fn delegate_buffer_pda(delegated_account: &Pubkey, owner_program: &Pubkey) -> Pubkey {
+    derive_pda(&[BUFFER_SEED, delegated_account.as_ref()], owner_program).0
+}
+
+/// Settings of a delegation to an ephemeral rollup
+///
+/// Settings left unset fall back to the defaults in the world config, then to the program
+/// defaults: commits every 30 seconds, no expiry, validator picked by the delegation program.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use mojo_sdk::{DelegationOptions, EU_DEVNET_VALIDATOR};
+/// let options = DelegationOptions::new()
+///     .validator(EU_DEVNET_VALIDATOR)
+///     .commit_every(Duration::from_secs(5))
+///     .lifetime(Duration::from_secs(15 * 60));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DelegationOptions {
+    commit_frequency_ms: Option<u32>,
+    validator: Option<Pubkey>,
+    lifetime_secs: Option<u32>,
+}
+
+impl DelegationOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How often the rollup commits the account back to the base layer, in milliseconds
+    pub fn commit_frequency_ms(mut self, commit_frequency_ms: u32) -> Self {
+        self.commit_frequency_ms = Some(commit_frequency_ms);
+        self
+    }
+
+    /// How often the rollup commits the account back to the base layer
+    pub fn commit_every(self, interval: Duration) -> Self {
+        self.commit_frequency_ms(u32::try_from(interval.as_millis()).unwrap_or(u32::MAX))
+    }
+
+    /// The ephemeral rollup validator to delegate to
+    pub fn validator(mut self, validator: Pubkey) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// How long the delegation lasts, once expired the account can no longer be updated
+    /// and anyone may undelegate it
+    pub fn lifetime(self, lifetime: Duration) -> Self {
+        self.lifetime_secs(u32::try_from(lifetime.as_secs()).unwrap_or(u32::MAX))
+    }
+
+    /// How long the delegation lasts, in seconds
+    pub fn lifetime_secs(mut self, lifetime_secs: u32) -> Self {
+        self.lifetime_secs = Some(lifetime_secs);
+        self
+    }
+
+    /// Encode the options as delegate instruction data
+    pub fn to_args(&self) -> DelegationArgs {
+        DelegationArgs {
+            commit_frequency_ms: self.commit_frequency_ms.unwrap_or(0).to_le_bytes(),
+            lifetime_secs: self.lifetime_secs.unwrap_or(0).to_le_bytes(),
+            validator: self.validator.map(|v| v.to_bytes()).unwrap_or_default(),
+        }
+    }
+
+    /// Encode the options as world config, the defaults of every delegation in the world
+    ///
+    /// The validator is not part of the world config and has to be given on each delegation
+    pub fn to_world_config(&self) -> [u8; 8] {
+        let mut config = [0u8; 8];
+        config[..4].copy_from_slice(&self.commit_frequency_ms.unwrap_or(0).to_le_bytes());
+        config[4..].copy_from_slice(&self.lifetime_secs.unwrap_or(0).to_le_bytes());
+        config
+    }
+}
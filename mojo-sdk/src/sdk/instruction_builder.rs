@@ -1,9 +1,18 @@
 //! Instruction Builder utilities for underlying solana game engine program
 
 use crate::{
+    arithmetic::FieldOp,
+    delegation::{
+        delegate_buffer_pda, delegation_metadata_pda, delegation_record_pda, DelegationOptions,
+        DELEGATION_PROGRAM_ID, MAGIC_CONTEXT_ID, MAGIC_PROGRAM_ID,
+    },
     derive_pda,
     errors::MojoSDKError,
-    types::{GenIxHandler, MojoInstructionDiscriminator},
+    state::PatchSegment,
+    types::{
+        session_actions, AccessMode, DelegationArgs, GenIxHandler, MojoInstructionDiscriminator,
+        StateSchema,
+    },
     utils::helpers::compute_hash,
 };
 use solana_instruction::{AccountMeta, Instruction};
@@ -16,8 +25,13 @@ pub struct UpdateDelegatedAccountBuilder {
     program_id: Pubkey,
     creator: Pubkey,
     account_to_update: Pubkey,
+    world: Pubkey,
     gen_handler: GenIxHandler,
     state_data: Vec<u8>,
+    schema: Option<StateSchema>,
+    expected_version: Option<u64>,
+    access: Vec<AccountMeta>,
+    session: Option<AccountMeta>,
 }
 
 impl UpdateDelegatedAccountBuilder {
@@ -25,6 +39,7 @@ impl UpdateDelegatedAccountBuilder {
         program_id: Pubkey,
         creator: Pubkey,
         account_to_update: Pubkey,
+        world: Pubkey,
         seed: &Vec<u8>,
         state_data: Vec<u8>,
     ) -> Self {
@@ -34,26 +49,625 @@ impl UpdateDelegatedAccountBuilder {
             program_id,
             creator,
             account_to_update,
+            world,
             gen_handler,
             state_data,
+            schema: None,
+            expected_version: None,
+            access: Vec::new(),
+            session: None,
         }
     }
 
+    /// Write a shared state through its access list, see [`crate::SharedState`]
+    pub fn access_list(mut self, acl: Pubkey, membership: Option<Pubkey>) -> Self {
+        self.access = access_accounts(acl, membership);
+        self
+    }
+
+    /// Sign with a session key of the owner, see [`crate::Session`]
+    ///
+    /// A session limited to a number of actions counts them, so it is passed writable
+    pub fn session(mut self, session: Pubkey, counted: bool) -> Self {
+        self.session = Some(AccountMeta {
+            pubkey: session,
+            is_signer: false,
+            is_writable: counted,
+        });
+        self
+    }
+
+    /// Send the `schema` of the state along with it, the program rejects any schema but the
+    /// stored one
+    pub fn schema(mut self, schema: StateSchema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Only write the state if it is still at `version`, see [`crate::StateHeader::version`]
+    pub fn expected_version(mut self, version: u64) -> Self {
+        self.expected_version = Some(version);
+        self
+    }
+
     pub fn build(self) -> Result<Instruction, MojoSDKError> {
-        // Build instruction data: [discriminator][GenIxHandler][game_state_data]
-        let mut instruction_data =
-            Vec::with_capacity(1 + GenIxHandler::LEN + self.state_data.len());
+        // Build instruction data:
+        // [discriminator][GenIxHandler][schema flag][StateSchema][game_state_data][expected version]
+        let mut instruction_data = Vec::with_capacity(
+            1 + GenIxHandler::LEN + 1 + StateSchema::LEN + self.state_data.len() + 8,
+        );
 
         instruction_data.push(MojoInstructionDiscriminator::UpdateDelegatedAccount.into());
         instruction_data.extend_from_slice(bytemuck::bytes_of(&self.gen_handler));
+        instruction_data.extend(StateSchema::instruction_data(self.schema.as_ref()));
         instruction_data.extend_from_slice(&self.state_data);
+        if let Some(version) = self.expected_version {
+            instruction_data.extend_from_slice(&version.to_le_bytes());
+        }
 
         // Build accounts
-        let accounts = vec![
+        let mut accounts = vec![
             AccountMeta::new(self.creator, true), // creator (signer)
             AccountMeta::new(self.account_to_update, false), // account_to_update
-            AccountMeta::new(system_program_id(), false),
-            AccountMeta::new(rent_id, false),
+            AccountMeta::new_readonly(self.world, false), // parent world
+        ];
+        if self.access.is_empty() && self.session.is_none() {
+            accounts.push(AccountMeta::new(system_program_id(), false));
+            accounts.push(AccountMeta::new(rent_id, false));
+        } else {
+            accounts.extend(self.session);
+            accounts.extend(self.access);
+        }
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        })
+    }
+}
+
+/// Builder for the resize instruction, growing or shrinking a state account on the base layer
+pub struct ResizeAccountBuilder {
+    program_id: Pubkey,
+    owner: Pubkey,
+    account_to_resize: Pubkey,
+    world: Pubkey,
+    payer: Pubkey,
+    gen_handler: GenIxHandler,
+    schema: Option<StateSchema>,
+    state_data: Vec<u8>,
+}
+
+impl ResizeAccountBuilder {
+    pub fn new(
+        program_id: Pubkey,
+        owner: Pubkey,
+        account_to_resize: Pubkey,
+        world: Pubkey,
+        seed: &Vec<u8>,
+        size: usize,
+    ) -> Self {
+        Self {
+            program_id,
+            owner,
+            account_to_resize,
+            world,
+            payer: owner,
+            gen_handler: GenIxHandler::new(seed, size),
+            schema: None,
+            state_data: Vec::new(),
+        }
+    }
+
+    /// Send the `schema` of the new state along with it, the program rejects any schema but
+    /// the stored one
+    pub fn schema(mut self, schema: StateSchema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Pay or receive the rent difference with `payer` instead of the owner, who must sign as well
+    pub fn payer(mut self, payer: Pubkey) -> Self {
+        self.payer = payer;
+        self
+    }
+
+    /// Write `state_data` into the resized account, it must be exactly the new size
+    pub fn state_data(mut self, state_data: Vec<u8>) -> Self {
+        self.state_data = state_data;
+        self
+    }
+
+    pub fn build(self) -> Result<Instruction, MojoSDKError> {
+        if !self.state_data.is_empty() && self.state_data.len() != self.gen_handler.size() {
+            return Err(MojoSDKError::InvalidStateData(format!(
+                "State is {} bytes but the account is resized to {}",
+                self.state_data.len(),
+                self.gen_handler.size()
+            )));
+        }
+
+        // Build instruction data: [discriminator][GenIxHandler][schema flag][StateSchema][state_data]
+        let schema = self.schema.filter(|_| !self.state_data.is_empty());
+        let mut instruction_data = Vec::with_capacity(
+            1 + GenIxHandler::LEN + 1 + StateSchema::LEN + self.state_data.len(),
+        );
+        instruction_data.push(MojoInstructionDiscriminator::ResizeAccount.into());
+        instruction_data.extend_from_slice(bytemuck::bytes_of(&self.gen_handler));
+        instruction_data.extend(StateSchema::instruction_data(schema.as_ref()));
+        instruction_data.extend_from_slice(&self.state_data);
+
+        let mut accounts = vec![
+            AccountMeta::new(self.owner, true), // owner (signer, pays or receives the rent)
+            AccountMeta::new(self.account_to_resize, false),
+            AccountMeta::new_readonly(self.world, false), // parent world
+            AccountMeta::new_readonly(system_program_id(), false),
+        ];
+        if self.payer != self.owner {
+            accounts.push(AccountMeta::new(self.payer, true)); // sponsor in place of the owner
+        }
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        })
+    }
+}
+
+/// Builder for the migrate instruction, moving a state to a newer schema of its type
+pub struct MigrateAccountBuilder {
+    program_id: Pubkey,
+    owner: Pubkey,
+    account_to_migrate: Pubkey,
+    world: Pubkey,
+    payer: Pubkey,
+    gen_handler: GenIxHandler,
+    schema: StateSchema,
+    state_data: Vec<u8>,
+}
+
+impl MigrateAccountBuilder {
+    pub fn new(
+        program_id: Pubkey,
+        owner: Pubkey,
+        account_to_migrate: Pubkey,
+        world: Pubkey,
+        seed: &Vec<u8>,
+        schema: StateSchema,
+        state_data: Vec<u8>,
+    ) -> Self {
+        Self {
+            program_id,
+            owner,
+            account_to_migrate,
+            world,
+            payer: owner,
+            gen_handler: GenIxHandler::new(seed, state_data.len()),
+            schema,
+            state_data,
+        }
+    }
+
+    /// Pay or receive the rent difference with `payer` instead of the owner, who must sign as well
+    pub fn payer(mut self, payer: Pubkey) -> Self {
+        self.payer = payer;
+        self
+    }
+
+    pub fn build(self) -> Result<Instruction, MojoSDKError> {
+        // Build instruction data: [discriminator][GenIxHandler][schema flag][StateSchema][state_data]
+        let mut instruction_data = Vec::with_capacity(
+            1 + GenIxHandler::LEN + 1 + StateSchema::LEN + self.state_data.len(),
+        );
+        instruction_data.push(MojoInstructionDiscriminator::MigrateAccount.into());
+        instruction_data.extend_from_slice(bytemuck::bytes_of(&self.gen_handler));
+        instruction_data.extend(StateSchema::instruction_data(Some(&self.schema)));
+        instruction_data.extend_from_slice(&self.state_data);
+
+        let mut accounts = vec![
+            AccountMeta::new(self.owner, true), // owner (signer, pays or receives the rent)
+            AccountMeta::new(self.account_to_migrate, false),
+            AccountMeta::new_readonly(self.world, false), // parent world
+            AccountMeta::new_readonly(system_program_id(), false),
+        ];
+        if self.payer != self.owner {
+            accounts.push(AccountMeta::new(self.payer, true)); // sponsor in place of the owner
+        }
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        })
+    }
+}
+
+/// Builder for the patch instruction, writing byte ranges of a state instead of all of it
+pub struct PatchAccountBuilder {
+    program_id: Pubkey,
+    owner: Pubkey,
+    account_to_patch: Pubkey,
+    world: Pubkey,
+    gen_handler: GenIxHandler,
+    access: Vec<AccountMeta>,
+    session: Option<AccountMeta>,
+    segments: Vec<PatchSegment>,
+}
+
+impl PatchAccountBuilder {
+    pub fn new(
+        program_id: Pubkey,
+        owner: Pubkey,
+        account_to_patch: Pubkey,
+        world: Pubkey,
+        seed: &Vec<u8>,
+        segments: Vec<PatchSegment>,
+    ) -> Self {
+        Self {
+            program_id,
+            owner,
+            account_to_patch,
+            world,
+            // the program bounds-checks segments against the account, the size is unused
+            gen_handler: GenIxHandler::new(seed, 0),
+            access: Vec::new(),
+            session: None,
+            segments,
+        }
+    }
+
+    /// Write a shared state through its access list, see [`crate::SharedState`]
+    pub fn access_list(mut self, acl: Pubkey, membership: Option<Pubkey>) -> Self {
+        self.access = access_accounts(acl, membership);
+        self
+    }
+
+    /// Sign with a session key of the owner, see [`crate::Session`]
+    ///
+    /// A session limited to a number of actions counts them, so it is passed writable
+    pub fn session(mut self, session: Pubkey, counted: bool) -> Self {
+        self.session = Some(AccountMeta {
+            pubkey: session,
+            is_signer: false,
+            is_writable: counted,
+        });
+        self
+    }
+
+    pub fn build(self) -> Result<Instruction, MojoSDKError> {
+        if self.segments.is_empty() {
+            return Err(MojoSDKError::InvalidStateData(
+                "A patch needs at least one segment".to_string(),
+            ));
+        }
+        // the state follows a single schema, typed segments have to agree on it
+        let mut schemas = self.segments.iter().filter_map(|segment| segment.schema);
+        let schema = schemas.next();
+        if let Some((schema, other)) = schema.zip(schemas.find(|other| Some(*other) != schema)) {
+            return Err(other.mismatch(&schema));
+        }
+
+        // Build instruction data:
+        // [discriminator][GenIxHandler][schema flag][StateSchema]([offset u32][len u16][bytes])*
+        let mut instruction_data = Vec::with_capacity(1 + GenIxHandler::LEN + 1 + StateSchema::LEN);
+        instruction_data.push(MojoInstructionDiscriminator::PatchAccount.into());
+        instruction_data.extend_from_slice(bytemuck::bytes_of(&self.gen_handler));
+        instruction_data.extend(StateSchema::instruction_data(schema.as_ref()));
+        for segment in &self.segments {
+            let offset = u32::try_from(segment.offset).map_err(|_| {
+                MojoSDKError::InvalidStateData(format!(
+                    "Patch offset {} does not fit in a u32",
+                    segment.offset
+                ))
+            })?;
+            let len = u16::try_from(segment.data.len()).map_err(|_| {
+                MojoSDKError::InvalidStateData(format!(
+                    "Patch segment of {} bytes does not fit in a u16",
+                    segment.data.len()
+                ))
+            })?;
+            instruction_data.extend_from_slice(&offset.to_le_bytes());
+            instruction_data.extend_from_slice(&len.to_le_bytes());
+            instruction_data.extend_from_slice(&segment.data);
+        }
+
+        let mut accounts = vec![
+            AccountMeta::new(self.owner, true), // owner (signer)
+            AccountMeta::new(self.account_to_patch, false),
+            AccountMeta::new_readonly(self.world, false), // parent world
+        ];
+        accounts.extend(self.session);
+        accounts.extend(self.access);
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        })
+    }
+}
+
+/// Builder for the arithmetic instruction, applying checked operations to integer fields
+pub struct ArithmeticBuilder {
+    program_id: Pubkey,
+    owner: Pubkey,
+    account_to_update: Pubkey,
+    world: Pubkey,
+    gen_handler: GenIxHandler,
+    access: Vec<AccountMeta>,
+    session: Option<AccountMeta>,
+    ops: Vec<FieldOp>,
+}
+
+impl ArithmeticBuilder {
+    pub fn new(
+        program_id: Pubkey,
+        owner: Pubkey,
+        account_to_update: Pubkey,
+        world: Pubkey,
+        seed: &Vec<u8>,
+        ops: Vec<FieldOp>,
+    ) -> Self {
+        Self {
+            program_id,
+            owner,
+            account_to_update,
+            world,
+            gen_handler: GenIxHandler::new(seed, 0),
+            access: Vec::new(),
+            session: None,
+            ops,
+        }
+    }
+
+    /// Write a shared state through its access list, see [`crate::SharedState`]
+    pub fn access_list(mut self, acl: Pubkey, membership: Option<Pubkey>) -> Self {
+        self.access = access_accounts(acl, membership);
+        self
+    }
+
+    /// Sign with a session key of the owner, see [`crate::Session`]
+    ///
+    /// A session limited to a number of actions counts them, so it is passed writable
+    pub fn session(mut self, session: Pubkey, counted: bool) -> Self {
+        self.session = Some(AccountMeta {
+            pubkey: session,
+            is_signer: false,
+            is_writable: counted,
+        });
+        self
+    }
+
+    pub fn build(self) -> Result<Instruction, MojoSDKError> {
+        if self.ops.is_empty() {
+            return Err(MojoSDKError::InvalidStateData(
+                "An arithmetic instruction needs at least one operation".to_string(),
+            ));
+        }
+
+        // Build instruction data: [discriminator][GenIxHandler]([op][type][offset u32][operand])*
+        let mut instruction_data = Vec::with_capacity(1 + GenIxHandler::LEN);
+        instruction_data.push(MojoInstructionDiscriminator::Arithmetic.into());
+        instruction_data.extend_from_slice(bytemuck::bytes_of(&self.gen_handler));
+        for op in &self.ops {
+            let offset = u32::try_from(op.offset).map_err(|_| {
+                MojoSDKError::InvalidStateData(format!(
+                    "Field offset {} does not fit in a u32",
+                    op.offset
+                ))
+            })?;
+            instruction_data.push(op.op as u8);
+            instruction_data.push(op.type_tag);
+            instruction_data.extend_from_slice(&offset.to_le_bytes());
+            instruction_data.extend_from_slice(&op.operand);
+        }
+
+        let mut accounts = vec![
+            AccountMeta::new(self.owner, true), // owner (signer)
+            AccountMeta::new(self.account_to_update, false),
+            AccountMeta::new_readonly(self.world, false), // parent world
+        ];
+        accounts.extend(self.session);
+        accounts.extend(self.access);
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        })
+    }
+}
+
+/// Builder for the instruction creating a shared state along with its access list
+pub struct CreateSharedStateBuilder {
+    program_id: Pubkey,
+    creator: Pubkey,
+    state: Pubkey,
+    world: Pubkey,
+    gen_handler: GenIxHandler,
+    mode: AccessMode,
+    schema: StateSchema,
+    state_data: Vec<u8>,
+}
+
+impl CreateSharedStateBuilder {
+    pub fn new(
+        program_id: Pubkey,
+        creator: Pubkey,
+        state: Pubkey,
+        world: Pubkey,
+        seed: &Vec<u8>,
+        mode: AccessMode,
+        state_data: Vec<u8>,
+    ) -> Self {
+        Self {
+            program_id,
+            creator,
+            state,
+            world,
+            gen_handler: GenIxHandler::new(seed, state_data.len()),
+            mode,
+            schema: StateSchema::default(),
+            state_data,
+        }
+    }
+
+    /// Record `schema` as the type of the state, untyped otherwise
+    pub fn schema(mut self, schema: StateSchema) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    pub fn build(self) -> Result<Instruction, MojoSDKError> {
+        // Build instruction data:
+        // [discriminator][GenIxHandler][access mode][schema flag][StateSchema][state_data]
+        let mut instruction_data = Vec::with_capacity(
+            1 + GenIxHandler::LEN + 2 + StateSchema::LEN + self.state_data.len(),
+        );
+        instruction_data.push(MojoInstructionDiscriminator::CreateSharedState.into());
+        instruction_data.extend_from_slice(bytemuck::bytes_of(&self.gen_handler));
+        instruction_data.push(self.mode as u8);
+        instruction_data.extend(StateSchema::instruction_data(Some(&self.schema)));
+        instruction_data.extend_from_slice(&self.state_data);
+
+        let accounts = vec![
+            AccountMeta::new(self.creator, true), // world creator (signer, pays the rent)
+            AccountMeta::new(self.state, false),
+            AccountMeta::new(self.world, false), // world state count is bumped
+            AccountMeta::new(acl_pda(&self.state, &self.program_id), false),
+            AccountMeta::new_readonly(system_program_id(), false),
+        ];
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        })
+    }
+}
+
+/// Builder for the add and remove writer instructions managing the access list of a shared state
+pub struct ManageWriterBuilder {
+    program_id: Pubkey,
+    creator: Pubkey,
+    state: Pubkey,
+    world: Pubkey,
+    writer: Pubkey,
+    remove: bool,
+}
+
+impl ManageWriterBuilder {
+    pub fn new(
+        program_id: Pubkey,
+        creator: Pubkey,
+        state: Pubkey,
+        world: Pubkey,
+        writer: Pubkey,
+    ) -> Self {
+        Self {
+            program_id,
+            creator,
+            state,
+            world,
+            writer,
+            remove: false,
+        }
+    }
+
+    /// Remove the writer from the list instead of adding it
+    pub fn remove(mut self) -> Self {
+        self.remove = true;
+        self
+    }
+
+    pub fn build(self) -> Result<Instruction, MojoSDKError> {
+        let discriminator = if self.remove {
+            MojoInstructionDiscriminator::RemoveWriter
+        } else {
+            MojoInstructionDiscriminator::AddWriter
+        };
+
+        // Build instruction data: [discriminator][writer]
+        let mut instruction_data = Vec::with_capacity(1 + 32);
+        instruction_data.push(discriminator.into());
+        instruction_data.extend_from_slice(self.writer.as_ref());
+
+        let accounts = vec![
+            AccountMeta::new_readonly(self.creator, true), // world creator (signer)
+            AccountMeta::new_readonly(self.state, false),
+            AccountMeta::new_readonly(self.world, false),
+            AccountMeta::new(acl_pda(&self.state, &self.program_id), false),
+        ];
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        })
+    }
+}
+
+/// Builder for the instruction creating or rotating the session of an owner in a world
+pub struct CreateSessionBuilder {
+    program_id: Pubkey,
+    owner: Pubkey,
+    world: Pubkey,
+    session_key: Pubkey,
+    expires_at: u64,
+    max_actions: u64,
+    allowed_actions: u8,
+}
+
+impl CreateSessionBuilder {
+    pub fn new(
+        program_id: Pubkey,
+        owner: Pubkey,
+        world: Pubkey,
+        session_key: Pubkey,
+        expires_at: u64,
+    ) -> Self {
+        Self {
+            program_id,
+            owner,
+            world,
+            session_key,
+            expires_at,
+            max_actions: 0,
+            allowed_actions: session_actions::ALL,
+        }
+    }
+
+    /// Limit the number of writes the session key may sign
+    pub fn max_actions(mut self, max_actions: u64) -> Self {
+        self.max_actions = max_actions;
+        self
+    }
+
+    /// Limit the write instructions the session key may sign, see [`session_actions`]
+    pub fn allowed_actions(mut self, allowed_actions: u8) -> Self {
+        self.allowed_actions = allowed_actions;
+        self
+    }
+
+    pub fn build(self) -> Result<Instruction, MojoSDKError> {
+        // Build instruction data: [discriminator][session key][expires at][max actions][allowed actions]
+        let mut instruction_data = Vec::with_capacity(1 + 32 + 8 + 8 + 1);
+        instruction_data.push(MojoInstructionDiscriminator::CreateSession.into());
+        instruction_data.extend_from_slice(self.session_key.as_ref());
+        instruction_data.extend_from_slice(&self.expires_at.to_le_bytes());
+        instruction_data.extend_from_slice(&self.max_actions.to_le_bytes());
+        instruction_data.push(self.allowed_actions);
+
+        let accounts = vec![
+            AccountMeta::new(self.owner, true), // owner (signer, pays the rent)
+            AccountMeta::new(
+                session_pda(&self.world, &self.owner, &self.program_id),
+                false,
+            ),
+            AccountMeta::new_readonly(self.world, false),
+            AccountMeta::new_readonly(system_program_id(), false),
         ];
 
         Ok(Instruction {
@@ -63,3 +677,443 @@ impl UpdateDelegatedAccountBuilder {
         })
     }
 }
+
+/// Builder for the instruction revoking the session of an owner in a world
+pub struct RevokeSessionBuilder {
+    program_id: Pubkey,
+    signer: Pubkey,
+    owner: Pubkey,
+    world: Pubkey,
+}
+
+impl RevokeSessionBuilder {
+    /// `signer` is the owner or the session key itself
+    pub fn new(program_id: Pubkey, signer: Pubkey, owner: Pubkey, world: Pubkey) -> Self {
+        Self {
+            program_id,
+            signer,
+            owner,
+            world,
+        }
+    }
+
+    pub fn build(self) -> Result<Instruction, MojoSDKError> {
+        let accounts = vec![
+            AccountMeta::new(self.signer, true),
+            AccountMeta::new(
+                session_pda(&self.world, &self.owner, &self.program_id),
+                false,
+            ),
+            AccountMeta::new(self.owner, false), // receives the rent
+        ];
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: vec![MojoInstructionDiscriminator::RevokeSession.into()],
+        })
+    }
+}
+
+/// Derive the session PDA of `owner` in `world`
+pub fn session_pda(world: &Pubkey, owner: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    derive_pda(&[b"session", world.as_ref(), owner.as_ref()], program_id).0
+}
+
+/// Derive the access list PDA of the shared `state`
+pub fn acl_pda(state: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    derive_pda(&[b"acl", state.as_ref()], program_id).0
+}
+
+/// Trailing accounts of a write to a shared state
+fn access_accounts(acl: Pubkey, membership: Option<Pubkey>) -> Vec<AccountMeta> {
+    std::iter::once(acl)
+        .chain(membership)
+        .map(|account| AccountMeta::new_readonly(account, false))
+        .collect()
+}
+
+/// Builder for delegate_account instruction
+///
+/// Delegating the world itself is done by passing the world PDA as `account_to_delegate`
+pub struct DelegateAccountBuilder {
+    program_id: Pubkey,
+    creator: Pubkey,
+    account_to_delegate: Pubkey,
+    world: Pubkey,
+    payer: Pubkey,
+    gen_handler: GenIxHandler,
+    options: DelegationOptions,
+}
+
+impl DelegateAccountBuilder {
+    pub fn new(
+        program_id: Pubkey,
+        creator: Pubkey,
+        account_to_delegate: Pubkey,
+        world: Pubkey,
+        seed_hash: [u8; 32],
+        options: DelegationOptions,
+    ) -> Self {
+        Self {
+            program_id,
+            creator,
+            account_to_delegate,
+            world,
+            payer: creator,
+            gen_handler: GenIxHandler::from_seed_hash(seed_hash, 0),
+            options,
+        }
+    }
+
+    /// Fund the delegation accounts from `payer` instead of the creator, who must sign as well
+    pub fn payer(mut self, payer: Pubkey) -> Self {
+        self.payer = payer;
+        self
+    }
+
+    pub fn build(self) -> Result<Instruction, MojoSDKError> {
+        // Build instruction data: [discriminator][GenIxHandler][DelegationArgs]
+        let mut instruction_data = Vec::with_capacity(1 + GenIxHandler::LEN + DelegationArgs::LEN);
+        instruction_data.push(MojoInstructionDiscriminator::DelegateAccount.into());
+        instruction_data.extend_from_slice(bytemuck::bytes_of(&self.gen_handler));
+        instruction_data.extend_from_slice(bytemuck::bytes_of(&self.options.to_args()));
+
+        let mut accounts = vec![
+            AccountMeta::new(self.creator, true), // creator (signer, payer unless one is set)
+            AccountMeta::new(self.account_to_delegate, false),
+            AccountMeta::new_readonly(self.world, false), // parent world
+            AccountMeta::new_readonly(self.program_id, false), // owner program
+            AccountMeta::new(
+                delegate_buffer_pda(&self.account_to_delegate, &self.program_id),
+                false,
+            ),
+            AccountMeta::new(delegation_record_pda(&self.account_to_delegate), false),
+            AccountMeta::new(delegation_metadata_pda(&self.account_to_delegate), false),
+            AccountMeta::new_readonly(system_program_id(), false),
+            AccountMeta::new_readonly(DELEGATION_PROGRAM_ID, false),
+        ];
+        if self.payer != self.creator {
+            accounts.push(AccountMeta::new(self.payer, true)); // sponsor in place of the creator
+        }
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        })
+    }
+}
+
+/// Builder for the commit and undelegate instructions, both executed on the ephemeral rollup
+pub struct CommitAccountBuilder {
+    program_id: Pubkey,
+    creator: Pubkey,
+    account_to_commit: Pubkey,
+    world: Pubkey,
+    gen_handler: GenIxHandler,
+    undelegate: bool,
+}
+
+impl CommitAccountBuilder {
+    pub fn new(
+        program_id: Pubkey,
+        creator: Pubkey,
+        account_to_commit: Pubkey,
+        world: Pubkey,
+        seed_hash: [u8; 32],
+    ) -> Self {
+        Self {
+            program_id,
+            creator,
+            account_to_commit,
+            world,
+            gen_handler: GenIxHandler::from_seed_hash(seed_hash, 0),
+            undelegate: false,
+        }
+    }
+
+    /// Commit the account and hand it back to the base layer
+    pub fn undelegate(mut self) -> Self {
+        self.undelegate = true;
+        self
+    }
+
+    pub fn build(self) -> Result<Instruction, MojoSDKError> {
+        let discriminator = if self.undelegate {
+            MojoInstructionDiscriminator::UndelegateAccount
+        } else {
+            MojoInstructionDiscriminator::Commit
+        };
+
+        // Build instruction data: [discriminator][GenIxHandler]
+        let mut instruction_data = Vec::with_capacity(1 + GenIxHandler::LEN);
+        instruction_data.push(discriminator.into());
+        instruction_data.extend_from_slice(bytemuck::bytes_of(&self.gen_handler));
+
+        let accounts = vec![
+            AccountMeta::new(self.creator, true), // creator (signer)
+            AccountMeta::new(self.account_to_commit, false),
+            AccountMeta::new_readonly(self.world, false), // parent world
+            AccountMeta::new(MAGIC_CONTEXT_ID, false),
+            AccountMeta::new_readonly(MAGIC_PROGRAM_ID, false),
+        ];
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        })
+    }
+}
+
+/// Builder for the batch delegate instruction, delegating several PDAs of one world at once
+///
+/// The world itself is delegated by adding the world PDA with the world seed hash
+pub struct DelegateBatchBuilder {
+    program_id: Pubkey,
+    signer: Pubkey,
+    world: Pubkey,
+    accounts: Vec<(Pubkey, [u8; 32])>,
+    co_signers: Vec<Pubkey>,
+    options: DelegationOptions,
+}
+
+impl DelegateBatchBuilder {
+    pub fn new(
+        program_id: Pubkey,
+        signer: Pubkey,
+        world: Pubkey,
+        options: DelegationOptions,
+    ) -> Self {
+        Self {
+            program_id,
+            signer,
+            world,
+            accounts: Vec::new(),
+            co_signers: Vec::new(),
+            options,
+        }
+    }
+
+    /// Add the PDA `account` derived from `seed_hash` to the batch
+    pub fn add(mut self, account: Pubkey, seed_hash: [u8; 32]) -> Self {
+        self.accounts.push((account, seed_hash));
+        self
+    }
+
+    /// Have `owner` sign for the states of the batch it owns and the signer does not
+    pub fn co_signer(mut self, owner: Pubkey) -> Self {
+        if owner != self.signer && !self.co_signers.contains(&owner) {
+            self.co_signers.push(owner);
+        }
+        self
+    }
+
+    pub fn build(self) -> Result<Instruction, MojoSDKError> {
+        // Build instruction data: [discriminator][count][seed hashes][DelegationArgs]
+        let mut instruction_data =
+            batch_instruction_data(MojoInstructionDiscriminator::DelegateBatch, &self.accounts)?;
+        instruction_data.extend_from_slice(bytemuck::bytes_of(&self.options.to_args()));
+
+        let mut accounts = vec![
+            AccountMeta::new(self.signer, true), // signer (payer)
+            AccountMeta::new_readonly(self.world, false),
+            AccountMeta::new_readonly(self.program_id, false), // owner program
+            AccountMeta::new_readonly(system_program_id(), false),
+            AccountMeta::new_readonly(DELEGATION_PROGRAM_ID, false),
+        ];
+        for (account, _seed_hash) in &self.accounts {
+            accounts.extend([
+                AccountMeta::new(*account, false),
+                AccountMeta::new(delegate_buffer_pda(account, &self.program_id), false),
+                AccountMeta::new(delegation_record_pda(account), false),
+                AccountMeta::new(delegation_metadata_pda(account), false),
+            ]);
+        }
+        accounts.extend(
+            self.co_signers
+                .iter()
+                .map(|owner| AccountMeta::new_readonly(*owner, true)),
+        );
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        })
+    }
+}
+
+/// Builder for the batch commit and undelegate instructions, both executed on the ephemeral rollup
+pub struct CommitBatchBuilder {
+    program_id: Pubkey,
+    signer: Pubkey,
+    world: Pubkey,
+    accounts: Vec<(Pubkey, [u8; 32])>,
+    co_signers: Vec<Pubkey>,
+    undelegate: bool,
+}
+
+impl CommitBatchBuilder {
+    pub fn new(program_id: Pubkey, signer: Pubkey, world: Pubkey) -> Self {
+        Self {
+            program_id,
+            signer,
+            world,
+            accounts: Vec::new(),
+            co_signers: Vec::new(),
+            undelegate: false,
+        }
+    }
+
+    /// Add the PDA `account` derived from `seed_hash` to the batch
+    pub fn add(mut self, account: Pubkey, seed_hash: [u8; 32]) -> Self {
+        self.accounts.push((account, seed_hash));
+        self
+    }
+
+    /// Have `owner` sign for the states of the batch it owns and the signer does not
+    pub fn co_signer(mut self, owner: Pubkey) -> Self {
+        if owner != self.signer && !self.co_signers.contains(&owner) {
+            self.co_signers.push(owner);
+        }
+        self
+    }
+
+    /// Commit the accounts and hand them back to the base layer
+    pub fn undelegate(mut self) -> Self {
+        self.undelegate = true;
+        self
+    }
+
+    pub fn build(self) -> Result<Instruction, MojoSDKError> {
+        let discriminator = if self.undelegate {
+            MojoInstructionDiscriminator::UndelegateBatch
+        } else {
+            MojoInstructionDiscriminator::CommitBatch
+        };
+
+        // Build instruction data: [discriminator][count][seed hashes]
+        let instruction_data = batch_instruction_data(discriminator, &self.accounts)?;
+
+        let mut accounts = vec![
+            AccountMeta::new(self.signer, true), // signer
+            AccountMeta::new_readonly(self.world, false),
+            AccountMeta::new(MAGIC_CONTEXT_ID, false),
+            AccountMeta::new_readonly(MAGIC_PROGRAM_ID, false),
+        ];
+        accounts.extend(
+            self.accounts
+                .iter()
+                .map(|(account, _seed_hash)| AccountMeta::new(*account, false)),
+        );
+        accounts.extend(
+            self.co_signers
+                .iter()
+                .map(|owner| AccountMeta::new_readonly(*owner, true)),
+        );
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        })
+    }
+}
+
+/// Builder for the close instruction, returning the rent of a state or a world to a recipient
+///
+/// Closing the world itself is done by passing the world PDA as `account_to_close`, its states
+/// are closed along with it by adding them as children
+pub struct CloseAccountBuilder {
+    program_id: Pubkey,
+    signer: Pubkey,
+    account_to_close: Pubkey,
+    world: Pubkey,
+    gen_handler: GenIxHandler,
+    recipient: Pubkey,
+    children: Vec<Pubkey>,
+}
+
+impl CloseAccountBuilder {
+    pub fn new(
+        program_id: Pubkey,
+        signer: Pubkey,
+        account_to_close: Pubkey,
+        world: Pubkey,
+        seed_hash: [u8; 32],
+    ) -> Self {
+        Self {
+            program_id,
+            signer,
+            account_to_close,
+            world,
+            gen_handler: GenIxHandler::from_seed_hash(seed_hash, 0),
+            recipient: signer,
+            children: Vec::new(),
+        }
+    }
+
+    /// Send the rent to `recipient` instead of the signer
+    pub fn recipient(mut self, recipient: Pubkey) -> Self {
+        self.recipient = recipient;
+        self
+    }
+
+    /// States of the world to close along with it
+    pub fn children(mut self, children: Vec<Pubkey>) -> Self {
+        self.children = children;
+        self
+    }
+
+    pub fn build(self) -> Result<Instruction, MojoSDKError> {
+        // Build instruction data: [discriminator][GenIxHandler]
+        let mut instruction_data = Vec::with_capacity(1 + GenIxHandler::LEN);
+        instruction_data.push(MojoInstructionDiscriminator::CloseAccount.into());
+        instruction_data.extend_from_slice(bytemuck::bytes_of(&self.gen_handler));
+
+        let mut accounts = vec![
+            AccountMeta::new(self.signer, true), // signer
+            AccountMeta::new(self.account_to_close, false),
+            AccountMeta::new(self.world, false), // parent world, its state count is updated
+            AccountMeta::new(self.recipient, false),
+        ];
+        accounts.extend(
+            self.children
+                .iter()
+                .map(|child| AccountMeta::new(*child, false)),
+        );
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        })
+    }
+}
+
+/// [discriminator][count: u8][count x seed hash]
+fn batch_instruction_data(
+    discriminator: MojoInstructionDiscriminator,
+    accounts: &[(Pubkey, [u8; 32])],
+) -> Result<Vec<u8>, MojoSDKError> {
+    let count = u8::try_from(accounts.len())
+        .ok()
+        .filter(|count| *count > 0)
+        .ok_or_else(|| {
+            MojoSDKError::InvalidStateData(format!(
+                "A batch holds 1 to {} accounts, got {}",
+                u8::MAX,
+                accounts.len()
+            ))
+        })?;
+
+    let mut instruction_data = Vec::with_capacity(2 + accounts.len() * 32);
+    instruction_data.push(discriminator.into());
+    instruction_data.push(count);
+    for (_account, seed_hash) in accounts {
+        instruction_data.extend_from_slice(seed_hash);
+    }
+    Ok(instruction_data)
+}
@@ -1,7 +1,19 @@
+pub mod arithmetic;
+pub mod delegation;
 pub mod instruction_builder;
+pub mod session;
+pub mod shared_state;
+pub mod sponsor;
 pub mod state;
+pub mod subscription;
 pub mod world;
 
+pub use arithmetic::*;
+pub use delegation::*;
 pub use instruction_builder::*;
+pub use session::*;
+pub use shared_state::*;
+pub use sponsor::*;
 pub use state::*;
+pub use subscription::*;
 pub use world::*;
@@ -0,0 +1,427 @@
+//! Session keys, letting a game sign moves without prompting the player's wallet
+//!
+//! The player signs once to authorize an ephemeral key for one world, the key then writes
+//! their states until it expires, runs out of actions or is revoked:
+//!
+//! ```no_run
+//! // let session = client.create_session(&world, &player, SessionOptions::new().max_actions(100))?;
+//! // session.write_state(&client, "position", Position { x: 1, y: 2 })?;
+//! // session.revoke(&client)?;
+//! ```
+
+use crate::{
+    arithmetic::{FieldOp, IntegerField, StateField},
+    errors::MojoSDKError,
+    instruction_builder::{
+        session_pda, ArithmeticBuilder, CreateSessionBuilder, PatchAccountBuilder,
+        RevokeSessionBuilder, UpdateDelegatedAccountBuilder,
+    },
+    state::{MojoState, PatchSegment},
+    transport::Transport,
+    types::{session_actions, SessionToken},
+    world::World,
+    SdkClient, StateHeader, StateSchema,
+};
+
+use solana_instruction::{AccountMeta, Instruction};
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_system_program::id as system_program_id;
+use solana_sysvar::clock::ID as clock_id;
+
+/// Slots a session lasts unless told otherwise, about an hour on a live cluster
+pub const DEFAULT_SESSION_SLOTS: u64 = 9_000;
+
+/// Lamports handed to a new session key to pay for the transactions it signs
+pub const DEFAULT_SESSION_FEE_LAMPORTS: u64 = 10_000_000;
+
+/// Settings of a session
+///
+/// ```no_run
+/// # use mojo_sdk::{session_actions, SessionOptions};
+/// let options = SessionOptions::new()
+///     .valid_for_slots(1_500)
+///     .max_actions(200)
+///     .allowed_actions(session_actions::PATCH | session_actions::ARITHMETIC);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionOptions {
+    expiry: SessionExpiry,
+    max_actions: u64,
+    allowed_actions: u8,
+    fee_lamports: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionExpiry {
+    AtSlot(u64),
+    ForSlots(u64),
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        Self {
+            expiry: SessionExpiry::ForSlots(DEFAULT_SESSION_SLOTS),
+            max_actions: 0,
+            allowed_actions: session_actions::ALL,
+            fee_lamports: DEFAULT_SESSION_FEE_LAMPORTS,
+        }
+    }
+}
+
+impl SessionOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the key valid for `slots` slots of the layer the world lives on
+    ///
+    /// Expiry is checked against the slot of the layer executing the write, a delegated
+    /// world is timed by the slots of the ephemeral rollup
+    pub fn valid_for_slots(mut self, slots: u64) -> Self {
+        self.expiry = SessionExpiry::ForSlots(slots);
+        self
+    }
+
+    /// Keep the key valid up to and including `slot`
+    pub fn expires_at(mut self, slot: u64) -> Self {
+        self.expiry = SessionExpiry::AtSlot(slot);
+        self
+    }
+
+    /// Limit the number of writes the key may sign
+    ///
+    /// The count lives in the session account, which stays on the base layer: a limited
+    /// session can not be created in a delegated world and fails with `AccountDelegated` on
+    /// delegated states. Use an expiry alone for sessions writing on an ephemeral rollup.
+    pub fn max_actions(mut self, max_actions: u64) -> Self {
+        self.max_actions = max_actions;
+        self
+    }
+
+    /// Limit the write instructions the key may sign, see [`session_actions`]
+    pub fn allowed_actions(mut self, allowed_actions: u8) -> Self {
+        self.allowed_actions = allowed_actions;
+        self
+    }
+
+    /// Lamports the owner hands to the key to pay for its transactions
+    pub fn fee_lamports(mut self, fee_lamports: u64) -> Self {
+        self.fee_lamports = fee_lamports;
+        self
+    }
+}
+
+/// An ephemeral key writing the states of its owner in one world
+pub struct Session {
+    /// The PDA of the world the session is scoped to
+    pub world_pda: Pubkey,
+    /// The player the key writes for
+    pub owner: Pubkey,
+    /// The PDA holding the session, derived from ["session", world pda, owner]
+    pub session_pda: Pubkey,
+    keypair: Keypair,
+    world_seed_hash: [u8; 32],
+    counted: bool,
+}
+
+impl Session {
+    /// Authorize a fresh key to write the states of `owner` in `world`, the only transaction
+    /// `owner` signs (sent to the base layer)
+    ///
+    /// Creating a session while one exists replaces it, see [`Session::rotate`]
+    pub fn create(
+        client: &SdkClient,
+        world: &World,
+        owner: &Keypair,
+        options: SessionOptions,
+    ) -> Result<Session, MojoSDKError> {
+        let mut session = Session {
+            world_pda: world.world_pda,
+            owner: owner.pubkey(),
+            session_pda: session_pda(&world.world_pda, &owner.pubkey(), &client.program_id),
+            keypair: Keypair::new(),
+            world_seed_hash: world.world_seed_hash,
+            counted: false,
+        };
+        session.authorize(client, owner, options)?;
+        Ok(session)
+    }
+
+    /// Replace the key with a fresh one, the old key stops working at once (sent to the
+    /// base layer)
+    pub fn rotate(
+        &mut self,
+        client: &SdkClient,
+        owner: &Keypair,
+        options: SessionOptions,
+    ) -> Result<(), MojoSDKError> {
+        self.keypair = Keypair::new();
+        self.authorize(client, owner, options)
+    }
+
+    /// Revoke the session with the key itself, returning its rent to the owner (sent to the
+    /// base layer)
+    pub fn revoke(self, client: &SdkClient) -> Result<(), MojoSDKError> {
+        let revoke_ix = RevokeSessionBuilder::new(
+            client.program_id,
+            self.keypair.pubkey(),
+            self.owner,
+            self.world_pda,
+        )
+        .build()?;
+        World::submit_instructions(client, client.client(), &self.keypair, vec![revoke_ix])
+    }
+
+    /// Revoke the session of `owner` in `world` without its key, e.g. after losing it (sent
+    /// to the base layer)
+    pub fn revoke_by_owner(
+        client: &SdkClient,
+        world: &World,
+        owner: &Keypair,
+    ) -> Result<(), MojoSDKError> {
+        let revoke_ix = RevokeSessionBuilder::new(
+            client.program_id,
+            owner.pubkey(),
+            owner.pubkey(),
+            world.world_pda,
+        )
+        .build()?;
+        World::submit_instructions(client, client.client(), owner, vec![revoke_ix])
+    }
+
+    /// The ephemeral key signing the writes
+    pub fn keypair(&self) -> &Keypair {
+        &self.keypair
+    }
+
+    /// Read the session as stored on-chain
+    pub fn read_token(&self, client: &SdkClient) -> Result<SessionToken, MojoSDKError> {
+        let account_data = World::fetch_owned_account_data(client, &self.session_pda)?;
+        SessionToken::from_account_data(&account_data)
+    }
+
+    /// Replace the state named `state_name` of the owner
+    ///
+    /// The state has to exist, a session key neither creates nor resizes states. Shorter
+    /// (framed) states are zero padded.
+    pub fn write_state<T: MojoState>(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        state: T,
+    ) -> Result<(), MojoSDKError> {
+        let (account_pda, seed_input, _seed_hash) =
+            self.world()
+                .derive_state_pda(state_name, &self.owner, client);
+        let account_data = World::fetch_owned_account_data(client, &account_pda)?;
+        let (header, current) = StateHeader::split_account_data(&account_data)?;
+        header.check_schema::<T>()?;
+        let state_data = World::fit_to_account(state.serialize()?, current.len())?;
+
+        let update_ix = UpdateDelegatedAccountBuilder::new(
+            client.program_id,
+            self.keypair.pubkey(),
+            account_pda,
+            self.world_pda,
+            &seed_input,
+            state_data,
+        )
+        .schema(StateSchema::of::<T>())
+        .session(self.session_pda, self.counted)
+        .build()?;
+        World::submit_instructions(
+            client,
+            client.rpc_for(&account_pda)?,
+            &self.keypair,
+            vec![update_ix],
+        )
+    }
+
+    /// Write only the `segments` of the state named `state_name` of the owner
+    pub fn patch_state(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        segments: &[PatchSegment],
+    ) -> Result<(), MojoSDKError> {
+        let (account_pda, seed_input, _seed_hash) =
+            self.world()
+                .derive_state_pda(state_name, &self.owner, client);
+        let patch_ix = PatchAccountBuilder::new(
+            client.program_id,
+            self.keypair.pubkey(),
+            account_pda,
+            self.world_pda,
+            &seed_input,
+            segments.to_vec(),
+        )
+        .session(self.session_pda, self.counted)
+        .build()?;
+        World::submit_instructions(
+            client,
+            client.rpc_for(&account_pda)?,
+            &self.keypair,
+            vec![patch_ix],
+        )
+    }
+
+    /// Add `delta` to an integer field of the state named `state_name` of the owner
+    pub fn increment<T, F: IntegerField>(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        field: StateField<T, F>,
+        delta: F,
+    ) -> Result<(), MojoSDKError> {
+        self.apply_field_ops(client, state_name, &[field.add(delta)])
+    }
+
+    /// Apply `ops` to integer fields of the state named `state_name` of the owner
+    pub fn apply_field_ops(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        ops: &[FieldOp],
+    ) -> Result<(), MojoSDKError> {
+        let (account_pda, seed_input, _seed_hash) =
+            self.world()
+                .derive_state_pda(state_name, &self.owner, client);
+        let arithmetic_ix = ArithmeticBuilder::new(
+            client.program_id,
+            self.keypair.pubkey(),
+            account_pda,
+            self.world_pda,
+            &seed_input,
+            ops.to_vec(),
+        )
+        .session(self.session_pda, self.counted)
+        .build()?;
+        World::submit_instructions(
+            client,
+            client.rpc_for(&account_pda)?,
+            &self.keypair,
+            vec![arithmetic_ix],
+        )
+    }
+
+    /// Store the current key with `options` and fund it, signed by `owner`
+    fn authorize(
+        &mut self,
+        client: &SdkClient,
+        owner: &Keypair,
+        options: SessionOptions,
+    ) -> Result<(), MojoSDKError> {
+        let expires_at = match options.expiry {
+            SessionExpiry::AtSlot(slot) => slot,
+            SessionExpiry::ForSlots(slots) => {
+                let rpc = client.rpc(client.endpoint_for(&self.world_pda)?);
+                current_slot(rpc)?.saturating_add(slots)
+            }
+        };
+
+        let mut instructions = Vec::with_capacity(2);
+        if options.fee_lamports > 0 {
+            instructions.push(transfer_instruction(
+                &owner.pubkey(),
+                &self.keypair.pubkey(),
+                options.fee_lamports,
+            ));
+        }
+        instructions.push(
+            CreateSessionBuilder::new(
+                client.program_id,
+                owner.pubkey(),
+                self.world_pda,
+                self.keypair.pubkey(),
+                expires_at,
+            )
+            .max_actions(options.max_actions)
+            .allowed_actions(options.allowed_actions)
+            .build()?,
+        );
+        World::submit_instructions(client, client.client(), owner, instructions)?;
+
+        self.counted = options.max_actions > 0;
+        Ok(())
+    }
+
+    fn world(&self) -> World {
+        World {
+            world_pda: self.world_pda,
+            world_seed_hash: self.world_seed_hash,
+        }
+    }
+}
+
+/// Who signs a write to a shared state, a player with their own key or a session key on
+/// their behalf
+#[derive(Clone, Copy)]
+pub enum Writer<'a> {
+    Key(&'a Keypair),
+    Session(&'a Session),
+}
+
+impl<'a> From<&'a Keypair> for Writer<'a> {
+    fn from(keypair: &'a Keypair) -> Self {
+        Writer::Key(keypair)
+    }
+}
+
+impl<'a> From<&'a Session> for Writer<'a> {
+    fn from(session: &'a Session) -> Self {
+        Writer::Session(session)
+    }
+}
+
+impl Writer<'_> {
+    /// The key signing the transaction
+    pub fn signer(&self) -> &Keypair {
+        match self {
+            Writer::Key(keypair) => keypair,
+            Writer::Session(session) => &session.keypair,
+        }
+    }
+
+    /// The player the write is made for
+    pub fn authority(&self) -> Pubkey {
+        match self {
+            Writer::Key(keypair) => keypair.pubkey(),
+            Writer::Session(session) => session.owner,
+        }
+    }
+
+    /// The session account passed along with the write, writable when it counts actions
+    pub(crate) fn session(&self) -> Option<(Pubkey, bool)> {
+        match self {
+            Writer::Key(_) => None,
+            Writer::Session(session) => Some((session.session_pda, session.counted)),
+        }
+    }
+}
+
+/// Current slot of the cluster behind `rpc`, read from the clock sysvar
+fn current_slot(rpc: &dyn Transport) -> Result<u64, MojoSDKError> {
+    let clock = rpc
+        .get_account(&clock_id)?
+        .ok_or_else(|| MojoSDKError::AccountNotFound(clock_id.to_string()))?;
+    // the clock starts with its slot as a little endian u64
+    clock
+        .data
+        .first_chunk::<8>()
+        .map(|slot| u64::from_le_bytes(*slot))
+        .ok_or_else(|| MojoSDKError::Deserialization("Invalid clock sysvar".to_string()))
+}
+
+/// System program transfer of `lamports` from `from` to `to`
+fn transfer_instruction(from: &Pubkey, to: &Pubkey, lamports: u64) -> Instruction {
+    // [instruction index u32 LE][lamports u64 LE]
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&lamports.to_le_bytes());
+    Instruction {
+        program_id: system_program_id(),
+        accounts: vec![AccountMeta::new(*from, true), AccountMeta::new(*to, false)],
+        data,
+    }
+}
@@ -0,0 +1,326 @@
+//! Shared states, owned by their world and writable by the players its access list allows
+//!
+//! A regular state is derived from and written by a single owner. A shared state is derived
+//! with the world PDA standing in for the owner, e.g. the board both players of a tic-tac-toe
+//! match write, and an access list next to it decides who may write it:
+//!
+//! ```no_run
+//! // let board = SharedState::create(&client, &world, &creator, "board", AccessMode::Listed, Board::zeroed())?;
+//! // board.add_writer(&client, &creator, &opponent)?;
+//! // board.write(&client, &opponent, next_board)?;
+//! ```
+//!
+//! Shared states are delegated, committed and undelegated with the world through
+//! [`World::delegate_all`], naming them as `(state name, world PDA)`.
+
+use crate::{
+    arithmetic::{FieldOp, IntegerField, StateField},
+    errors::MojoSDKError,
+    instruction_builder::{
+        acl_pda, ArithmeticBuilder, CloseAccountBuilder, CreateSharedStateBuilder,
+        ManageWriterBuilder, PatchAccountBuilder, UpdateDelegatedAccountBuilder,
+    },
+    session::Writer,
+    state::{MojoState, PatchSegment},
+    world::World,
+    AccessMode, SdkClient, StateAcl, StateHeader, StateSchema,
+};
+
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+
+/// Handle on a shared state of a world and its access list
+#[derive(Debug, Clone)]
+pub struct SharedState {
+    /// The PDA of the world owning the state
+    pub world_pda: Pubkey,
+    /// The PDA of the state, derived from [seed hash, world pda, world pda]
+    pub state_pda: Pubkey,
+    /// The PDA of the access list, derived from ["acl", state pda]
+    pub acl_pda: Pubkey,
+    pub seed_hash: [u8; 32],
+    seed_input: Vec<u8>,
+    world_seed_hash: [u8; 32],
+}
+
+impl SharedState {
+    /// Create the shared state named `state_name` in `world` along with its access list
+    ///
+    /// `creator` has to be the creator of the world, they can always write the state and
+    /// manage its writers (sent to the base layer)
+    pub fn create<T: MojoState>(
+        client: &SdkClient,
+        world: &World,
+        creator: &Keypair,
+        state_name: &str,
+        mode: AccessMode,
+        initial_state: T,
+    ) -> Result<SharedState, MojoSDKError> {
+        let shared_state = Self::open(client, world, state_name);
+
+        let create_ix = CreateSharedStateBuilder::new(
+            client.program_id,
+            creator.pubkey(),
+            shared_state.state_pda,
+            shared_state.world_pda,
+            &shared_state.seed_input,
+            mode,
+            initial_state.serialize()?,
+        )
+        .schema(StateSchema::of::<T>())
+        .build()?;
+        World::submit_instructions(client, client.client(), creator, vec![create_ix])?;
+
+        Ok(shared_state)
+    }
+
+    /// Handle on the existing shared state named `state_name` in `world`
+    pub fn open(client: &SdkClient, world: &World, state_name: &str) -> SharedState {
+        let (state_pda, seed_input, seed_hash) =
+            world.derive_state_pda(state_name, &world.world_pda, client);
+        SharedState {
+            world_pda: world.world_pda,
+            state_pda,
+            acl_pda: acl_pda(&state_pda, &client.program_id),
+            seed_hash,
+            seed_input,
+            world_seed_hash: world.world_seed_hash,
+        }
+    }
+
+    /// Read the state
+    pub fn read<T: MojoState>(&self, client: &SdkClient) -> Result<T, MojoSDKError> {
+        Ok(self.read_versioned(client)?.0)
+    }
+
+    /// Read the state along with its version, to be passed to [`SharedState::write_if_version`]
+    pub fn read_versioned<T: MojoState>(
+        &self,
+        client: &SdkClient,
+    ) -> Result<(T, u64), MojoSDKError> {
+        let account_data = World::fetch_owned_account_data(client, &self.state_pda)?;
+        let (header, state_data) = StateHeader::split_account_data(&account_data)?;
+        Ok((header.decode_state(state_data)?, header.version()))
+    }
+
+    /// Read the access list of the state
+    pub fn read_acl(&self, client: &SdkClient) -> Result<StateAcl, MojoSDKError> {
+        let account_data = World::fetch_owned_account_data(client, &self.acl_pda)?;
+        StateAcl::from_account_data(&account_data)
+    }
+
+    /// Replace the state, `writer` has to be allowed by the access list
+    ///
+    /// `writer` is the keypair of a player or a [`crate::Session`] of theirs. Shared states keep their
+    /// size, shorter (framed) states are zero padded. Delegated states are written through the
+    /// ephemeral rollup.
+    pub fn write<'a, T: MojoState>(
+        &self,
+        client: &SdkClient,
+        writer: impl Into<Writer<'a>>,
+        state: T,
+    ) -> Result<(), MojoSDKError> {
+        self.write_versioned(client, writer.into(), state, None)
+    }
+
+    /// Replace the state only if it is still at `expected_version`
+    pub fn write_if_version<'a, T: MojoState>(
+        &self,
+        client: &SdkClient,
+        writer: impl Into<Writer<'a>>,
+        state: T,
+        expected_version: u64,
+    ) -> Result<(), MojoSDKError> {
+        self.write_versioned(client, writer.into(), state, Some(expected_version))
+    }
+
+    fn write_versioned<T: MojoState>(
+        &self,
+        client: &SdkClient,
+        writer: Writer<'_>,
+        state: T,
+        expected_version: Option<u64>,
+    ) -> Result<(), MojoSDKError> {
+        let account_data = World::fetch_owned_account_data(client, &self.state_pda)?;
+        let (header, current) = StateHeader::split_account_data(&account_data)?;
+        header.check_schema::<T>()?;
+        let state_data = World::fit_to_account(state.serialize()?, current.len())?;
+
+        let builder = UpdateDelegatedAccountBuilder::new(
+            client.program_id,
+            writer.signer().pubkey(),
+            self.state_pda,
+            self.world_pda,
+            &self.seed_input,
+            state_data,
+        )
+        .schema(StateSchema::of::<T>());
+        let builder = match writer.session() {
+            Some((session, counted)) => builder.session(session, counted),
+            None => builder,
+        };
+        let (acl, membership) = self.access_accounts(client, &writer.authority())?;
+        let builder = builder.access_list(acl, membership);
+        let update_ix = match expected_version {
+            Some(version) => builder.expected_version(version),
+            None => builder,
+        }
+        .build()?;
+
+        let rpc = client.rpc_for(&self.state_pda)?;
+        World::submit_instructions(client, rpc, writer.signer(), vec![update_ix]).map_err(|err| {
+            match expected_version {
+                Some(expected) => World::version_conflict(rpc, &self.state_pda, expected, err),
+                None => err,
+            }
+        })
+    }
+
+    /// Write only the `segments` of the state, see [`World::patch_state`]
+    pub fn patch<'a>(
+        &self,
+        client: &SdkClient,
+        writer: impl Into<Writer<'a>>,
+        segments: &[PatchSegment],
+    ) -> Result<(), MojoSDKError> {
+        let writer = writer.into();
+        let builder = PatchAccountBuilder::new(
+            client.program_id,
+            writer.signer().pubkey(),
+            self.state_pda,
+            self.world_pda,
+            &self.seed_input,
+            segments.to_vec(),
+        );
+        let builder = match writer.session() {
+            Some((session, counted)) => builder.session(session, counted),
+            None => builder,
+        };
+        let (acl, membership) = self.access_accounts(client, &writer.authority())?;
+        let patch_ix = builder.access_list(acl, membership).build()?;
+        World::submit_instructions(
+            client,
+            client.rpc_for(&self.state_pda)?,
+            writer.signer(),
+            vec![patch_ix],
+        )
+    }
+
+    /// Add `delta` to an integer field of the state
+    pub fn increment<'a, T, F: IntegerField>(
+        &self,
+        client: &SdkClient,
+        writer: impl Into<Writer<'a>>,
+        field: StateField<T, F>,
+        delta: F,
+    ) -> Result<(), MojoSDKError> {
+        self.apply_field_ops(client, writer, &[field.add(delta)])
+    }
+
+    /// Apply `ops` to integer fields of the state, see [`World::apply_field_ops`]
+    pub fn apply_field_ops<'a>(
+        &self,
+        client: &SdkClient,
+        writer: impl Into<Writer<'a>>,
+        ops: &[FieldOp],
+    ) -> Result<(), MojoSDKError> {
+        let writer = writer.into();
+        let builder = ArithmeticBuilder::new(
+            client.program_id,
+            writer.signer().pubkey(),
+            self.state_pda,
+            self.world_pda,
+            &self.seed_input,
+            ops.to_vec(),
+        );
+        let builder = match writer.session() {
+            Some((session, counted)) => builder.session(session, counted),
+            None => builder,
+        };
+        let (acl, membership) = self.access_accounts(client, &writer.authority())?;
+        let arithmetic_ix = builder.access_list(acl, membership).build()?;
+        World::submit_instructions(
+            client,
+            client.rpc_for(&self.state_pda)?,
+            writer.signer(),
+            vec![arithmetic_ix],
+        )
+    }
+
+    /// List `writer` in the access list, `creator` is the creator of the world
+    /// (sent to the base layer)
+    pub fn add_writer(
+        &self,
+        client: &SdkClient,
+        creator: &Keypair,
+        writer: &Pubkey,
+    ) -> Result<(), MojoSDKError> {
+        let add_ix = ManageWriterBuilder::new(
+            client.program_id,
+            creator.pubkey(),
+            self.state_pda,
+            self.world_pda,
+            *writer,
+        )
+        .build()?;
+        World::submit_instructions(client, client.client(), creator, vec![add_ix])
+    }
+
+    /// Drop `writer` from the access list (sent to the base layer)
+    pub fn remove_writer(
+        &self,
+        client: &SdkClient,
+        creator: &Keypair,
+        writer: &Pubkey,
+    ) -> Result<(), MojoSDKError> {
+        let remove_ix = ManageWriterBuilder::new(
+            client.program_id,
+            creator.pubkey(),
+            self.state_pda,
+            self.world_pda,
+            *writer,
+        )
+        .remove()
+        .build()?;
+        World::submit_instructions(client, client.client(), creator, vec![remove_ix])
+    }
+
+    /// Close the state and its access list, returning their rent to `creator`
+    /// (sent to the base layer)
+    pub fn close(&self, client: &SdkClient, creator: &Keypair) -> Result<(), MojoSDKError> {
+        let close_ix = CloseAccountBuilder::new(
+            client.program_id,
+            creator.pubkey(),
+            self.state_pda,
+            self.world_pda,
+            self.seed_hash,
+        )
+        .children(vec![self.acl_pda])
+        .build()?;
+        World::submit_instructions(client, client.client(), creator, vec![close_ix])
+    }
+
+    /// The access list followed, when `writer` relies on being a world member, by a state of
+    /// theirs proving it
+    fn access_accounts(
+        &self,
+        client: &SdkClient,
+        writer: &Pubkey,
+    ) -> Result<(Pubkey, Option<Pubkey>), MojoSDKError> {
+        let acl = self.read_acl(client)?;
+        if acl.access_mode() != Some(AccessMode::AnyMember) || acl.is_listed(writer) {
+            return Ok((self.acl_pda, None));
+        }
+
+        let world = World {
+            world_pda: self.world_pda,
+            world_seed_hash: self.world_seed_hash,
+        };
+        let membership = world
+            .states_of_owner(client, writer)?
+            .first()
+            .map(|entry| entry.pubkey);
+        Ok((self.acl_pda, membership))
+    }
+}
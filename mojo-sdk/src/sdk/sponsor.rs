@@ -0,0 +1,83 @@
+//! Transactions paid by a sponsor, letting players without SOL own states
+
+use crate::{errors::MojoSDKError, Endpoint, SdkClient};
+
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_message::Message;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+/// A transaction signed by the state owner, waiting for the sponsor paying its fees and rent
+///
+/// Built on the player side with [`World::sponsored_write_state`] or
+/// [`World::sponsored_delegate_state`], then handed to the game server holding the sponsor key.
+/// The recent blockhash is set when building, so the server has to co-sign it before it expires.
+///
+/// [`World::sponsored_write_state`]: crate::World::sponsored_write_state
+/// [`World::sponsored_delegate_state`]: crate::World::sponsored_delegate_state
+#[derive(Debug, Clone)]
+pub struct PartiallySignedTransaction {
+    pub transaction: Transaction,
+    /// The endpoint the transaction has to be sent to
+    pub endpoint: Endpoint,
+}
+
+impl PartiallySignedTransaction {
+    /// Build a transaction of `instructions` paid by `sponsor` and sign it as `owner`
+    pub(crate) fn new(
+        client: &SdkClient,
+        endpoint: Endpoint,
+        owner: &Keypair,
+        sponsor: &Pubkey,
+        instructions: Vec<Instruction>,
+    ) -> Result<Self, MojoSDKError> {
+        let message = Message::new(&instructions, Some(sponsor));
+        let recent_blockhash = client.rpc(endpoint).latest_blockhash()?;
+
+        let mut transaction = Transaction::new_unsigned(message);
+        transaction
+            .try_partial_sign(&[owner], recent_blockhash)
+            .map_err(|err| MojoSDKError::SolanaSdk(err.to_string()))?;
+        Ok(Self {
+            transaction,
+            endpoint,
+        })
+    }
+
+    /// The sponsor expected to co-sign, paying the fees
+    pub fn fee_payer(&self) -> Pubkey {
+        self.transaction.message.account_keys[0]
+    }
+
+    /// Add the signature of `sponsor`, completing the transaction
+    pub fn co_sign(mut self, sponsor: &Keypair) -> Result<Transaction, MojoSDKError> {
+        if sponsor.pubkey() != self.fee_payer() {
+            return Err(MojoSDKError::SolanaSdk(format!(
+                "Transaction is paid by {}, not {}",
+                self.fee_payer(),
+                sponsor.pubkey()
+            )));
+        }
+
+        let recent_blockhash = self.transaction.message.recent_blockhash;
+        self.transaction
+            .try_partial_sign(&[sponsor], recent_blockhash)
+            .map_err(|err| MojoSDKError::SolanaSdk(err.to_string()))?;
+        Ok(self.transaction)
+    }
+
+    /// Co-sign as `sponsor` and send the transaction to its endpoint
+    pub fn co_sign_and_send(
+        self,
+        client: &SdkClient,
+        sponsor: &Keypair,
+    ) -> Result<(), MojoSDKError> {
+        let endpoint = self.endpoint;
+        let transaction = self.co_sign(sponsor)?;
+
+        client.rpc(endpoint).send_transaction(&transaction)?;
+        Ok(())
+    }
+}
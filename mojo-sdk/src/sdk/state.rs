@@ -1,13 +1,27 @@
 //! Definitions for Mojo state types
 
-use crate::errors::MojoSDKError;
+use crate::{errors::MojoSDKError, StateSchema};
 
 /// Trait that all Mojo state types must implement
 ///
 /// Defines how state types are serialized and deserialized when interacting with chain
 /// @dev Whatever state structure, since you interact with chain
 /// it has to be serializable and deserializable
+///
+/// Plain data structs get it from `#[mojo_state]` or `#[derive(MojoState)]`
 pub trait MojoState: Sized {
+    /// Identifier of the state type recorded in the state header, 0 for untyped states
+    const STATE_TYPE_ID: u8 = 0;
+
+    /// Layout version of the state type recorded in the state header, raised whenever the
+    /// layout changes so older states can be migrated
+    const SCHEMA_VERSION: u8 = 0;
+
+    /// Hash of the state layout recorded in the state header, zeroed for untyped states
+    ///
+    /// `#[mojo_state]` derives it from the struct name and its fields with [`schema_hash`]
+    const SCHEMA_HASH: [u8; 8] = [0; 8];
+
     /// Serialize the state to bytes
     fn serialize(&self) -> Result<Vec<u8>, MojoSDKError>;
 
@@ -18,6 +32,97 @@ pub trait MojoState: Sized {
     fn size(&self) -> usize {
         self.serialize().map(|data| data.len()).unwrap_or(0)
     }
+
+    /// Build the state from `data` written under the older `schema_version` of its type
+    ///
+    /// States do not migrate unless they implement [`Migrate`], which `#[mojo_state(migrate)]`
+    /// hooks in here
+    fn migrate_from(schema_version: u8, _data: &[u8]) -> Result<Self, MojoSDKError> {
+        Err(MojoSDKError::SchemaMismatch(format!(
+            "no migration from schema version {} to {}",
+            schema_version,
+            Self::SCHEMA_VERSION
+        )))
+    }
+}
+
+/// Upgrade path for states written under older layouts of a state type
+///
+/// Raise the `version` of the state with every layout change and rebuild the current state
+/// from the bytes of any older version. Reads apply it automatically, the migrated state is
+/// persisted by the next write or by [`crate::World::migrate_state`].
+///
+/// # Example
+/// ```ignore
+/// #[mojo_state(type_id = 2, version = 1, seed = "hero", migrate)]
+/// pub struct Hero {
+///     level: u64,
+///     gold: u64,
+/// }
+///
+/// impl Migrate for Hero {
+///     fn from_version(version: u8, bytes: &[u8]) -> Result<Self, MojoSDKError> {
+///         match version {
+///             // version 0 held the level only
+///             0 => Ok(Hero { level: bytemuck::pod_read_unaligned(&bytes[..8]), gold: 0 }),
+///             _ => Err(MojoSDKError::SchemaMismatch(format!("unknown version {}", version))),
+///         }
+///     }
+/// }
+/// ```
+pub trait Migrate: MojoState {
+    /// Build the current state from `bytes` written under schema `version`
+    fn from_version(version: u8, bytes: &[u8]) -> Result<Self, MojoSDKError>;
+}
+
+/// Hash a state layout description into the schema hash stored in state headers
+///
+/// 64 bit FNV-1a, usable in constants so the derive macros compute it at compile time
+pub const fn schema_hash(layout: &str) -> [u8; 8] {
+    let bytes = layout.as_bytes();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+        i += 1;
+    }
+    hash.to_le_bytes()
+}
+
+/// A byte range written into a state by [`crate::World::patch_state`]
+///
+/// The offset is relative to the start of the state. `#[derive(MojoState)]` generates a
+/// `patch_<field>` constructor for every named field of the state, its segments only land
+/// on states following the schema of the type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchSegment {
+    pub offset: usize,
+    pub data: Vec<u8>,
+    /// Schema the state has to follow, any state is patched without one
+    pub schema: Option<StateSchema>,
+}
+
+impl PatchSegment {
+    /// Segment writing `data` at `offset`
+    pub fn new(offset: usize, data: Vec<u8>) -> Self {
+        Self {
+            offset,
+            data,
+            schema: None,
+        }
+    }
+
+    /// Segment writing the bytes of the plain data `value` at `offset`
+    pub fn value<T: bytemuck::Pod>(offset: usize, value: &T) -> Self {
+        Self::new(offset, bytemuck::bytes_of(value).to_vec())
+    }
+
+    /// Only write the segment into states of type `T`
+    pub fn typed<T: MojoState>(mut self) -> Self {
+        self.schema = Some(StateSchema::of::<T>());
+        self
+    }
 }
 
 /// Helper macro acts as a wrapper for bytemuck
@@ -30,15 +135,123 @@ macro_rules! impl_mojo_state_pod {
             }
 
             fn deserialize(data: &[u8]) -> Result<Self, $crate::errors::MojoSDKError> {
-                bytemuck::try_from_bytes(data)
-                    .map(|p: &Self| *p)
-                    .map_err(|e| {
-                        $crate::MojoSDKError::Deserialization(format!(
-                            "Failed to deserialize: {}",
-                            e
-                        ))
-                    })
+                bytemuck::try_pod_read_unaligned(data).map_err(|e| {
+                    $crate::MojoSDKError::Deserialization(format!("Failed to deserialize: {}", e))
+                })
             }
         }
     };
 }
+
+/// Size of the length prefix written in front of codec encoded states
+pub const FRAME_PREFIX_LEN: usize = core::mem::size_of::<u32>();
+
+/// Pluggable encoding for states that are not plain old data (`Vec`, `String`, `Option`, ...)
+///
+/// Backends for borsh and postcard ship behind the `borsh` and `postcard` features
+pub trait MojoCodec<T> {
+    /// Encode the state to bytes
+    fn encode(value: &T) -> Result<Vec<u8>, MojoSDKError>;
+
+    /// Decode the state from exactly the bytes produced by `encode`
+    fn decode(data: &[u8]) -> Result<T, MojoSDKError>;
+}
+
+/// Prefix an encoded state with its little endian u32 length
+///
+/// Accounts keep the size they were created with, so a shorter state leaves trailing
+/// bytes behind; the prefix tells readers where the payload ends
+pub fn frame(payload: Vec<u8>) -> Result<Vec<u8>, MojoSDKError> {
+    let len = u32::try_from(payload.len()).map_err(|_| {
+        MojoSDKError::Serialization(format!("State too large to frame: {} bytes", payload.len()))
+    })?;
+
+    let mut framed = Vec::with_capacity(FRAME_PREFIX_LEN + payload.len());
+    framed.extend_from_slice(&len.to_le_bytes());
+    framed.extend_from_slice(&payload);
+    Ok(framed)
+}
+
+/// Get the payload of a framed state, ignoring any trailing bytes
+pub fn unframe(data: &[u8]) -> Result<&[u8], MojoSDKError> {
+    let prefix = data.get(..FRAME_PREFIX_LEN).ok_or_else(|| {
+        MojoSDKError::Deserialization(format!("Framed state too small: got {} bytes", data.len()))
+    })?;
+    let len = u32::from_le_bytes(prefix.try_into().expect("prefix is 4 bytes")) as usize;
+
+    data[FRAME_PREFIX_LEN..].get(..len).ok_or_else(|| {
+        MojoSDKError::Deserialization(format!(
+            "Framed state declares {} bytes but only {} are available",
+            len,
+            data.len() - FRAME_PREFIX_LEN
+        ))
+    })
+}
+
+/// Borsh backend for [`MojoCodec`]
+#[cfg(feature = "borsh")]
+pub struct BorshCodec;
+
+#[cfg(feature = "borsh")]
+impl<T: borsh::BorshSerialize + borsh::BorshDeserialize> MojoCodec<T> for BorshCodec {
+    fn encode(value: &T) -> Result<Vec<u8>, MojoSDKError> {
+        borsh::to_vec(value)
+            .map_err(|e| MojoSDKError::Serialization(format!("Failed to serialize: {}", e)))
+    }
+
+    fn decode(data: &[u8]) -> Result<T, MojoSDKError> {
+        borsh::from_slice(data)
+            .map_err(|e| MojoSDKError::Deserialization(format!("Failed to deserialize: {}", e)))
+    }
+}
+
+/// Postcard backend for [`MojoCodec`]
+#[cfg(feature = "postcard")]
+pub struct PostcardCodec;
+
+#[cfg(feature = "postcard")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> MojoCodec<T> for PostcardCodec {
+    fn encode(value: &T) -> Result<Vec<u8>, MojoSDKError> {
+        postcard::to_allocvec(value)
+            .map_err(|e| MojoSDKError::Serialization(format!("Failed to serialize: {}", e)))
+    }
+
+    fn decode(data: &[u8]) -> Result<T, MojoSDKError> {
+        postcard::from_bytes(data)
+            .map_err(|e| MojoSDKError::Deserialization(format!("Failed to deserialize: {}", e)))
+    }
+}
+
+/// Helper macro implementing `MojoState` through a [`MojoCodec`] with length-prefix framing
+#[macro_export]
+macro_rules! impl_mojo_state_codec {
+    ($type:ty, $codec:ty) => {
+        impl $crate::MojoState for $type {
+            fn serialize(&self) -> Result<Vec<u8>, $crate::MojoSDKError> {
+                $crate::frame(<$codec as $crate::MojoCodec<$type>>::encode(self)?)
+            }
+
+            fn deserialize(data: &[u8]) -> Result<Self, $crate::MojoSDKError> {
+                <$codec as $crate::MojoCodec<$type>>::decode($crate::unframe(data)?)
+            }
+        }
+    };
+}
+
+/// Helper macro acts as a wrapper for borsh
+#[cfg(feature = "borsh")]
+#[macro_export]
+macro_rules! impl_mojo_state_borsh {
+    ($type:ty) => {
+        $crate::impl_mojo_state_codec!($type, $crate::BorshCodec);
+    };
+}
+
+/// Helper macro acts as a wrapper for postcard
+#[cfg(feature = "postcard")]
+#[macro_export]
+macro_rules! impl_mojo_state_postcard {
+    ($type:ty) => {
+        $crate::impl_mojo_state_codec!($type, $crate::PostcardCodec);
+    };
+}
@@ -0,0 +1,391 @@
+//! Live state subscriptions over account-change websockets
+//!
+//! A subscription listens to the base layer and the ephemeral rollup at the same time, so
+//! updates keep flowing while a state is delegated and undelegated. Dropped connections are
+//! reopened and resubscribed with an exponential backoff.
+
+use std::{
+    marker::PhantomData,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_pubkey::Pubkey;
+use solana_pubsub_client::pubsub_client::{AccountSubscription, PubsubClient};
+
+use crate::{errors::MojoSDKError, state::MojoState, Endpoint, SdkClient, StateHeader};
+
+/// How long a listener waits on its stream before checking for shutdown
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A raw account change notification
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountNotification {
+    pub slot: u64,
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+/// An open account subscription on one endpoint
+pub trait AccountStream: Send {
+    /// Wait up to `timeout` for the next notification
+    ///
+    /// Returns `Ok(None)` on timeout and an error once the connection is gone
+    fn recv_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Option<AccountNotification>, MojoSDKError>;
+}
+
+/// Source of account subscriptions, implemented over websockets by [`WebsocketPubsub`]
+///
+/// Swap it for a local stand-in to test subscribers without a validator
+pub trait AccountPubsub: Send + Sync + 'static {
+    /// Subscribe to changes of `account` on `endpoint`
+    fn account_subscribe(
+        &self,
+        endpoint: Endpoint,
+        account: &Pubkey,
+    ) -> Result<Box<dyn AccountStream>, MojoSDKError>;
+}
+
+/// [`AccountPubsub`] backed by the Solana `accountSubscribe` websocket API
+pub struct WebsocketPubsub {
+    pub ws_url: String,
+    pub er_ws_url: String,
+}
+
+impl WebsocketPubsub {
+    /// Use the websocket endpoints of `client`
+    pub fn from_client(client: &SdkClient) -> Self {
+        Self {
+            ws_url: client.ws_url.clone(),
+            er_ws_url: client.er_ws_url.clone(),
+        }
+    }
+}
+
+impl AccountPubsub for WebsocketPubsub {
+    fn account_subscribe(
+        &self,
+        endpoint: Endpoint,
+        account: &Pubkey,
+    ) -> Result<Box<dyn AccountStream>, MojoSDKError> {
+        let url = match endpoint {
+            Endpoint::Base => &self.ws_url,
+            Endpoint::Ephemeral => &self.er_ws_url,
+        };
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        };
+
+        let subscription = PubsubClient::account_subscribe(url, account, Some(config))
+            .map_err(|e| MojoSDKError::Subscription(format!("{}: {}", url, e)))?;
+
+        Ok(Box::new(WebsocketStream { subscription }))
+    }
+}
+
+struct WebsocketStream {
+    subscription: AccountSubscription,
+}
+
+impl AccountStream for WebsocketStream {
+    fn recv_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Option<AccountNotification>, MojoSDKError> {
+        let response = match self.subscription.1.recv_timeout(timeout) {
+            Ok(response) => response,
+            Err(e) if e.is_timeout() => return Ok(None),
+            Err(e) => return Err(MojoSDKError::Subscription(e.to_string())),
+        };
+
+        let account = response.value;
+        let owner = Pubkey::from_str(&account.owner)
+            .map_err(|e| MojoSDKError::Deserialization(format!("Invalid owner: {}", e)))?;
+        let data = account.data.decode().ok_or_else(|| {
+            MojoSDKError::Deserialization("Unsupported account data encoding".to_string())
+        })?;
+
+        Ok(Some(AccountNotification {
+            slot: response.context.slot,
+            owner,
+            data,
+        }))
+    }
+}
+
+impl Drop for WebsocketStream {
+    fn drop(&mut self) {
+        let _ = self.subscription.0.shutdown();
+    }
+}
+
+/// Reconnect behaviour of a subscription
+#[derive(Debug, Clone)]
+pub struct SubscriptionConfig {
+    /// Endpoints to listen on, both by default
+    pub endpoints: Vec<Endpoint>,
+    /// Delay before the first reconnect attempt, doubled on every failure
+    pub reconnect_delay: Duration,
+    /// Upper bound of the reconnect delay
+    pub max_reconnect_delay: Duration,
+}
+
+impl Default for SubscriptionConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: vec![Endpoint::Base, Endpoint::Ephemeral],
+            reconnect_delay: Duration::from_millis(500),
+            max_reconnect_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+/// A decoded state change
+#[derive(Debug, Clone)]
+pub struct StateUpdate<T> {
+    /// The PDA of the state
+    pub pubkey: Pubkey,
+    /// The endpoint that reported the change
+    pub endpoint: Endpoint,
+    /// Slot of the change on that endpoint
+    pub slot: u64,
+    pub header: StateHeader,
+    pub state: T,
+}
+
+/// Stops the listeners of a subscription when unsubscribed or dropped
+pub struct SubscriptionHandle {
+    shutdown: Arc<AtomicBool>,
+    listeners: Vec<JoinHandle<()>>,
+}
+
+impl SubscriptionHandle {
+    /// Stop listening and wait for the listeners to close their connections
+    pub fn unsubscribe(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        for listener in self.listeners.drain(..) {
+            let _ = listener.join();
+        }
+    }
+}
+
+impl Drop for SubscriptionHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Typed stream of state changes, see [`crate::World::subscribe_state`]
+///
+/// Connection failures are reported as errors on the stream while the listeners retry
+pub struct StateSubscription<T> {
+    receiver: Receiver<Result<StateUpdate<T>, MojoSDKError>>,
+    handle: SubscriptionHandle,
+}
+
+impl<T: MojoState + Send + 'static> StateSubscription<T> {
+    /// Open a subscription to the state at `account`
+    pub fn open(
+        pubsub: Arc<dyn AccountPubsub>,
+        program_id: Pubkey,
+        account: Pubkey,
+        config: SubscriptionConfig,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        // the same change can be reported by both endpoints, e.g. by a rollup clone
+        let last_data = Arc::new(Mutex::new(None));
+
+        let listeners = config
+            .endpoints
+            .iter()
+            .map(|&endpoint| {
+                let listener = Listener {
+                    pubsub: pubsub.clone(),
+                    program_id,
+                    account,
+                    endpoint,
+                    config: config.clone(),
+                    sender: sender.clone(),
+                    shutdown: shutdown.clone(),
+                    last_data: last_data.clone(),
+                    _state: PhantomData,
+                };
+                thread::spawn(move || listener.run())
+            })
+            .collect();
+
+        Self {
+            receiver,
+            handle: SubscriptionHandle {
+                shutdown,
+                listeners,
+            },
+        }
+    }
+
+    /// Block until the next update
+    pub fn recv(&self) -> Option<Result<StateUpdate<T>, MojoSDKError>> {
+        self.receiver.recv().ok()
+    }
+
+    /// Wait up to `timeout` for the next update
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Result<StateUpdate<T>, MojoSDKError>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(update) => Some(update),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => None,
+        }
+    }
+
+    /// Get the next update if one is waiting, meant to be polled once per frame
+    pub fn try_recv(&self) -> Option<Result<StateUpdate<T>, MojoSDKError>> {
+        match self.receiver.try_recv() {
+            Ok(update) => Some(update),
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+        }
+    }
+
+    /// Drain every waiting update, returning the most recent state if any
+    pub fn latest(&self) -> Option<StateUpdate<T>> {
+        self.receiver.try_iter().filter_map(Result::ok).last()
+    }
+
+    /// Hand every update to `callback` on a background thread
+    pub fn for_each<F>(self, mut callback: F) -> SubscriptionHandle
+    where
+        F: FnMut(Result<StateUpdate<T>, MojoSDKError>) + Send + 'static,
+    {
+        let StateSubscription {
+            receiver,
+            mut handle,
+        } = self;
+
+        // ends once the listeners are stopped and drop their senders
+        handle.listeners.push(thread::spawn(move || {
+            for update in receiver {
+                callback(update);
+            }
+        }));
+        handle
+    }
+
+    /// Stop the subscription
+    pub fn unsubscribe(self) {
+        self.handle.unsubscribe();
+    }
+}
+
+struct Listener<T> {
+    pubsub: Arc<dyn AccountPubsub>,
+    program_id: Pubkey,
+    account: Pubkey,
+    endpoint: Endpoint,
+    config: SubscriptionConfig,
+    sender: Sender<Result<StateUpdate<T>, MojoSDKError>>,
+    shutdown: Arc<AtomicBool>,
+    last_data: Arc<Mutex<Option<Vec<u8>>>>,
+    _state: PhantomData<fn() -> T>,
+}
+
+impl<T: MojoState + Send + 'static> Listener<T> {
+    fn run(self) {
+        let mut delay = self.config.reconnect_delay;
+
+        while !self.is_shutdown() {
+            match self.pubsub.account_subscribe(self.endpoint, &self.account) {
+                Ok(mut stream) => {
+                    delay = self.config.reconnect_delay;
+                    if !self.listen(stream.as_mut()) {
+                        return;
+                    }
+                }
+                Err(err) => {
+                    if self.sender.send(Err(err)).is_err() {
+                        return;
+                    }
+                }
+            }
+
+            self.sleep(delay);
+            delay = (delay * 2).min(self.config.max_reconnect_delay);
+        }
+    }
+
+    /// Forward notifications until the stream drops, false once nobody is listening anymore
+    fn listen(&self, stream: &mut dyn AccountStream) -> bool {
+        while !self.is_shutdown() {
+            match stream.recv_timeout(POLL_INTERVAL) {
+                Ok(Some(notification)) => {
+                    if let Some(update) = self.decode(notification) {
+                        if self.sender.send(update).is_err() {
+                            return false;
+                        }
+                    }
+                }
+                Ok(None) => continue,
+                Err(_) => return true,
+            }
+        }
+        false
+    }
+
+    fn decode(
+        &self,
+        notification: AccountNotification,
+    ) -> Option<Result<StateUpdate<T>, MojoSDKError>> {
+        // while delegated the base layer account belongs to the delegation program
+        if notification.owner != self.program_id {
+            return None;
+        }
+
+        {
+            let mut last_data = self.last_data.lock().unwrap_or_else(|e| e.into_inner());
+            if last_data.as_ref() == Some(&notification.data) {
+                return None;
+            }
+            *last_data = Some(notification.data.clone());
+        }
+
+        let update =
+            StateHeader::split_account_data(&notification.data).and_then(|(header, state_data)| {
+                Ok(StateUpdate {
+                    pubkey: self.account,
+                    endpoint: self.endpoint,
+                    slot: notification.slot,
+                    header,
+                    state: header.decode_state(state_data)?,
+                })
+            });
+        Some(update)
+    }
+
+    fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::Relaxed)
+    }
+
+    /// Sleep in small steps so shutdown is not held up by a long backoff
+    fn sleep(&self, duration: Duration) {
+        let mut remaining = duration;
+        while !remaining.is_zero() && !self.is_shutdown() {
+            let step = remaining.min(POLL_INTERVAL);
+            thread::sleep(step);
+            remaining -= step;
+        }
+    }
+}
@@ -1,13 +1,28 @@
 //! World struct represents the crux of the engine
 
 use crate::{
-    errors::MojoSDKError, instruction_builder::UpdateDelegatedAccountBuilder, state::MojoState,
-    types::derive_pda, utils::helpers as utils, GenIxHandler, MojoInstructionDiscriminator,
-    SdkClient,
+    arithmetic::{FieldOp, IntegerField, StateField},
+    errors::{MojoProgramError, MojoSDKError},
+    instruction_builder::{
+        acl_pda, ArithmeticBuilder, CloseAccountBuilder, CommitAccountBuilder, CommitBatchBuilder,
+        DelegateAccountBuilder, DelegateBatchBuilder, MigrateAccountBuilder, PatchAccountBuilder,
+        ResizeAccountBuilder, UpdateDelegatedAccountBuilder,
+    },
+    sponsor::PartiallySignedTransaction,
+    state::{MojoState, PatchSegment},
+    subscription::{
+        StateSubscription, StateUpdate, SubscriptionConfig, SubscriptionHandle, WebsocketPubsub,
+    },
+    transport::Transport,
+    types::derive_pda,
+    utils::helpers as utils,
+    DelegationOptions, Endpoint, GenIxHandler, MojoInstructionDiscriminator, SdkClient,
+    StateHeader, StateSchema, WorldHeader, STATE_MAGIC,
 };
 
-use solana_client::client_error::{ClientError, ClientErrorKind};
-use solana_client::rpc_request::RpcError;
+use std::sync::Arc;
+
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
 use solana_instruction::{AccountMeta, Instruction};
 use solana_keypair::Keypair;
 use solana_message::Message;
@@ -17,6 +32,9 @@ use solana_system_program::id as system_program_id;
 use solana_sysvar::rent::ID as rent_id;
 use solana_transaction::Transaction;
 
+/// Largest serialized transaction a cluster accepts
+pub const MAX_TRANSACTION_SIZE: usize = 1232;
+
 /// Represents Mojo World which is seen as a container of states of the game
 pub struct World {
     /// The PDA of the game world
@@ -26,12 +44,22 @@ pub struct World {
 
 impl World {
     /// Create a new World instance
-
     pub fn create_world<T: MojoState>(
         client: &SdkClient,
         creator: &Keypair,
         world_name: &str,
         initial_world_state: T,
+    ) -> Result<World, MojoSDKError> {
+        Self::create_world_with_config(client, creator, world_name, [0u8; 8], initial_world_state)
+    }
+
+    /// Create a new World instance with world level `config` bytes stored in its header
+    pub fn create_world_with_config<T: MojoState>(
+        client: &SdkClient,
+        creator: &Keypair,
+        world_name: &str,
+        config: [u8; 8],
+        initial_world_state: T,
     ) -> Result<World, MojoSDKError> {
         // Serialize the state data
         let state_data = initial_world_state.serialize()?;
@@ -53,8 +81,9 @@ impl World {
         };
 
         let instruction_data = [
-            vec![MojoInstructionDiscriminator::CreateAccount as u8], // Discriminator
+            vec![MojoInstructionDiscriminator::CreateWorld as u8], // Discriminator
             bytemuck::bytes_of(&mojo_data).to_vec(),
+            config.to_vec(),
             state_data,
         ]
         .concat();
@@ -71,7 +100,7 @@ impl World {
             data: instruction_data,
         };
 
-        Self::submit_instructions(client, creator, vec![ix])?;
+        Self::submit_instructions(client, client.client(), creator, vec![ix])?;
 
         Ok(World {
             world_pda,
@@ -79,6 +108,41 @@ impl World {
         })
     }
 
+    /// Open an existing World created by `creator` under `world_name`
+    pub fn open(
+        client: &SdkClient,
+        creator: &Pubkey,
+        world_name: &str,
+    ) -> Result<World, MojoSDKError> {
+        let seed_bytes = utils::compute_hash(&Self::world_seed_input(world_name, creator));
+        let (world_pda, _bump) = derive_pda(&[&seed_bytes, creator.as_ref()], &client.program_id);
+
+        Self::from_pda(client, &world_pda)
+    }
+
+    /// Open an existing World from its PDA
+    ///
+    /// Verifies the account exists, is owned by the program and that its header derives `world_pda`
+    pub fn from_pda(client: &SdkClient, world_pda: &Pubkey) -> Result<World, MojoSDKError> {
+        let account_data = Self::fetch_owned_account_data(client, world_pda)?;
+        let header = WorldHeader::from_account_data(&account_data)?;
+
+        let creator = header.creator();
+        let (expected_pda, _bump) =
+            derive_pda(&[&header.name_hash, creator.as_ref()], &client.program_id);
+        if &expected_pda != world_pda {
+            return Err(MojoSDKError::InvalidPda(format!(
+                "{} is not a world PDA, its header derives {}",
+                world_pda, expected_pda
+            )));
+        }
+
+        Ok(World {
+            world_pda: *world_pda,
+            world_seed_hash: header.name_hash,
+        })
+    }
+
     pub fn write_state<T: MojoState>(
         &self,
         client: &SdkClient,
@@ -86,40 +150,311 @@ impl World {
         owner: &Keypair,
         state: T,
     ) -> Result<(), MojoSDKError> {
-        // Serialize the state data
-        let state_data = state.serialize()?;
+        self.write_state_versioned(client, state_name, owner, state, None)
+    }
 
+    /// Write the state only if nobody wrote it since `expected_version`
+    ///
+    /// The version comes from [`World::read_versioned_state`] or [`StateHeader::version`], a
+    /// write that lost the race fails with [`MojoSDKError::VersionConflict`] and can be retried
+    /// on top of the new state. The state must already exist and keep its size.
+    pub fn write_state_if_version<T: MojoState>(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+        state: T,
+        expected_version: u64,
+    ) -> Result<(), MojoSDKError> {
+        self.write_state_versioned(client, state_name, owner, state, Some(expected_version))
+    }
+
+    fn write_state_versioned<T: MojoState>(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+        state: T,
+        expected_version: Option<u64>,
+    ) -> Result<(), MojoSDKError> {
         let owner_pubkey = owner.pubkey();
+        let (endpoint, account_pda, instruction) = self.write_state_instruction(
+            client,
+            state_name,
+            &owner_pubkey,
+            &client.payer_for(&owner_pubkey),
+            StateSchema::of::<T>(),
+            state.serialize()?,
+            expected_version,
+        )?;
+
+        let rpc = client.rpc(endpoint);
+        Self::submit_instructions(client, rpc, owner, vec![instruction]).map_err(|err| {
+            match expected_version {
+                Some(expected) => Self::version_conflict(rpc, &account_pda, expected, err),
+                None => err,
+            }
+        })
+    }
+
+    /// Build a state write signed by `owner` only, for `sponsor` to co-sign and pay
+    ///
+    /// The sponsor pays the fees and, when the state is created or grows, its rent. Hand the
+    /// transaction to the game server holding the sponsor key, which sends it with
+    /// [`PartiallySignedTransaction::co_sign_and_send`].
+    pub fn sponsored_write_state<T: MojoState>(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+        sponsor: &Pubkey,
+        state: T,
+    ) -> Result<PartiallySignedTransaction, MojoSDKError> {
+        let (endpoint, _account_pda, instruction) = self.write_state_instruction(
+            client,
+            state_name,
+            &owner.pubkey(),
+            sponsor,
+            StateSchema::of::<T>(),
+            state.serialize()?,
+            None,
+        )?;
+        PartiallySignedTransaction::new(client, endpoint, owner, sponsor, vec![instruction])
+    }
+
+    /// Build the instruction writing `state_data`, with the endpoint to send it to
+    ///
+    /// Creates the state when it is missing, migrates it when it follows an older schema and
+    /// resizes it when it changed size on the base layer, `payer` funds the rent each time.
+    /// All three record `schema` in the state header, a state following any other schema fails
+    /// with [`MojoSDKError::SchemaMismatch`].
+    #[allow(clippy::too_many_arguments)]
+    fn write_state_instruction(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Pubkey,
+        payer: &Pubkey,
+        schema: StateSchema,
+        state_data: Vec<u8>,
+        expected_version: Option<u64>,
+    ) -> Result<(Endpoint, Pubkey, Instruction), MojoSDKError> {
         let (account_pda, state_seed_input, _seed_hash) =
-            self.derive_state_pda(state_name, &owner_pubkey, client);
+            self.derive_state_pda(state_name, owner, client);
 
-        let account_status = Self::delegated_account_status(client, &account_pda)?;
+        // delegated states are written through the ephemeral rollup, the rest through the base layer
+        let endpoint = client.endpoint_for(&account_pda)?;
+        let rpc = client.rpc(endpoint);
+        let account_status = Self::delegated_account_status(client, rpc, &account_pda)?;
 
-        match account_status {
-            DelegatedAccountStatus::Exists => {
-                let update_ix = Self::build_update_state_instruction(
+        let instruction = match account_status {
+            // resizing does not check versions, a versioned write has to keep the size
+            DelegatedAccountStatus::Exists(state_len, _)
+                if state_len != state_data.len()
+                    && endpoint == Endpoint::Base
+                    && expected_version.is_some() =>
+            {
+                return Err(MojoSDKError::InvalidStateData(format!(
+                    "State is {} bytes but the account holds {}, resize it before a versioned write",
+                    state_data.len(),
+                    state_len
+                )));
+            }
+            // a state written under an older schema of its type is migrated on the base layer
+            DelegatedAccountStatus::Exists(_, stored)
+                if stored != schema
+                    && stored.migrates_to(&schema)
+                    && endpoint == Endpoint::Base
+                    && expected_version.is_none() =>
+            {
+                MigrateAccountBuilder::new(
                     client.program_id,
-                    owner_pubkey,
+                    *owner,
                     account_pda,
+                    self.world_pda,
                     &state_seed_input,
-                    &state_data,
-                )?;
-                Self::submit_instructions(client, owner, vec![update_ix])
+                    schema,
+                    state_data,
+                )
+                .payer(*payer)
+                .build()?
+            }
+            // any other schema is another type, or only migrates on the base layer unversioned
+            DelegatedAccountStatus::Exists(_, stored) if stored != schema => {
+                return Err(stored.mismatch(&schema));
+            }
+            // a state that changed size is resized and written in one go on the base layer
+            DelegatedAccountStatus::Exists(state_len, _)
+                if state_len != state_data.len() && endpoint == Endpoint::Base =>
+            {
+                ResizeAccountBuilder::new(
+                    client.program_id,
+                    *owner,
+                    account_pda,
+                    self.world_pda,
+                    &state_seed_input,
+                    state_data.len(),
+                )
+                .state_data(state_data)
+                .schema(schema)
+                .payer(*payer)
+                .build()?
             }
-            DelegatedAccountStatus::Missing => {
-                let create_ix = Self::build_create_state_instruction(
+            DelegatedAccountStatus::Exists(state_len, _) => {
+                // delegated accounts can not be resized, shorter (framed) states are padded
+                let state_data = Self::fit_to_account(state_data, state_len)?;
+                Self::build_update_state_instruction(
                     client.program_id,
-                    owner_pubkey,
+                    *owner,
                     account_pda,
+                    self.world_pda,
                     &state_seed_input,
+                    schema,
                     &state_data,
-                );
-                Self::submit_instructions(client, owner, vec![create_ix])
+                    expected_version,
+                )?
             }
+            DelegatedAccountStatus::Missing if expected_version.is_some() => {
+                return Err(MojoSDKError::AccountNotFound(account_pda.to_string()));
+            }
+            DelegatedAccountStatus::Missing => Self::build_create_state_instruction(
+                client.program_id,
+                *owner,
+                *payer,
+                account_pda,
+                self.world_pda,
+                &state_seed_input,
+                schema,
+                &state_data,
+            ),
+        };
+        Ok((endpoint, account_pda, instruction))
+    }
+
+    /// Turn a rejected versioned write into a `VersionConflict` carrying the current version
+    pub(crate) fn version_conflict(
+        rpc: &dyn Transport,
+        account: &Pubkey,
+        expected: u64,
+        err: MojoSDKError,
+    ) -> MojoSDKError {
+        if err.program_error() != Some(MojoProgramError::VersionConflict) {
+            return err;
         }
+        let found = rpc
+            .get_account(account)
+            .ok()
+            .flatten()
+            .and_then(|account| StateHeader::from_account_data(&account.data).ok())
+            .map(|header| header.version());
+        MojoSDKError::VersionConflict { expected, found }
+    }
+
+    /// Write only the `segments` of the state named `state_name` owned by `owner`
+    ///
+    /// Segment offsets are relative to the start of the state and must stay inside it, use the
+    /// `patch_<field>` constructors generated by `#[derive(MojoState)]` to rewrite single fields,
+    /// the program refuses them on a state of another schema with `InvalidSchema`.
+    /// Delegated states are patched through the ephemeral rollup.
+    pub fn patch_state(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+        segments: &[PatchSegment],
+    ) -> Result<(), MojoSDKError> {
+        let owner_pubkey = owner.pubkey();
+        let (account_pda, state_seed_input, _seed_hash) =
+            self.derive_state_pda(state_name, &owner_pubkey, client);
+
+        let patch_ix = PatchAccountBuilder::new(
+            client.program_id,
+            owner_pubkey,
+            account_pda,
+            self.world_pda,
+            &state_seed_input,
+            segments.to_vec(),
+        )
+        .build()?;
+        Self::submit_instructions(client, client.rpc_for(&account_pda)?, owner, vec![patch_ix])
+    }
+
+    /// Add `delta` to an integer field of the state named `state_name` owned by `owner`
+    ///
+    /// The addition happens on-chain against the current value and fails on overflow, e.g.
+    /// `world.increment(&client, "score", &player, Score::field_points(), 10)`
+    pub fn increment<T, F: IntegerField>(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+        field: StateField<T, F>,
+        delta: F,
+    ) -> Result<(), MojoSDKError> {
+        self.apply_field_ops(client, state_name, owner, &[field.add(delta)])
+    }
+
+    /// Subtract `delta` from an integer field, failing if the result does not fit
+    pub fn decrement<T, F: IntegerField>(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+        field: StateField<T, F>,
+        delta: F,
+    ) -> Result<(), MojoSDKError> {
+        self.apply_field_ops(client, state_name, owner, &[field.sub(delta)])
+    }
+
+    /// Apply `ops` to integer fields of a state in one instruction, all or none of them land
+    ///
+    /// Delegated states are updated through the ephemeral rollup.
+    pub fn apply_field_ops(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+        ops: &[FieldOp],
+    ) -> Result<(), MojoSDKError> {
+        let owner_pubkey = owner.pubkey();
+        let (account_pda, state_seed_input, _seed_hash) =
+            self.derive_state_pda(state_name, &owner_pubkey, client);
+
+        let arithmetic_ix = ArithmeticBuilder::new(
+            client.program_id,
+            owner_pubkey,
+            account_pda,
+            self.world_pda,
+            &state_seed_input,
+            ops.to_vec(),
+        )
+        .build()?;
+        Self::submit_instructions(
+            client,
+            client.rpc_for(&account_pda)?,
+            owner,
+            vec![arithmetic_ix],
+        )
+    }
+
+    /// Read a state along with its version, to be passed to [`World::write_state_if_version`]
+    pub fn read_versioned_state<T: MojoState>(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Pubkey,
+    ) -> Result<(T, u64), MojoSDKError> {
+        let (account_pda, _seed_input, _seed_hash) =
+            self.derive_state_pda(state_name, owner, client);
+        let account_data = Self::fetch_owned_account_data(client, &account_pda)?;
+        let (header, state_data) = StateHeader::split_account_data(&account_data)?;
+        Ok((header.decode_state(state_data)?, header.version()))
     }
 
     /// Read the current state stored in a delegated account
+    ///
+    /// States written under an older schema version of `T` are migrated on the fly, any other
+    /// type or layout fails with [`MojoSDKError::SchemaMismatch`]
     pub fn read_delegated_state<T: MojoState>(
         &self,
         client: &SdkClient,
@@ -129,44 +464,733 @@ impl World {
         let (account_pda, _seed_input, _seed_hash) =
             self.derive_state_pda(state_name, owner, client);
         let account_data = Self::fetch_owned_account_data(client, &account_pda)?;
-        T::deserialize(&account_data)
+        let (header, state_data) = StateHeader::split_account_data(&account_data)?;
+        header.decode_state(state_data)
+    }
+
+    /// Persist the state named `state_name` of `owner` under the schema of `T`
+    ///
+    /// The state is read as a `T`, upgrading it through [`crate::Migrate`] when it follows an
+    /// older schema version, and rewritten with the migrate instruction (sent to the base
+    /// layer). States already following the schema are left untouched. Returns the state.
+    pub fn migrate_state<T: MojoState>(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+    ) -> Result<T, MojoSDKError> {
+        let owner_pubkey = owner.pubkey();
+        let (account_pda, state_seed_input, _seed_hash) =
+            self.derive_state_pda(state_name, &owner_pubkey, client);
+        let account_data = Self::fetch_owned_account_data(client, &account_pda)?;
+        let (header, state_data) = StateHeader::split_account_data(&account_data)?;
+        let state: T = header.decode_state(state_data)?;
+
+        let schema = StateSchema::of::<T>();
+        if header.schema() != schema {
+            let migrate_ix = MigrateAccountBuilder::new(
+                client.program_id,
+                owner_pubkey,
+                account_pda,
+                self.world_pda,
+                &state_seed_input,
+                schema,
+                state.serialize()?,
+            )
+            .payer(client.payer_for(&owner_pubkey))
+            .build()?;
+            Self::submit_instructions(client, client.client(), owner, vec![migrate_ix])?;
+        }
+        Ok(state)
+    }
+
+    /// Subscribe to live changes of the state named `state_name` owned by `owner`
+    ///
+    /// Listens on both the base layer and the ephemeral rollup and reconnects on failure
+    pub fn subscribe_state<T: MojoState + Send + 'static>(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Pubkey,
+    ) -> Result<StateSubscription<T>, MojoSDKError> {
+        let account_pda = self.state_pda(state_name, owner, &client.program_id);
+        Ok(StateSubscription::open(
+            Arc::new(WebsocketPubsub::from_client(client)),
+            client.program_id,
+            account_pda,
+            SubscriptionConfig::default(),
+        ))
+    }
+
+    /// Call `callback` on a background thread with every change of a state
+    pub fn on_state_change<T, F>(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Pubkey,
+        callback: F,
+    ) -> Result<SubscriptionHandle, MojoSDKError>
+    where
+        T: MojoState + Send + 'static,
+        F: FnMut(Result<StateUpdate<T>, MojoSDKError>) + Send + 'static,
+    {
+        Ok(self
+            .subscribe_state(client, state_name, owner)?
+            .for_each(callback))
+    }
+
+    /// List every state account of this world, on the base layer and the ephemeral rollup
+    ///
+    /// Delegated states are only visible on the ephemeral rollup, every other state is
+    /// taken from the base layer
+    pub fn list_states(&self, client: &SdkClient) -> Result<Vec<StateEntry>, MojoSDKError> {
+        self.find_states(client, None)
+    }
+
+    /// List every state account of this world owned by `owner`
+    pub fn states_of_owner(
+        &self,
+        client: &SdkClient,
+        owner: &Pubkey,
+    ) -> Result<Vec<StateEntry>, MojoSDKError> {
+        self.find_states(client, Some(owner))
     }
 
-    /// Read the data stored in the world PDA itself
+    /// Read every state of this world as `T`, skipping states of another type or schema
+    pub fn list_states_as<T: MojoState>(
+        &self,
+        client: &SdkClient,
+    ) -> Result<Vec<(StateEntry, T)>, MojoSDKError> {
+        Ok(self
+            .list_states(client)?
+            .into_iter()
+            .filter_map(|entry| entry.state::<T>().ok().map(|state| (entry, state)))
+            .collect())
+    }
+
+    /// Read the data stored in the world PDA itself, after the world header
     pub fn read_world_state<T: MojoState>(&self, client: &SdkClient) -> Result<T, MojoSDKError> {
         let account_data = Self::fetch_owned_account_data(client, &self.world_pda)?;
-        T::deserialize(&account_data)
+        WorldHeader::from_account_data(&account_data)?;
+        T::deserialize(&account_data[WorldHeader::LEN..])
+    }
+
+    /// Read the on-chain metadata header of the world
+    pub fn read_header(&self, client: &SdkClient) -> Result<WorldHeader, MojoSDKError> {
+        let account_data = Self::fetch_owned_account_data(client, &self.world_pda)?;
+        WorldHeader::from_account_data(&account_data)
+    }
+
+    /// Delegate the world PDA to `validator` on the ephemeral rollup (sent to the base layer)
+    pub fn delegate(
+        &self,
+        client: &SdkClient,
+        creator: &Keypair,
+        validator: Pubkey,
+    ) -> Result<(), MojoSDKError> {
+        self.delegate_with(
+            client,
+            creator,
+            DelegationOptions::new().validator(validator),
+        )
+    }
+
+    /// Delegate the world PDA to the ephemeral rollup with `options` (sent to the base layer)
+    pub fn delegate_with(
+        &self,
+        client: &SdkClient,
+        creator: &Keypair,
+        options: DelegationOptions,
+    ) -> Result<(), MojoSDKError> {
+        let delegate_ix = DelegateAccountBuilder::new(
+            client.program_id,
+            creator.pubkey(),
+            self.world_pda,
+            self.world_pda,
+            self.world_seed_hash,
+            options,
+        )
+        .build()?;
+        Self::submit_instructions(client, client.client(), creator, vec![delegate_ix])
+    }
+
+    /// Commit the world PDA to the base layer (sent to the ephemeral rollup)
+    pub fn commit(&self, client: &SdkClient, creator: &Keypair) -> Result<(), MojoSDKError> {
+        let commit_ix = CommitAccountBuilder::new(
+            client.program_id,
+            creator.pubkey(),
+            self.world_pda,
+            self.world_pda,
+            self.world_seed_hash,
+        )
+        .build()?;
+        Self::submit_instructions(client, client.er_client(), creator, vec![commit_ix])
+    }
+
+    /// Commit the world PDA and hand it back to the base layer (sent to the ephemeral rollup)
+    pub fn undelegate(&self, client: &SdkClient, creator: &Keypair) -> Result<(), MojoSDKError> {
+        let undelegate_ix = CommitAccountBuilder::new(
+            client.program_id,
+            creator.pubkey(),
+            self.world_pda,
+            self.world_pda,
+            self.world_seed_hash,
+        )
+        .undelegate()
+        .build()?;
+        Self::submit_instructions(client, client.er_client(), creator, vec![undelegate_ix])
+    }
+
+    /// Delegate a state PDA owned by `owner` to `validator` (sent to the base layer)
+    pub fn delegate_state(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+        validator: Pubkey,
+    ) -> Result<(), MojoSDKError> {
+        self.delegate_state_with(
+            client,
+            state_name,
+            owner,
+            DelegationOptions::new().validator(validator),
+        )
+    }
+
+    /// Delegate a state PDA owned by `owner` with `options` (sent to the base layer)
+    pub fn delegate_state_with(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+        options: DelegationOptions,
+    ) -> Result<(), MojoSDKError> {
+        let owner_pubkey = owner.pubkey();
+        let delegate_ix = self.delegate_state_instruction(
+            client,
+            state_name,
+            &owner_pubkey,
+            &client.payer_for(&owner_pubkey),
+            options,
+        )?;
+        Self::submit_instructions(client, client.client(), owner, vec![delegate_ix])
+    }
+
+    /// Build a state delegation signed by `owner` only, for `sponsor` to co-sign and pay
+    pub fn sponsored_delegate_state(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+        sponsor: &Pubkey,
+        options: DelegationOptions,
+    ) -> Result<PartiallySignedTransaction, MojoSDKError> {
+        let delegate_ix =
+            self.delegate_state_instruction(client, state_name, &owner.pubkey(), sponsor, options)?;
+        PartiallySignedTransaction::new(client, Endpoint::Base, owner, sponsor, vec![delegate_ix])
+    }
+
+    fn delegate_state_instruction(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Pubkey,
+        payer: &Pubkey,
+        options: DelegationOptions,
+    ) -> Result<Instruction, MojoSDKError> {
+        let (account_pda, _seed_input, seed_hash) =
+            self.derive_state_pda(state_name, owner, client);
+        DelegateAccountBuilder::new(
+            client.program_id,
+            *owner,
+            account_pda,
+            self.world_pda,
+            seed_hash,
+            options,
+        )
+        .payer(*payer)
+        .build()
+    }
+
+    /// Commit a delegated state PDA to the base layer (sent to the ephemeral rollup)
+    pub fn commit_state(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+    ) -> Result<(), MojoSDKError> {
+        let owner_pubkey = owner.pubkey();
+        let (account_pda, _seed_input, seed_hash) =
+            self.derive_state_pda(state_name, &owner_pubkey, client);
+        let commit_ix = CommitAccountBuilder::new(
+            client.program_id,
+            owner_pubkey,
+            account_pda,
+            self.world_pda,
+            seed_hash,
+        )
+        .build()?;
+        Self::submit_instructions(client, client.er_client(), owner, vec![commit_ix])
+    }
+
+    /// Commit a delegated state PDA and hand it back to the base layer (sent to the ephemeral rollup)
+    pub fn undelegate_state(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+    ) -> Result<(), MojoSDKError> {
+        let owner_pubkey = owner.pubkey();
+        let (account_pda, _seed_input, seed_hash) =
+            self.derive_state_pda(state_name, &owner_pubkey, client);
+        let undelegate_ix = CommitAccountBuilder::new(
+            client.program_id,
+            owner_pubkey,
+            account_pda,
+            self.world_pda,
+            seed_hash,
+        )
+        .undelegate()
+        .build()?;
+        Self::submit_instructions(client, client.er_client(), owner, vec![undelegate_ix])
+    }
+
+    /// Delegate the world and the states named in `states`, given as (state name, owner), with
+    /// `options` (sent to the base layer)
+    ///
+    /// `creator` has to be the creator of the world, `owners` the players owning the other
+    /// states, who co-sign the batches holding them. The PDAs are split into as many
+    /// transactions as needed to fit the transaction size limit
+    pub fn delegate_all(
+        &self,
+        client: &SdkClient,
+        creator: &Keypair,
+        states: &[(&str, Pubkey)],
+        owners: &[&Keypair],
+        options: DelegationOptions,
+    ) -> Result<(), MojoSDKError> {
+        let accounts = self.batch_accounts(client, creator, states, owners)?;
+        let payer = client.payer_for(&creator.pubkey());
+        let instructions = Self::split_batch(&payer, &accounts, |batch| {
+            batch
+                .iter()
+                .fold(
+                    DelegateBatchBuilder::new(
+                        client.program_id,
+                        creator.pubkey(),
+                        self.world_pda,
+                        options,
+                    ),
+                    |builder, (account, seed_hash, co_signer)| match co_signer {
+                        Some(owner) => builder.add(*account, *seed_hash).co_signer(*owner),
+                        None => builder.add(*account, *seed_hash),
+                    },
+                )
+                .build()
+        })?;
+
+        for instruction in instructions {
+            Self::submit_co_signed(client, client.client(), creator, owners, vec![instruction])?;
+        }
+        Ok(())
+    }
+
+    /// Commit the world and the states named in `states` to the base layer, `owners` co-sign
+    /// for the states of other players (sent to the ephemeral rollup)
+    pub fn commit_all(
+        &self,
+        client: &SdkClient,
+        creator: &Keypair,
+        states: &[(&str, Pubkey)],
+        owners: &[&Keypair],
+    ) -> Result<(), MojoSDKError> {
+        self.commit_batch(client, creator, states, owners, false)
+    }
+
+    /// Commit the world and the states named in `states` and hand them back to the base layer,
+    /// `owners` co-sign for the states of other players (sent to the ephemeral rollup)
+    pub fn undelegate_all(
+        &self,
+        client: &SdkClient,
+        creator: &Keypair,
+        states: &[(&str, Pubkey)],
+        owners: &[&Keypair],
+    ) -> Result<(), MojoSDKError> {
+        self.commit_batch(client, creator, states, owners, true)
+    }
+
+    /// Close the world and every state in it, returning their rent to `creator` and the rent of
+    /// the states of other players to those players (sent to the base layer)
+    pub fn close(&self, client: &SdkClient, creator: &Keypair) -> Result<(), MojoSDKError> {
+        self.close_to(client, creator, &creator.pubkey())
+    }
+
+    /// Close the world and every state in it, returning their rent to `recipient`, the states
+    /// of other players refund those players
+    ///
+    /// The states are closed over as many transactions as needed, the last one closes the
+    /// world. Delegated states have to be undelegated first.
+    pub fn close_to(
+        &self,
+        client: &SdkClient,
+        creator: &Keypair,
+        recipient: &Pubkey,
+    ) -> Result<(), MojoSDKError> {
+        let states = self.list_states(client)?;
+        if let Some(delegated) = states
+            .iter()
+            .find(|entry| entry.endpoint == Endpoint::Ephemeral || entry.header.is_delegated())
+        {
+            return Err(MojoSDKError::InvalidStateData(format!(
+                "State {} is delegated, undelegate it before closing the world",
+                delegated.pubkey
+            )));
+        }
+
+        // a shared state goes along with its access list, the state of another player along
+        // with that player, who gets its rent back
+        let children: Vec<Vec<Pubkey>> = states
+            .iter()
+            .map(|entry| {
+                if entry.owner() == self.world_pda {
+                    vec![entry.pubkey, acl_pda(&entry.pubkey, &client.program_id)]
+                } else if entry.owner() == creator.pubkey() {
+                    vec![entry.pubkey]
+                } else {
+                    vec![entry.pubkey, entry.owner()]
+                }
+            })
+            .collect();
+        let build = |batch: &[Vec<Pubkey>]| {
+            CloseAccountBuilder::new(
+                client.program_id,
+                creator.pubkey(),
+                self.world_pda,
+                self.world_pda,
+                self.world_seed_hash,
+            )
+            .recipient(*recipient)
+            .children(batch.concat())
+            .build()
+        };
+        let instructions = if children.is_empty() {
+            vec![build(&[])?]
+        } else {
+            Self::split_batch(&client.payer_for(&creator.pubkey()), &children, build)?
+        };
+
+        for instruction in instructions {
+            Self::submit_instructions(client, client.client(), creator, vec![instruction])?;
+        }
+
+        // the program keeps the world open while it still counts states
+        if client.client().get_account(&self.world_pda)?.is_some() {
+            return Err(MojoSDKError::InvalidStateData(format!(
+                "World {} still holds states that could not be found",
+                self.world_pda
+            )));
+        }
+        Ok(())
+    }
+
+    /// Resize the state named `state_name` of `owner` to hold `size` bytes (sent to the base layer)
+    ///
+    /// Added bytes are zeroed, the rent difference is paid by or refunded to the owner, or to
+    /// the sponsor of the client when it has one.
+    /// [`World::write_state`] resizes on its own when the state changes size.
+    pub fn resize_state(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+        size: usize,
+    ) -> Result<(), MojoSDKError> {
+        let owner_pubkey = owner.pubkey();
+        let (account_pda, state_seed_input, _seed_hash) =
+            self.derive_state_pda(state_name, &owner_pubkey, client);
+        let resize_ix = ResizeAccountBuilder::new(
+            client.program_id,
+            owner_pubkey,
+            account_pda,
+            self.world_pda,
+            &state_seed_input,
+            size,
+        )
+        .payer(client.payer_for(&owner_pubkey))
+        .build()?;
+        Self::submit_instructions(client, client.client(), owner, vec![resize_ix])
+    }
+
+    /// Close a state owned by `owner`, returning its rent to them (sent to the base layer)
+    pub fn close_state(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Keypair,
+    ) -> Result<(), MojoSDKError> {
+        self.close_state_to(client, state_name, &owner.pubkey(), owner, &owner.pubkey())
+    }
+
+    /// Close the state named `state_name` of `owner`, returning its rent to `recipient`
+    ///
+    /// `signer` is the owner of the state or the creator of the world, who can only send the
+    /// rent of a player's state back to that player
+    pub fn close_state_to(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Pubkey,
+        signer: &Keypair,
+        recipient: &Pubkey,
+    ) -> Result<(), MojoSDKError> {
+        let (account_pda, _seed_input, seed_hash) =
+            self.derive_state_pda(state_name, owner, client);
+        let close_ix = CloseAccountBuilder::new(
+            client.program_id,
+            signer.pubkey(),
+            account_pda,
+            self.world_pda,
+            seed_hash,
+        )
+        .recipient(*recipient)
+        .build()?;
+        Self::submit_instructions(client, client.client(), signer, vec![close_ix])
+    }
+
+    /// Hand a state whose delegation lifetime ran out back to the base layer on behalf of
+    /// `owner`, `payer` signs and pays for it (sent to the ephemeral rollup)
+    pub fn undelegate_expired_state(
+        &self,
+        client: &SdkClient,
+        state_name: &str,
+        owner: &Pubkey,
+        payer: &Keypair,
+    ) -> Result<(), MojoSDKError> {
+        let (account_pda, _seed_input, seed_hash) =
+            self.derive_state_pda(state_name, owner, client);
+        let undelegate_ix = CommitAccountBuilder::new(
+            client.program_id,
+            payer.pubkey(),
+            account_pda,
+            self.world_pda,
+            seed_hash,
+        )
+        .undelegate()
+        .build()?;
+        Self::submit_instructions(client, client.er_client(), payer, vec![undelegate_ix])
+    }
+
+    fn commit_batch(
+        &self,
+        client: &SdkClient,
+        creator: &Keypair,
+        states: &[(&str, Pubkey)],
+        owners: &[&Keypair],
+        undelegate: bool,
+    ) -> Result<(), MojoSDKError> {
+        let accounts = self.batch_accounts(client, creator, states, owners)?;
+        let payer = client.payer_for(&creator.pubkey());
+        let instructions = Self::split_batch(&payer, &accounts, |batch| {
+            let builder = batch.iter().fold(
+                CommitBatchBuilder::new(client.program_id, creator.pubkey(), self.world_pda),
+                |builder, (account, seed_hash, co_signer)| match co_signer {
+                    Some(owner) => builder.add(*account, *seed_hash).co_signer(*owner),
+                    None => builder.add(*account, *seed_hash),
+                },
+            );
+            if undelegate {
+                builder.undelegate().build()
+            } else {
+                builder.build()
+            }
+        })?;
+
+        for instruction in instructions {
+            Self::submit_co_signed(
+                client,
+                client.er_client(),
+                creator,
+                owners,
+                vec![instruction],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The world PDA followed by the PDAs of `states`, each with its seed hash and the owner
+    /// that has to co-sign for it
+    ///
+    /// The creator signs for the world, its own states and the shared states, every other
+    /// owner has to be one of `owners`
+    fn batch_accounts(
+        &self,
+        client: &SdkClient,
+        creator: &Keypair,
+        states: &[(&str, Pubkey)],
+        owners: &[&Keypair],
+    ) -> Result<Vec<(Pubkey, [u8; 32], Option<Pubkey>)>, MojoSDKError> {
+        let mut accounts = vec![(self.world_pda, self.world_seed_hash, None)];
+        for (state_name, owner) in states {
+            let (account_pda, _seed_input, seed_hash) =
+                self.derive_state_pda(state_name, owner, client);
+            let co_signer = if *owner == creator.pubkey() || *owner == self.world_pda {
+                None
+            } else if owners.iter().any(|keypair| keypair.pubkey() == *owner) {
+                Some(*owner)
+            } else {
+                return Err(MojoSDKError::InvalidStateData(format!(
+                    "State {} belongs to {}, who has to co-sign the batch",
+                    account_pda, owner
+                )));
+            };
+            accounts.push((account_pda, seed_hash, co_signer));
+        }
+        Ok(accounts)
+    }
+
+    /// Split `accounts` into as few instructions built by `build` as fit in a transaction each
+    ///
+    /// `payer` is the fee payer the transactions are sent with, a sponsor takes a key and a
+    /// signature of its own
+    pub(crate) fn split_batch<A>(
+        payer: &Pubkey,
+        accounts: &[A],
+        build: impl Fn(&[A]) -> Result<Instruction, MojoSDKError>,
+    ) -> Result<Vec<Instruction>, MojoSDKError> {
+        let mut instructions = Vec::new();
+        let mut start = 0;
+
+        while start < accounts.len() {
+            let mut end = start + 1;
+            let mut instruction = build(&accounts[start..end])?;
+            if Self::transaction_size(payer, &instruction) > MAX_TRANSACTION_SIZE {
+                return Err(MojoSDKError::InvalidStateData(
+                    "A single account does not fit in a transaction".to_string(),
+                ));
+            }
+
+            // a batch counts its accounts in a single byte
+            while end < accounts.len() && end - start < u8::MAX as usize {
+                let candidate = build(&accounts[start..end + 1])?;
+                if Self::transaction_size(payer, &candidate) > MAX_TRANSACTION_SIZE {
+                    break;
+                }
+                instruction = candidate;
+                end += 1;
+            }
+
+            instructions.push(instruction);
+            start = end;
+        }
+
+        Ok(instructions)
+    }
+
+    /// Serialized size of a transaction holding only `instruction`, paid by `payer`
+    fn transaction_size(payer: &Pubkey, instruction: &Instruction) -> usize {
+        // a payer the instruction does not list is added as one more signing key
+        let message = Message::new(std::slice::from_ref(instruction), Some(payer));
+        let signatures = message.header.num_required_signatures as usize;
+        // the signature count is a compact u16, a single byte below 128 signatures
+        1 + signatures * 64 + message.serialize().len()
+    }
+
+    fn find_states(
+        &self,
+        client: &SdkClient,
+        owner: Option<&Pubkey>,
+    ) -> Result<Vec<StateEntry>, MojoSDKError> {
+        let filters = Self::state_filters(&self.world_pda, owner);
+
+        let mut entries = Self::fetch_states(client, Endpoint::Base, filters.clone())?;
+        for entry in Self::fetch_states(client, Endpoint::Ephemeral, filters)? {
+            // accounts cloned into the rollup are stale copies unless they are delegated
+            if !entries.iter().any(|known| known.pubkey == entry.pubkey) {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// memcmp filters matching the state header of states in `world`, optionally of `owner`
+    fn state_filters(world: &Pubkey, owner: Option<&Pubkey>) -> Vec<RpcFilterType> {
+        let mut filters = vec![
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                StateHeader::MAGIC_OFFSET,
+                &STATE_MAGIC,
+            )),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                StateHeader::WORLD_OFFSET,
+                world.as_ref(),
+            )),
+        ];
+        if let Some(owner) = owner {
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                StateHeader::OWNER_OFFSET,
+                owner.as_ref(),
+            )));
+        }
+        filters
+    }
+
+    fn fetch_states(
+        client: &SdkClient,
+        endpoint: Endpoint,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<StateEntry>, MojoSDKError> {
+        let accounts = client
+            .rpc(endpoint)
+            .get_program_accounts(&client.program_id, filters)?;
+
+        accounts
+            .into_iter()
+            .map(|(pubkey, account)| StateEntry::new(pubkey, endpoint, &account.data))
+            .collect()
     }
 
     fn world_seed_input(world_name: &str, creator: &Pubkey) -> Vec<u8> {
         crate::encode_packed!(b"world", world_name.as_bytes(), creator.as_ref())
     }
 
-    fn derive_state_pda(
+    /// Derive the PDA of the state named `state_name` owned by `owner` in this world
+    pub fn state_pda(&self, state_name: &str, owner: &Pubkey, program_id: &Pubkey) -> Pubkey {
+        let seed_hash = utils::compute_hash(&Self::state_seed_input(state_name, owner));
+        derive_pda(
+            &[&seed_hash, self.world_pda.as_ref(), owner.as_ref()],
+            program_id,
+        )
+        .0
+    }
+
+    /// State PDAs are bound to their world on-chain: [seed hash, world pda, owner]
+    pub(crate) fn derive_state_pda(
         &self,
         state_name: &str,
         owner: &Pubkey,
         client: &SdkClient,
     ) -> (Pubkey, Vec<u8>, [u8; 32]) {
-        let seed_input = crate::encode_packed!(
-            b"state",
-            self.world_seed_hash.as_ref(),
-            state_name.as_bytes(),
-            owner.as_ref()
-        );
+        let seed_input = Self::state_seed_input(state_name, owner);
         let seed_hash = utils::compute_hash(&seed_input);
-        let (pda, _bump) = derive_pda(&[&seed_hash, owner.as_ref()], &client.program_id);
+        let (pda, _bump) = derive_pda(
+            &[&seed_hash, self.world_pda.as_ref(), owner.as_ref()],
+            &client.program_id,
+        );
         (pda, seed_input, seed_hash)
     }
 
-    fn fetch_owned_account_data(
+    fn state_seed_input(state_name: &str, owner: &Pubkey) -> Vec<u8> {
+        crate::encode_packed!(b"state", state_name.as_bytes(), owner.as_ref())
+    }
+
+    pub(crate) fn fetch_owned_account_data(
         client: &SdkClient,
         account: &Pubkey,
     ) -> Result<Vec<u8>, MojoSDKError> {
         let acc = client
-            .client
-            .get_account(account)
-            .map_err(|e| MojoSDKError::AccountNotFound(format!("{}: {}", account, e)))?;
+            .rpc_for(account)?
+            .get_account(account)?
+            .ok_or_else(|| MojoSDKError::AccountNotFound(account.to_string()))?;
 
         if acc.owner != client.program_id {
             return Err(MojoSDKError::InvalidAccountOwner(format!(
@@ -180,101 +1204,195 @@ impl World {
 
     fn delegated_account_status(
         client: &SdkClient,
+        rpc: &dyn Transport,
         account: &Pubkey,
     ) -> Result<DelegatedAccountStatus, MojoSDKError> {
-        match client.client.get_account(account) {
-            Ok(acc) => {
+        match rpc.get_account(account)? {
+            Some(acc) => {
                 if acc.owner != client.program_id {
                     return Err(MojoSDKError::InvalidAccountOwner(format!(
                         "expected {}, got {}",
                         client.program_id, acc.owner
                     )));
                 }
-                Ok(DelegatedAccountStatus::Exists)
-            }
-            Err(err) => {
-                if Self::is_account_missing(&err) {
-                    Ok(DelegatedAccountStatus::Missing)
-                } else {
-                    Err(MojoSDKError::SolanaSdk(err.to_string()))
-                }
+                let header = StateHeader::from_account_data(&acc.data)?;
+                Ok(DelegatedAccountStatus::Exists(
+                    acc.data.len() - StateHeader::LEN,
+                    header.schema(),
+                ))
             }
+            None => Ok(DelegatedAccountStatus::Missing),
         }
     }
 
-    fn is_account_missing(err: &ClientError) -> bool {
-        match err.kind() {
-            ClientErrorKind::RpcError(RpcError::ForUser(message)) => {
-                message.contains("AccountNotFound") || message.contains("could not find account")
-            }
-            _ => false,
+    /// Delegated accounts keep their size, so shorter (framed) states are zero padded
+    pub(crate) fn fit_to_account(
+        mut state_data: Vec<u8>,
+        account_len: usize,
+    ) -> Result<Vec<u8>, MojoSDKError> {
+        if state_data.len() > account_len {
+            return Err(MojoSDKError::InvalidStateData(format!(
+                "State needs {} bytes but the account only holds {}",
+                state_data.len(),
+                account_len
+            )));
         }
+        state_data.resize(account_len, 0);
+        Ok(state_data)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build_create_state_instruction(
         program_id: Pubkey,
         owner: Pubkey,
+        payer: Pubkey,
         account_pda: Pubkey,
+        world_pda: Pubkey,
         seed_input: &Vec<u8>,
+        schema: StateSchema,
         state_data: &[u8],
     ) -> Instruction {
         let mojo_data = GenIxHandler::new(seed_input, state_data.len());
 
+        let mut accounts = vec![
+            AccountMeta::new(owner, true),
+            AccountMeta::new(account_pda, false),
+            AccountMeta::new(world_pda, false), // world state count is bumped
+            AccountMeta::new(system_program_id(), false),
+            AccountMeta::new(rent_id, false),
+        ];
+        if payer != owner {
+            accounts.push(AccountMeta::new(payer, true)); // sponsor funding the rent
+        }
+
         Instruction {
             program_id,
-            accounts: vec![
-                AccountMeta::new(owner, true),
-                AccountMeta::new(account_pda, false),
-                AccountMeta::new(system_program_id(), false),
-                AccountMeta::new(rent_id, false),
-            ],
+            accounts,
             data: [
                 vec![MojoInstructionDiscriminator::CreateAccount as u8],
                 bytemuck::bytes_of(&mojo_data).to_vec(),
+                StateSchema::instruction_data(Some(&schema)),
                 state_data.to_vec(),
             ]
             .concat(),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build_update_state_instruction(
         program_id: Pubkey,
         owner: Pubkey,
         account_pda: Pubkey,
+        world_pda: Pubkey,
         seed_input: &Vec<u8>,
+        schema: StateSchema,
         state_data: &[u8],
+        expected_version: Option<u64>,
     ) -> Result<Instruction, MojoSDKError> {
-        UpdateDelegatedAccountBuilder::new(
+        let builder = UpdateDelegatedAccountBuilder::new(
             program_id,
             owner,
             account_pda,
+            world_pda,
             seed_input,
             state_data.to_vec(),
         )
+        .schema(schema);
+        match expected_version {
+            Some(version) => builder.expected_version(version),
+            None => builder,
+        }
         .build()
     }
 
-    fn submit_instructions(
+    /// Sign and send `instructions`, the sponsor of the client pays the fees when it has one
+    pub(crate) fn submit_instructions(
         client: &SdkClient,
+        rpc: &dyn Transport,
         signer: &Keypair,
         instructions: Vec<Instruction>,
     ) -> Result<(), MojoSDKError> {
-        let message = Message::new(&instructions, Some(&signer.pubkey()));
-        let recent_blockhash = client
-            .client
-            .get_latest_blockhash()
-            .map_err(|_e| MojoSDKError::SolanaClient())?;
-        let transaction = Transaction::new(&[signer], message, recent_blockhash);
-
-        client
-            .client
-            .send_and_confirm_transaction(&transaction)
-            .map_err(|e| MojoSDKError::TransactionFailed(e.to_string()))?;
+        Self::submit_co_signed(client, rpc, signer, &[], instructions)
+    }
+
+    /// Sign and send `instructions` like [`World::submit_instructions`], along with those of
+    /// `co_signers` the instructions ask a signature of
+    pub(crate) fn submit_co_signed(
+        client: &SdkClient,
+        rpc: &dyn Transport,
+        signer: &Keypair,
+        co_signers: &[&Keypair],
+        instructions: Vec<Instruction>,
+    ) -> Result<(), MojoSDKError> {
+        let mut signers = vec![signer];
+        let payer = match client.sponsor() {
+            Some(sponsor) if sponsor.pubkey() != signer.pubkey() => {
+                signers.push(sponsor);
+                sponsor.pubkey()
+            }
+            _ => signer.pubkey(),
+        };
+        for co_signer in co_signers {
+            let required = instructions.iter().any(|instruction| {
+                instruction
+                    .accounts
+                    .iter()
+                    .any(|meta| meta.is_signer && meta.pubkey == co_signer.pubkey())
+            });
+            if required
+                && signers
+                    .iter()
+                    .all(|known| known.pubkey() != co_signer.pubkey())
+            {
+                signers.push(co_signer);
+            }
+        }
+
+        let recent_blockhash = rpc.latest_blockhash()?;
+        let message = Message::new(&instructions, Some(&payer));
+        let transaction = Transaction::new(&signers, message, recent_blockhash);
+
+        rpc.send_transaction(&transaction)?;
         Ok(())
     }
 }
 
+/// A state account found by [`World::list_states`]
+#[derive(Debug, Clone)]
+pub struct StateEntry {
+    /// The PDA of the state
+    pub pubkey: Pubkey,
+    /// The endpoint the state was read from
+    pub endpoint: Endpoint,
+    pub header: StateHeader,
+    /// Raw state bytes, after the header
+    pub data: Vec<u8>,
+}
+
+impl StateEntry {
+    fn new(pubkey: Pubkey, endpoint: Endpoint, account_data: &[u8]) -> Result<Self, MojoSDKError> {
+        let (header, data) = StateHeader::split_account_data(account_data)?;
+        Ok(Self {
+            pubkey,
+            endpoint,
+            header,
+            data: data.to_vec(),
+        })
+    }
+
+    /// Get the owner of the state
+    pub fn owner(&self) -> Pubkey {
+        self.header.owner()
+    }
+
+    /// Decode the state as `T`, see [`StateHeader::decode_state`]
+    pub fn state<T: MojoState>(&self) -> Result<T, MojoSDKError> {
+        self.header.decode_state(&self.data)
+    }
+}
+
 enum DelegatedAccountStatus {
-    Exists,
+    /// The account exists, holding this many state bytes after its header, following the schema
+    Exists(usize, StateSchema),
     Missing,
 }
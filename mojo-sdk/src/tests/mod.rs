@@ -1,3 +1,16 @@
+mod test_arithmetic;
+mod test_close_account;
+mod test_codec;
 mod test_create_world;
+mod test_delegation;
+mod test_errors;
+mod test_list_states;
+mod test_migrate;
+mod test_mojo_state_derive;
+mod test_pda_bump;
+mod test_session;
+mod test_shared_state;
+mod test_sponsor;
+mod test_subscription;
 mod test_write_state;
 mod utils;
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use crate::{client::SdkClient, mojo_state, MojoProgramError};
+    use bytemuck::Zeroable;
+
+    use solana_keypair::Keypair;
+    use solana_signer::Signer;
+
+    #[mojo_state(seed = "score")]
+    #[derive(Debug, PartialEq)]
+    pub struct Score {
+        points: u64,
+        health: i32,
+        level: u16,
+        combo: u8,
+        mood: i8,
+    }
+
+    fn setup() -> (SdkClient, Keypair) {
+        SdkClient::litesvm().expect("Could not load mojo_program.so, run cargo build-sbf first")
+    }
+
+    #[test]
+    fn test_field_ops_encoding() {
+        assert_eq!(Score::field_points().offset(), 0);
+        assert_eq!(Score::field_health().offset(), 8);
+        assert_eq!(Score::field_mood().offset(), 15);
+
+        let op = Score::field_health().sub(3);
+        assert_eq!(op.type_tag, 6);
+        assert_eq!(op.offset, 8);
+        assert_eq!(op.operand, 3i32.to_le_bytes().to_vec());
+    }
+
+    #[test]
+    fn test_increment() {
+        let (client, creator) = setup();
+        let creator_pubkey = creator.pubkey();
+
+        let world = client
+            .create_world(&creator, "arithmetic world", Score::zeroed())
+            .unwrap();
+        Score {
+            points: 10,
+            health: 5,
+            level: 1,
+            combo: 250,
+            mood: -100,
+        }
+        .write(&world, &client, &creator)
+        .unwrap();
+
+        world
+            .increment(&client, Score::SEED, &creator, Score::field_points(), 32)
+            .unwrap();
+        client
+            .increment(&world, Score::SEED, &creator, Score::field_level(), 1)
+            .unwrap();
+        world
+            .decrement(&client, Score::SEED, &creator, Score::field_health(), 8)
+            .unwrap();
+        client
+            .apply_field_ops(
+                &world,
+                Score::SEED,
+                &creator,
+                &[
+                    Score::field_combo().min(7),
+                    Score::field_mood().max(-1),
+                    Score::field_points().set(40),
+                    Score::field_points().add(2),
+                ],
+            )
+            .unwrap();
+
+        let score = Score::read(&world, &client, &creator_pubkey).unwrap();
+        assert_eq!(
+            score,
+            Score {
+                points: 42,
+                health: -3,
+                level: 2,
+                combo: 7,
+                mood: -1,
+            }
+        );
+
+        // overflows fail the whole instruction and leave the state untouched
+        let err = client
+            .apply_field_ops(
+                &world,
+                Score::SEED,
+                &creator,
+                &[
+                    Score::field_points().add(1),
+                    Score::field_combo().add(u8::MAX),
+                ],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.program_error(),
+            Some(MojoProgramError::ArithmeticOverflow)
+        );
+        let err = world
+            .decrement(&client, Score::SEED, &creator, Score::field_points(), 43)
+            .unwrap_err();
+        assert_eq!(
+            err.program_error(),
+            Some(MojoProgramError::ArithmeticOverflow)
+        );
+        assert_eq!(
+            Score::read(&world, &client, &creator_pubkey).unwrap(),
+            score
+        );
+    }
+}
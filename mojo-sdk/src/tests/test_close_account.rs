@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::SdkClient, impl_mojo_state_pod, CloseAccountBuilder, LiteSvmTransport,
+        MojoProgramError, World,
+    };
+    use bytemuck::{self, Pod, Zeroable};
+    use std::sync::Arc;
+
+    use solana_keypair::Keypair;
+    use solana_pubkey::Pubkey;
+    use solana_signer::Signer;
+
+    const PROGRAM_ID: Pubkey = Pubkey::new_from_array(mojo_program::ID);
+
+    #[repr(C)]
+    #[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+    pub struct Position {
+        x: u64,
+        y: u64,
+    }
+    impl_mojo_state_pod!(Position);
+
+    fn setup() -> (SdkClient, Keypair) {
+        SdkClient::litesvm().expect("Could not load mojo_program.so, run cargo build-sbf first")
+    }
+
+    fn lamports(client: &SdkClient, account: &Pubkey) -> u64 {
+        client
+            .client()
+            .get_account(account)
+            .unwrap()
+            .map(|account| account.lamports)
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_close_state() {
+        let (client, creator) = setup();
+        let creator_pubkey = creator.pubkey();
+
+        let world = client
+            .create_world(&creator, "close state world", Position { x: 0, y: 0 })
+            .unwrap();
+        client
+            .write_state(&world, "player_one", &creator, Position { x: 1, y: 1 })
+            .unwrap();
+        client
+            .write_state(&world, "player_two", &creator, Position { x: 2, y: 2 })
+            .unwrap();
+
+        let state_pda = world.state_pda("player_one", &creator_pubkey, &client.program_id);
+        let rent = lamports(&client, &state_pda);
+        assert!(rent > 0);
+
+        let recipient = Pubkey::new_unique();
+        world
+            .close_state_to(&client, "player_one", &creator_pubkey, &creator, &recipient)
+            .unwrap();
+
+        assert!(client.client().get_account(&state_pda).unwrap().is_none());
+        assert_eq!(lamports(&client, &recipient), rent);
+        assert_eq!(client.read_world_header(&world).unwrap().state_count(), 1);
+
+        // a closed state can not be closed twice
+        let err = client
+            .close_state(&world, "player_one", &creator)
+            .unwrap_err();
+        assert_eq!(
+            err.program_error(),
+            Some(MojoProgramError::UninitializedAccount)
+        );
+    }
+
+    #[test]
+    fn test_close_world_with_states() {
+        let (client, creator) = setup();
+        let creator_pubkey = creator.pubkey();
+
+        let world = client
+            .create_world(&creator, "close world", Position { x: 0, y: 0 })
+            .unwrap();
+        let names = ["player_one", "player_two", "player_three"];
+        for (i, name) in names.iter().enumerate() {
+            client
+                .write_state(
+                    &world,
+                    name,
+                    &creator,
+                    Position {
+                        x: i as u64,
+                        y: i as u64,
+                    },
+                )
+                .unwrap();
+        }
+
+        let balance_before = lamports(&client, &creator_pubkey);
+        client.close_world(&world, &creator).unwrap();
+
+        assert!(client
+            .client()
+            .get_account(&world.world_pda)
+            .unwrap()
+            .is_none());
+        for name in names {
+            let state_pda = world.state_pda(name, &creator_pubkey, &client.program_id);
+            assert!(client.client().get_account(&state_pda).unwrap().is_none());
+        }
+        // the rent of four accounts outweighs the fee of the close transaction
+        assert!(lamports(&client, &creator_pubkey) > balance_before);
+    }
+
+    #[test]
+    fn test_close_world_counts_each_state_once() {
+        let (client, creator) = setup();
+        let creator_pubkey = creator.pubkey();
+
+        let world = client
+            .create_world(&creator, "double close world", Position { x: 0, y: 0 })
+            .unwrap();
+        for name in ["player_one", "player_two"] {
+            client
+                .write_state(&world, name, &creator, Position { x: 1, y: 1 })
+                .unwrap();
+        }
+
+        // passing the same state twice would close the world with a state left in it
+        let state_pda = world.state_pda("player_one", &creator_pubkey, &client.program_id);
+        let close_ix = CloseAccountBuilder::new(
+            client.program_id,
+            creator_pubkey,
+            world.world_pda,
+            world.world_pda,
+            world.world_seed_hash,
+        )
+        .children(vec![state_pda, state_pda])
+        .build()
+        .unwrap();
+        World::submit_instructions(&client, client.client(), &creator, vec![close_ix]).unwrap_err();
+
+        assert!(client.client().get_account(&state_pda).unwrap().is_some());
+        assert_eq!(client.read_world_header(&world).unwrap().state_count(), 2);
+    }
+
+    #[test]
+    fn test_creator_refunds_players() {
+        let svm = Arc::new(
+            LiteSvmTransport::with_mojo_program(PROGRAM_ID)
+                .expect("Could not load mojo_program.so, run cargo build-sbf first"),
+        );
+        let creator = Keypair::new();
+        let player = Keypair::new();
+        for keypair in [&creator, &player] {
+            svm.airdrop(&keypair.pubkey(), 10_000_000_000).unwrap();
+        }
+        let client =
+            SdkClient::with_transports(Box::new(svm.clone()), Box::new(svm.clone()), PROGRAM_ID);
+
+        let world = client
+            .create_world(&creator, "refund world", Position { x: 0, y: 0 })
+            .unwrap();
+        client
+            .write_state(&world, "player_one", &player, Position { x: 1, y: 1 })
+            .unwrap();
+        let state_pda = world.state_pda("player_one", &player.pubkey(), &PROGRAM_ID);
+        let rent = lamports(&client, &state_pda);
+
+        // the creator can not take the rent of a player's state
+        let err = world
+            .close_state_to(
+                &client,
+                "player_one",
+                &player.pubkey(),
+                &creator,
+                &creator.pubkey(),
+            )
+            .unwrap_err();
+        assert_eq!(err.program_error(), Some(MojoProgramError::Unauthorized));
+
+        // closing the world hands it back to the player
+        let balance_before = lamports(&client, &player.pubkey());
+        client.close_world(&world, &creator).unwrap();
+        assert!(client.client().get_account(&state_pda).unwrap().is_none());
+        assert_eq!(lamports(&client, &player.pubkey()), balance_before + rent);
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use crate::{frame, unframe, FRAME_PREFIX_LEN};
+
+    #[test]
+    pub fn test_frame_ignores_trailing_bytes() {
+        let mut framed = frame(vec![1, 2, 3]).unwrap();
+        assert_eq!(framed.len(), FRAME_PREFIX_LEN + 3);
+
+        // accounts are zero padded up to the size they were created with
+        framed.resize(32, 0);
+        assert_eq!(unframe(&framed).unwrap(), &[1, 2, 3]);
+
+        assert!(unframe(&framed[..2]).is_err());
+        assert!(unframe(&framed[..FRAME_PREFIX_LEN + 2]).is_err());
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    pub fn test_borsh_state_roundtrip() {
+        use crate::MojoState;
+        use borsh::{BorshDeserialize, BorshSerialize};
+
+        #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+        pub struct Inventory {
+            items: Vec<u16>,
+            nickname: String,
+            equipped: Option<u16>,
+        }
+        crate::impl_mojo_state_borsh!(Inventory);
+
+        // borsh has its own `serialize`, so the MojoState methods are called explicitly
+
+        let inventory = Inventory {
+            items: vec![3, 7, 42],
+            nickname: "mojo".to_string(),
+            equipped: Some(7),
+        };
+        let mut bytes = MojoState::serialize(&inventory).unwrap();
+        bytes.resize(bytes.len() + 16, 0);
+        assert_eq!(
+            <Inventory as MojoState>::deserialize(&bytes).unwrap(),
+            inventory
+        );
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    pub fn test_postcard_state_roundtrip() {
+        use crate::MojoState;
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        pub struct ChatLog {
+            messages: Vec<String>,
+            pinned: Option<u32>,
+        }
+        crate::impl_mojo_state_postcard!(ChatLog);
+
+        // serde has its own `serialize`, so the MojoState methods are called explicitly
+
+        let chat_log = ChatLog {
+            messages: vec!["gm".to_string(), "gg".to_string()],
+            pinned: None,
+        };
+        let mut bytes = MojoState::serialize(&chat_log).unwrap();
+        bytes.resize(bytes.len() + 16, 0);
+        assert_eq!(
+            <ChatLog as MojoState>::deserialize(&bytes).unwrap(),
+            chat_log
+        );
+    }
+}
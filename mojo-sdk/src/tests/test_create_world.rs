@@ -1,14 +1,11 @@
 use super::utils::helpers as utils;
-use crate::{encode_packed, GenIxHandler, MojoInstructionDiscriminator};
+use crate::{encode_packed, GenIxHandler, MojoInstructionDiscriminator, StateSchema};
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use crate::{
-        client::{RpcType, SdkClient},
-        impl_mojo_state_pod, MojoState,
-    };
+    use crate::{client::SdkClient, impl_mojo_state_pod, MojoState};
     use bytemuck::{self, Pod, Zeroable};
     use solana_program::msg;
     use std::{
@@ -21,7 +18,7 @@ mod tests {
     use solana_keypair::Keypair;
     use solana_message::Message;
     use solana_pubkey::Pubkey;
-    use solana_signer::{EncodableKey, Signer};
+    use solana_signer::Signer;
     use solana_system_program;
     use solana_sysvar::rent::ID as RENT_ID;
     use solana_transaction::Transaction;
@@ -44,17 +41,10 @@ mod tests {
     impl_mojo_state_pod!(Position);
 
     fn setup() -> (SdkClient, Keypair) {
-        let client = SdkClient::new(RpcType::Dev);
-        let creator =
-            Keypair::read_from_file("../dev_wallet.json").expect("Could not read keypair file");
-        return (client, creator);
+        SdkClient::litesvm().expect("Could not load mojo_program.so, run cargo build-sbf first")
     }
 
-    /**
-     * Tests assume use of a funded key
-     */
     #[test]
-    #[ignore]
     pub fn test_create_world() -> Result<(), Error> {
         let (client, creator) = setup();
         let creator_pubkey = creator.pubkey();
@@ -86,18 +76,29 @@ mod tests {
             .unwrap();
         assert_eq!(fetched_world_state, starting_position);
 
+        let world_header = client
+            .read_world_header(&world)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        assert_eq!(world_header.creator(), creator_pubkey);
+        assert_eq!(world_header.name_hash, seed_bytes);
+        assert_eq!(world_header.state_count(), 0);
+        assert!(!world_header.is_delegated());
+        assert_eq!(world_header.delegated_until(), None);
+        assert!(world_header.created_at() > 0);
+
         // create a delegated state account manually via CreateAccount instruction
         let state_name = "player_position";
         let player_state = Position { x: 9, y: 9 };
-        let state_seed_input = crate::encode_packed!(
-            b"state",
-            world.world_seed_hash.as_ref(),
-            state_name.as_bytes(),
-            creator_pubkey.as_ref()
-        );
+        let state_seed_input =
+            crate::encode_packed!(b"state", state_name.as_bytes(), creator_pubkey.as_ref());
         let state_seed_hash = utils::compute_hash(&state_seed_input);
         let (state_pda, _bump) = derive_pda(
-            &[&state_seed_hash, creator_pubkey.as_ref()],
+            &[
+                &state_seed_hash,
+                world.world_pda.as_ref(),
+                creator_pubkey.as_ref(),
+            ],
             &client.program_id,
         );
 
@@ -108,23 +109,40 @@ mod tests {
             accounts: vec![
                 AccountMeta::new(creator_pubkey, true),
                 AccountMeta::new(state_pda, false),
+                AccountMeta::new(world.world_pda, false),
                 AccountMeta::new(solana_system_program::id(), false),
                 AccountMeta::new(Pubkey::new_from_array(RENT_ID.to_bytes()), false),
             ],
             data: [
                 vec![MojoInstructionDiscriminator::CreateAccount as u8],
                 bytemuck::bytes_of(&gen_ix).to_vec(),
+                StateSchema::instruction_data(None),
                 player_state_bytes.clone(),
             ]
             .concat(),
         };
 
-        let recent_blockhash = client.client.get_latest_blockhash().unwrap();
+        // the schema flag leads the state, a payload without one is refused
+        let mut unflagged_ix = create_state_ix.clone();
+        unflagged_ix.data.remove(1 + GenIxHandler::LEN);
+        let message = Message::new(&[unflagged_ix], Some(&creator_pubkey));
+        let transaction = Transaction::new(
+            &[&creator],
+            message,
+            client.client.latest_blockhash().unwrap(),
+        );
+        let err = client.client.send_transaction(&transaction).unwrap_err();
+        assert_eq!(
+            err.program_error(),
+            Some(crate::MojoProgramError::InvalidInstructionData)
+        );
+
+        let recent_blockhash = client.client.latest_blockhash().unwrap();
         let message = Message::new(&[create_state_ix], Some(&creator_pubkey));
         let transaction = Transaction::new(&[&creator], message, recent_blockhash);
         client
             .client
-            .send_and_confirm_transaction(&transaction)
+            .send_transaction(&transaction)
             .expect("failed to create delegated state");
 
         let fetched_player_state: Position = client
@@ -132,6 +150,43 @@ mod tests {
             .map_err(|e| e.to_string())
             .unwrap();
         assert_eq!(fetched_player_state, player_state);
+
+        let world_header = client
+            .read_world_header(&world)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        assert_eq!(world_header.state_count(), 1);
         Ok(())
     }
+
+    #[test]
+    pub fn test_state_is_not_a_world() {
+        let (client, victim) = setup();
+        let world = client
+            .create_world(&victim, "victim world", Position { x: 0, y: 0 })
+            .unwrap();
+        let position = Position { x: 4, y: 2 };
+        client
+            .write_state(&world, "position", &victim, position)
+            .unwrap();
+        let victim_state = world.state_pda("position", &victim.pubkey(), &client.program_id);
+
+        // a state passed as the parent world would have its header counted into
+        let fake_world = crate::World {
+            world_pda: victim_state,
+            world_seed_hash: [0u8; 32],
+        };
+        let err = client
+            .write_state(&fake_world, "position", &victim, position)
+            .unwrap_err();
+        assert_eq!(
+            err.program_error(),
+            Some(crate::MojoProgramError::UninitializedAccount)
+        );
+
+        let stored: Position = client
+            .read_delegated_state(&world, "position", &victim.pubkey())
+            .unwrap();
+        assert_eq!(stored, position);
+    }
 }
@@ -0,0 +1,463 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use crate::{
+        delegation::{
+            DelegationOptions, DELEGATION_PROGRAM_ID, EU_DEVNET_VALIDATOR, MAGIC_CONTEXT_ID,
+        },
+        impl_mojo_state_pod,
+        instruction_builder::{
+            CommitAccountBuilder, CommitBatchBuilder, DelegateAccountBuilder, DelegateBatchBuilder,
+        },
+        session_actions, session_pda,
+        types::derive_pda,
+        DelegationArgs, GenIxHandler, LiteSvmTransport, MojoInstructionDiscriminator,
+        MojoProgramError, SdkClient, SessionToken, World, MAX_TRANSACTION_SIZE, SESSION_MAGIC,
+    };
+    use bytemuck::{Pod, Zeroable};
+    use solana_account::Account;
+    use solana_instruction::{AccountMeta, Instruction};
+    use solana_keypair::Keypair;
+    use solana_message::Message;
+    use solana_pubkey::Pubkey;
+    use solana_signer::Signer;
+    use solana_system_program::id as system_program_id;
+    use solana_transaction::Transaction;
+
+    #[repr(C)]
+    #[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+    pub struct Position {
+        x: u64,
+        y: u64,
+    }
+    impl_mojo_state_pod!(Position);
+
+    const PROGRAM_ID: Pubkey = Pubkey::new_from_array(mojo_program::ID);
+
+    /// Prefix of the callback the delegation program sends when it hands an account back
+    const UNDELEGATE_CALLBACK_DISCRIMINATOR: [u8; 8] = [196, 28, 41, 206, 48, 37, 51, 167];
+
+    #[test]
+    pub fn test_delegate_instruction_accounts() {
+        let creator = Pubkey::new_unique();
+        let world = Pubkey::new_unique();
+        let state = Pubkey::new_unique();
+        let seed_hash = [7u8; 32];
+
+        let ix = DelegateAccountBuilder::new(
+            PROGRAM_ID,
+            creator,
+            state,
+            world,
+            seed_hash,
+            DelegationOptions::new()
+                .validator(EU_DEVNET_VALIDATOR)
+                .commit_every(Duration::from_secs(5)),
+        )
+        .build()
+        .unwrap();
+
+        assert_eq!(
+            ix.data[0],
+            MojoInstructionDiscriminator::DelegateAccount as u8
+        );
+        assert_eq!(&ix.data[1..33], &seed_hash);
+        assert_eq!(ix.data.len(), 1 + GenIxHandler::LEN + DelegationArgs::LEN);
+
+        let args: DelegationArgs = bytemuck::pod_read_unaligned(&ix.data[1 + GenIxHandler::LEN..]);
+        assert_eq!(u32::from_le_bytes(args.commit_frequency_ms), 5_000);
+        assert_eq!(u32::from_le_bytes(args.lifetime_secs), 0);
+        assert_eq!(args.validator, EU_DEVNET_VALIDATOR.to_bytes());
+
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        let (buffer, _) = derive_pda(&[b"buffer", state.as_ref()], &PROGRAM_ID);
+        let (record, _) = derive_pda(&[b"delegation", state.as_ref()], &DELEGATION_PROGRAM_ID);
+        let (metadata, _) = derive_pda(
+            &[b"delegation-metadata", state.as_ref()],
+            &DELEGATION_PROGRAM_ID,
+        );
+        assert_eq!(
+            keys[..7],
+            [creator, state, world, PROGRAM_ID, buffer, record, metadata]
+        );
+        assert!(ix.accounts[0].is_signer);
+    }
+
+    #[test]
+    pub fn test_commit_and_undelegate_instruction() {
+        let creator = Pubkey::new_unique();
+        let world = Pubkey::new_unique();
+
+        let commit_ix = CommitAccountBuilder::new(PROGRAM_ID, creator, world, world, [1u8; 32])
+            .build()
+            .unwrap();
+        assert_eq!(
+            commit_ix.data[0],
+            MojoInstructionDiscriminator::Commit as u8
+        );
+        assert_eq!(commit_ix.accounts[3].pubkey, MAGIC_CONTEXT_ID);
+        assert!(commit_ix.accounts[3].is_writable);
+
+        let undelegate_ix = CommitAccountBuilder::new(PROGRAM_ID, creator, world, world, [1u8; 32])
+            .undelegate()
+            .build()
+            .unwrap();
+        assert_eq!(
+            undelegate_ix.data[0],
+            MojoInstructionDiscriminator::UndelegateAccount as u8
+        );
+        assert_eq!(undelegate_ix.accounts.len(), 5);
+    }
+
+    #[test]
+    pub fn test_delegation_options() {
+        // unset options leave every field to the world config or the program defaults
+        let args = DelegationOptions::new().to_args();
+        assert_eq!(bytemuck::bytes_of(&args), &[0u8; DelegationArgs::LEN]);
+
+        let options = DelegationOptions::new()
+            .commit_frequency_ms(1_000)
+            .lifetime(Duration::from_secs(600));
+        let args = options.to_args();
+        assert_eq!(u32::from_le_bytes(args.commit_frequency_ms), 1_000);
+        assert_eq!(u32::from_le_bytes(args.lifetime_secs), 600);
+        assert_eq!(args.validator, [0u8; 32]);
+
+        let config = options.to_world_config();
+        assert_eq!(config[..4], 1_000u32.to_le_bytes());
+        assert_eq!(config[4..], 600u32.to_le_bytes());
+    }
+
+    #[test]
+    pub fn test_world_delegation_config() {
+        let (client, creator) = SdkClient::litesvm()
+            .expect("Could not load mojo_program.so, run cargo build-sbf first");
+
+        let options = DelegationOptions::new()
+            .commit_every(Duration::from_secs(10))
+            .lifetime_secs(3_600);
+        let world = World::create_world_with_config(
+            &client,
+            &creator,
+            "delegation config world",
+            options.to_world_config(),
+            Position { x: 0, y: 0 },
+        )
+        .unwrap();
+
+        let header = world.read_header(&client).unwrap();
+        assert_eq!(header.commit_frequency_ms(), 10_000);
+        assert_eq!(header.delegation_lifetime_secs(), 3_600);
+        assert_eq!(header.delegated_until(), None);
+    }
+
+    #[test]
+    pub fn test_batch_instructions() {
+        let creator = Pubkey::new_unique();
+        let world = Pubkey::new_unique();
+        let states = [
+            (Pubkey::new_unique(), [2u8; 32]),
+            (Pubkey::new_unique(), [3u8; 32]),
+        ];
+
+        let delegate_ix = DelegateBatchBuilder::new(
+            PROGRAM_ID,
+            creator,
+            world,
+            DelegationOptions::new().validator(EU_DEVNET_VALIDATOR),
+        )
+        .add(world, [1u8; 32])
+        .add(states[0].0, states[0].1)
+        .add(states[1].0, states[1].1)
+        .build()
+        .unwrap();
+        assert_eq!(
+            delegate_ix.data[0],
+            MojoInstructionDiscriminator::DelegateBatch as u8
+        );
+        assert_eq!(delegate_ix.data[1], 3);
+        assert_eq!(&delegate_ix.data[2..34], &[1u8; 32]);
+        assert_eq!(&delegate_ix.data[66..98], &[3u8; 32]);
+        assert_eq!(delegate_ix.data.len(), 2 + 3 * 32 + DelegationArgs::LEN);
+        // 5 fixed accounts then the pda, buffer, record and metadata of every entry
+        assert_eq!(delegate_ix.accounts.len(), 5 + 3 * 4);
+        assert_eq!(delegate_ix.accounts[9].pubkey, states[0].0);
+        assert_eq!(
+            delegate_ix.accounts[11].pubkey,
+            derive_pda(
+                &[b"delegation", states[0].0.as_ref()],
+                &DELEGATION_PROGRAM_ID
+            )
+            .0
+        );
+
+        let undelegate_ix = CommitBatchBuilder::new(PROGRAM_ID, creator, world)
+            .add(states[0].0, states[0].1)
+            .add(states[1].0, states[1].1)
+            .undelegate()
+            .build()
+            .unwrap();
+        assert_eq!(
+            undelegate_ix.data[0],
+            MojoInstructionDiscriminator::UndelegateBatch as u8
+        );
+        assert_eq!(undelegate_ix.data.len(), 2 + 2 * 32);
+        assert_eq!(undelegate_ix.accounts[2].pubkey, MAGIC_CONTEXT_ID);
+        assert_eq!(undelegate_ix.accounts[4].pubkey, states[0].0);
+        assert_eq!(undelegate_ix.accounts.len(), 6);
+
+        // owners co-sign after the PDAs, once each, the signer never twice
+        let owner = Pubkey::new_unique();
+        let commit_ix = CommitBatchBuilder::new(PROGRAM_ID, creator, world)
+            .add(states[0].0, states[0].1)
+            .co_signer(owner)
+            .add(states[1].0, states[1].1)
+            .co_signer(owner)
+            .co_signer(creator)
+            .build()
+            .unwrap();
+        assert_eq!(commit_ix.accounts.len(), 7);
+        assert_eq!(
+            commit_ix.accounts[6],
+            AccountMeta::new_readonly(owner, true)
+        );
+
+        // empty batches are refused before they reach the program
+        assert!(CommitBatchBuilder::new(PROGRAM_ID, creator, world)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    pub fn test_batch_split_by_transaction_size() {
+        let creator = Pubkey::new_unique();
+        let world = Pubkey::new_unique();
+        let accounts: Vec<(Pubkey, [u8; 32])> =
+            (0..20u8).map(|i| (Pubkey::new_unique(), [i; 32])).collect();
+
+        let instructions = World::split_batch(&creator, &accounts, |batch| {
+            batch
+                .iter()
+                .fold(
+                    DelegateBatchBuilder::new(PROGRAM_ID, creator, world, DelegationOptions::new()),
+                    |builder, (account, seed_hash)| builder.add(*account, *seed_hash),
+                )
+                .build()
+        })
+        .unwrap();
+        assert!(instructions.len() > 1);
+
+        // every account is delegated exactly once, in order, by transactions that fit
+        let mut batched = Vec::new();
+        for instruction in &instructions {
+            let message = Message::new(std::slice::from_ref(instruction), Some(&creator));
+            assert!(1 + 64 + message.serialize().len() <= MAX_TRANSACTION_SIZE);
+            batched.extend(
+                instruction.accounts[5..]
+                    .chunks(4)
+                    .map(|delegation| delegation[0].pubkey),
+            );
+        }
+        let expected: Vec<Pubkey> = accounts.iter().map(|(account, _)| *account).collect();
+        assert_eq!(batched, expected);
+
+        // a sponsor paying the fees adds its key and signature to every transaction
+        let sponsor = Pubkey::new_unique();
+        let sponsored = World::split_batch(&sponsor, &accounts, |batch| {
+            batch
+                .iter()
+                .fold(
+                    DelegateBatchBuilder::new(PROGRAM_ID, creator, world, DelegationOptions::new()),
+                    |builder, (account, seed_hash)| builder.add(*account, *seed_hash),
+                )
+                .build()
+        })
+        .unwrap();
+        assert!(sponsored.len() >= instructions.len());
+        for instruction in &sponsored {
+            let message = Message::new(std::slice::from_ref(instruction), Some(&sponsor));
+            assert_eq!(message.header.num_required_signatures, 2);
+            assert!(1 + 2 * 64 + message.serialize().len() <= MAX_TRANSACTION_SIZE);
+        }
+    }
+
+    #[test]
+    pub fn test_undelegate_callback_rejects_forged_buffer() {
+        let svm = Arc::new(
+            LiteSvmTransport::with_mojo_program(PROGRAM_ID)
+                .expect("Could not load mojo_program.so, run cargo build-sbf first"),
+        );
+        let attacker = Keypair::new();
+        svm.airdrop(&attacker.pubkey(), 10_000_000_000).unwrap();
+        let client =
+            SdkClient::with_transports(Box::new(svm.clone()), Box::new(svm.clone()), PROGRAM_ID);
+
+        let world = client
+            .create_world(&attacker, "callback world", Position { x: 0, y: 0 })
+            .unwrap();
+        let victim = Pubkey::new_unique();
+        let session = session_pda(&world.world_pda, &victim, &PROGRAM_ID);
+
+        // a buffer signed by a plain keypair, holding a session of the victim for the attacker
+        let forged = SessionToken {
+            magic: SESSION_MAGIC,
+            world: world.world_pda.to_bytes(),
+            owner: victim.to_bytes(),
+            session_key: attacker.pubkey().to_bytes(),
+            expires_at: u64::MAX.to_le_bytes(),
+            allowed_actions: session_actions::ALL,
+            ..SessionToken::zeroed()
+        };
+        let buffer = Keypair::new();
+        svm.with_svm(|svm| {
+            svm.set_account(
+                buffer.pubkey(),
+                Account {
+                    lamports: 1_000_000_000,
+                    data: bytemuck::bytes_of(&forged).to_vec(),
+                    owner: DELEGATION_PROGRAM_ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+        })
+        .unwrap();
+
+        // borsh Vec<Vec<u8>> of the session seeds
+        let seeds: [&[u8]; 3] = [b"session", world.world_pda.as_ref(), victim.as_ref()];
+        let mut data = UNDELEGATE_CALLBACK_DISCRIMINATOR.to_vec();
+        data.extend((seeds.len() as u32).to_le_bytes());
+        for seed in seeds {
+            data.extend((seed.len() as u32).to_le_bytes());
+            data.extend(seed);
+        }
+        let callback = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(session, false),
+                AccountMeta::new_readonly(buffer.pubkey(), true),
+                AccountMeta::new(attacker.pubkey(), true),
+                AccountMeta::new_readonly(system_program_id(), false),
+            ],
+            data,
+        };
+        let transaction = Transaction::new_signed_with_payer(
+            &[callback],
+            Some(&attacker.pubkey()),
+            &[&attacker, &buffer],
+            client.client().latest_blockhash().unwrap(),
+        );
+
+        let err = client.client().send_transaction(&transaction).unwrap_err();
+        assert_eq!(err.program_error(), Some(MojoProgramError::InvalidPda));
+        assert!(client.client().get_account(&session).unwrap().is_none());
+    }
+
+    #[test]
+    pub fn test_states_written_while_world_delegated() {
+        let svm = Arc::new(
+            LiteSvmTransport::with_mojo_program(PROGRAM_ID)
+                .expect("Could not load mojo_program.so, run cargo build-sbf first"),
+        );
+        let creator = Keypair::new();
+        svm.airdrop(&creator.pubkey(), 10_000_000_000).unwrap();
+        let client =
+            SdkClient::with_transports(Box::new(svm.clone()), Box::new(svm.clone()), PROGRAM_ID);
+
+        let world = client
+            .create_world(&creator, "delegated world", Position { x: 0, y: 0 })
+            .unwrap();
+        client
+            .write_state(&world, "late joiner", &creator, Position { x: 1, y: 1 })
+            .unwrap();
+
+        // the delegation program owns the world on the base layer once it is delegated
+        svm.with_svm(|svm| {
+            let mut account = svm.get_account(&world.world_pda).unwrap();
+            account.owner = DELEGATION_PROGRAM_ID;
+            svm.set_account(world.world_pda, account).unwrap();
+        });
+
+        // states left on the base layer are still written there
+        client
+            .write_state(&world, "late joiner", &creator, Position { x: 2, y: 2 })
+            .unwrap();
+        let position: Position = client
+            .read_delegated_state(&world, "late joiner", &creator.pubkey())
+            .unwrap();
+        assert_eq!(position, Position { x: 2, y: 2 });
+
+        // but the world can not count new ones
+        let err = client
+            .write_state(&world, "newcomer", &creator, Position { x: 3, y: 3 })
+            .unwrap_err();
+        assert_eq!(
+            err.program_error(),
+            Some(MojoProgramError::AccountDelegated)
+        );
+    }
+
+    #[test]
+    pub fn test_batch_needs_owner_consent() {
+        let svm = Arc::new(
+            LiteSvmTransport::with_mojo_program(PROGRAM_ID)
+                .expect("Could not load mojo_program.so, run cargo build-sbf first"),
+        );
+        let creator = Keypair::new();
+        let player = Keypair::new();
+        svm.airdrop(&creator.pubkey(), 10_000_000_000).unwrap();
+        svm.airdrop(&player.pubkey(), 10_000_000_000).unwrap();
+        let client =
+            SdkClient::with_transports(Box::new(svm.clone()), Box::new(svm.clone()), PROGRAM_ID);
+
+        let world = client
+            .create_world(&creator, "consent world", Position { x: 0, y: 0 })
+            .unwrap();
+        client
+            .write_state(&world, "position", &player, Position { x: 1, y: 1 })
+            .unwrap();
+        let (state_pda, _seed_input, seed_hash) =
+            world.derive_state_pda("position", &player.pubkey(), &client);
+
+        // the SDK asks for the player before sending anything
+        assert!(client
+            .delegate_all(
+                &world,
+                &creator,
+                &[("position", player.pubkey())],
+                &[],
+                DelegationOptions::new(),
+            )
+            .is_err());
+
+        // the program refuses the creator acting alone on the state of the player
+        let delegate_ix = DelegateBatchBuilder::new(
+            PROGRAM_ID,
+            creator.pubkey(),
+            world.world_pda,
+            DelegationOptions::new(),
+        )
+        .add(state_pda, seed_hash)
+        .build()
+        .unwrap();
+        let err = World::submit_instructions(
+            &client,
+            client.client(),
+            &creator,
+            vec![delegate_ix.clone()],
+        )
+        .unwrap_err();
+        assert_eq!(err.program_error(), Some(MojoProgramError::Unauthorized));
+
+        // an account list that does not match the seed hashes is refused as such
+        let mut short_ix = delegate_ix;
+        short_ix.accounts.pop();
+        let err = World::submit_instructions(&client, client.client(), &creator, vec![short_ix])
+            .unwrap_err();
+        assert_eq!(
+            err.program_error(),
+            Some(MojoProgramError::InvalidInstructionData)
+        );
+    }
+}
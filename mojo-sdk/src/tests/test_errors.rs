@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests {
+    use crate::{client::SdkClient, impl_mojo_state_pod, MojoProgramError, MojoSDKError, RpcType};
+    use bytemuck::{self, Pod, Zeroable};
+    use mojo_program::errors::MojoError;
+    use solana_instruction::{error::InstructionError, AccountMeta, Instruction};
+    use solana_keypair::Keypair;
+    use solana_message::Message;
+    use solana_signer::Signer;
+    use solana_transaction::Transaction;
+    use solana_transaction_error::TransactionError;
+
+    #[repr(C)]
+    #[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+    pub struct Position {
+        x: u64,
+        y: u64,
+    }
+    impl_mojo_state_pod!(Position);
+
+    fn setup() -> (SdkClient, Keypair) {
+        SdkClient::litesvm().expect("Could not load mojo_program.so, run cargo build-sbf first")
+    }
+
+    #[test]
+    fn test_error_codes_match_program() {
+        let pairs = [
+            (MojoError::InvalidPda, MojoProgramError::InvalidPda),
+            (
+                MojoError::InvalidAccountSize,
+                MojoProgramError::InvalidAccountSize,
+            ),
+            (MojoError::MissingSigner, MojoProgramError::MissingSigner),
+            (
+                MojoError::AlreadyInitialized,
+                MojoProgramError::AlreadyInitialized,
+            ),
+            (MojoError::NotDelegated, MojoProgramError::NotDelegated),
+            (
+                MojoError::UnknownInstruction,
+                MojoProgramError::UnknownInstruction,
+            ),
+            (
+                MojoError::InvalidInstructionData,
+                MojoProgramError::InvalidInstructionData,
+            ),
+            (
+                MojoError::UninitializedAccount,
+                MojoProgramError::UninitializedAccount,
+            ),
+            (MojoError::WorldMismatch, MojoProgramError::WorldMismatch),
+            (
+                MojoError::DelegationExpired,
+                MojoProgramError::DelegationExpired,
+            ),
+            (MojoError::Unauthorized, MojoProgramError::Unauthorized),
+            (
+                MojoError::AccountDelegated,
+                MojoProgramError::AccountDelegated,
+            ),
+            (
+                MojoError::PatchOutOfBounds,
+                MojoProgramError::PatchOutOfBounds,
+            ),
+            (
+                MojoError::VersionConflict,
+                MojoProgramError::VersionConflict,
+            ),
+            (
+                MojoError::ArithmeticOverflow,
+                MojoProgramError::ArithmeticOverflow,
+            ),
+            (MojoError::AclFull, MojoProgramError::AclFull),
+            (MojoError::SessionExpired, MojoProgramError::SessionExpired),
+            (
+                MojoError::SessionLimitReached,
+                MojoProgramError::SessionLimitReached,
+            ),
+            (MojoError::InvalidSchema, MojoProgramError::InvalidSchema),
+        ];
+        for (program_error, sdk_error) in pairs {
+            assert_eq!(program_error as u32, sdk_error.code());
+            assert_eq!(
+                MojoProgramError::from_code(program_error as u32),
+                Some(sdk_error)
+            );
+        }
+        assert_eq!(MojoProgramError::from_code(u32::MAX), None);
+
+        let failed = TransactionError::InstructionError(0, InstructionError::Custom(6004));
+        assert_eq!(
+            MojoProgramError::from_transaction_error(&failed),
+            Some(MojoProgramError::NotDelegated)
+        );
+        // a system program error, the account to create is already in use
+        let failed = TransactionError::InstructionError(0, InstructionError::Custom(0));
+        assert_eq!(MojoProgramError::from_transaction_error(&failed), None);
+        assert_eq!(
+            MojoProgramError::from_transaction_error(&TransactionError::AccountNotFound),
+            None
+        );
+    }
+
+    #[test]
+    fn test_transaction_failed_carries_cause() {
+        let (client, creator) = setup();
+
+        client
+            .create_world(&creator, "Error World", Position { x: 1, y: 1 })
+            .unwrap();
+        let err = client
+            .create_world(&creator, "Error World", Position { x: 1, y: 1 })
+            .err()
+            .expect("a world can only be created once");
+        assert!(matches!(err, MojoSDKError::TransactionFailed { .. }));
+        assert_eq!(
+            err.program_error(),
+            Some(MojoProgramError::AlreadyInitialized)
+        );
+
+        // unknown discriminators are reported as such
+        let ix = Instruction {
+            program_id: client.program_id,
+            accounts: vec![AccountMeta::new(creator.pubkey(), true)],
+            data: vec![u8::MAX],
+        };
+        let blockhash = client.client.latest_blockhash().unwrap();
+        let transaction = Transaction::new(
+            &[&creator],
+            Message::new(&[ix], Some(&creator.pubkey())),
+            blockhash,
+        );
+        let err = client.client.send_transaction(&transaction).unwrap_err();
+        assert_eq!(
+            err.program_error(),
+            Some(MojoProgramError::UnknownInstruction)
+        );
+    }
+
+    #[test]
+    fn test_client_needs_an_ephemeral_rollup_url() {
+        assert!(SdkClient::new(RpcType::Dev).is_ok());
+
+        // mainnet has no ephemeral rollup to route delegated accounts to
+        assert!(matches!(
+            SdkClient::new(RpcType::Main),
+            Err(MojoSDKError::InvalidEndpoint(_))
+        ));
+        assert!(matches!(
+            SdkClient::new_with_er(RpcType::Dev, RpcType::ERMain),
+            Err(MojoSDKError::InvalidEndpoint(_))
+        ));
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::SdkClient, impl_mojo_state_pod, MojoState, StateHeader, STATE_HEADER_VERSION,
+        STATE_MAGIC,
+    };
+    use bytemuck::{self, Pod, Zeroable};
+    use std::{
+        io::Error,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use solana_keypair::Keypair;
+    use solana_pubkey::Pubkey;
+    use solana_signer::Signer;
+
+    #[repr(C)]
+    #[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+    pub struct Position {
+        x: u64,
+        y: u64,
+    }
+    impl_mojo_state_pod!(Position);
+
+    fn setup() -> (SdkClient, Keypair) {
+        SdkClient::litesvm().expect("Could not load mojo_program.so, run cargo build-sbf first")
+    }
+
+    #[test]
+    fn test_state_header_layout() {
+        // memcmp filters rely on the magic leading the account data, then the world and owner
+        assert_eq!(StateHeader::LEN, 105);
+        assert_eq!(StateHeader::MAGIC_OFFSET, 0);
+        assert_eq!(StateHeader::WORLD_OFFSET, 15);
+        assert_eq!(StateHeader::OWNER_OFFSET, 47);
+
+        let world = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let position = Position { x: 3, y: 4 };
+        let account_data = [
+            STATE_MAGIC.to_vec(),
+            vec![STATE_HEADER_VERSION],
+            vec![5u8],    // state type id
+            vec![1u8],    // schema version
+            vec![9u8; 8], // schema hash
+            world.to_bytes().to_vec(),
+            owner.to_bytes().to_vec(),
+            vec![0u8], // not delegated
+            0i64.to_le_bytes().to_vec(),
+            7u64.to_le_bytes().to_vec(),  // version
+            42u64.to_le_bytes().to_vec(), // last updated slot
+            vec![254u8],                  // bump
+            position.serialize().unwrap(),
+        ]
+        .concat();
+
+        let (header, state_data) = StateHeader::split_account_data(&account_data).unwrap();
+        assert_eq!(header.state_type_id, 5);
+        assert_eq!(header.schema_version, 1);
+        assert_eq!(header.schema_hash, [9u8; 8]);
+        assert_eq!(header.world(), world);
+        assert_eq!(header.owner(), owner);
+        assert!(!header.is_delegated());
+        assert_eq!(header.delegated_until(), None);
+        assert_eq!(header.version(), 7);
+        assert_eq!(header.last_updated(), 42);
+        assert_eq!(header.bump(), 254);
+        assert_eq!(Position::deserialize(state_data).unwrap(), position);
+
+        // readers of another type are turned away
+        assert!(header.check_schema::<Position>().is_err());
+
+        assert!(StateHeader::from_account_data(&account_data[..40]).is_err());
+        let mut foreign = account_data.clone();
+        foreign[0] = 0;
+        assert!(StateHeader::from_account_data(&foreign).is_err());
+    }
+
+    #[test]
+    pub fn test_list_states() -> Result<(), Error> {
+        let (client, creator) = setup();
+        let creator_pubkey = creator.pubkey();
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        let world = client
+            .create_world(
+                &creator,
+                &format!("List World {}", millis),
+                Position { x: 0, y: 0 },
+            )
+            .map_err(|e| e.to_string())
+            .unwrap();
+
+        let player_one = Position { x: 1, y: 1 };
+        let player_two = Position { x: 2, y: 2 };
+        client
+            .write_state(&world, "player_one", &creator, player_one)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        client
+            .write_state(&world, "player_two", &creator, player_two)
+            .map_err(|e| e.to_string())
+            .unwrap();
+
+        let entries = client
+            .list_states(&world)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+        for entry in &entries {
+            assert_eq!(entry.header.world(), world.world_pda);
+            assert_eq!(entry.owner(), creator_pubkey);
+        }
+        assert!(entries.iter().any(|entry| {
+            entry.pubkey == world.state_pda("player_one", &creator_pubkey, &client.program_id)
+                && entry.state::<Position>().unwrap() == player_one
+        }));
+
+        let owned = client
+            .states_of_owner(&world, &creator_pubkey)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        assert_eq!(owned.len(), 2);
+
+        let not_owned = client
+            .states_of_owner(&world, &Pubkey::new_unique())
+            .map_err(|e| e.to_string())
+            .unwrap();
+        assert!(not_owned.is_empty());
+
+        Ok(())
+    }
+}
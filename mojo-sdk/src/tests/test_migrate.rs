@@ -0,0 +1,234 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::SdkClient, mojo_state, Migrate, MigrateAccountBuilder, MojoProgramError,
+        MojoSDKError, MojoState, PatchAccountBuilder, ResizeAccountBuilder, StateHeader,
+        StateSchema, UpdateDelegatedAccountBuilder, World,
+    };
+    use bytemuck::Zeroable;
+
+    use solana_keypair::Keypair;
+    use solana_pubkey::Pubkey;
+    use solana_signer::Signer;
+
+    // the first layout of the hero, before it carried gold
+    #[mojo_state(type_id = 3, seed = "hero")]
+    #[derive(Debug, PartialEq)]
+    pub struct HeroV0 {
+        level: u64,
+    }
+
+    #[mojo_state(type_id = 3, version = 1, seed = "hero", migrate)]
+    #[derive(Debug, PartialEq)]
+    pub struct Hero {
+        level: u64,
+        gold: u64,
+    }
+
+    impl Migrate for Hero {
+        fn from_version(version: u8, bytes: &[u8]) -> Result<Self, MojoSDKError> {
+            match version {
+                0 => Ok(Hero {
+                    level: HeroV0::deserialize(bytes)?.level,
+                    gold: 100,
+                }),
+                _ => Err(MojoSDKError::SchemaMismatch(format!(
+                    "unknown hero version {}",
+                    version
+                ))),
+            }
+        }
+    }
+
+    fn setup() -> (SdkClient, Keypair) {
+        SdkClient::litesvm().expect("Could not load mojo_program.so, run cargo build-sbf first")
+    }
+
+    fn header(client: &SdkClient, state: &Pubkey) -> (StateHeader, usize) {
+        let account = client.client().get_account(state).unwrap().unwrap();
+        let (header, state_data) = StateHeader::split_account_data(&account.data).unwrap();
+        (header, state_data.len())
+    }
+
+    #[test]
+    fn test_migrate_on_read_and_write() {
+        let (client, player) = setup();
+        let player_pubkey = player.pubkey();
+
+        let world = client
+            .create_world(&player, "migrating world", HeroV0::zeroed())
+            .unwrap();
+        HeroV0 { level: 4 }.write(&world, &client, &player).unwrap();
+        let state_pda = Hero::pda(&world, &player_pubkey, &client.program_id);
+
+        // reads upgrade the old layout without touching the account
+        assert_eq!(
+            Hero::read(&world, &client, &player_pubkey).unwrap(),
+            Hero {
+                level: 4,
+                gold: 100
+            }
+        );
+        let (stored, size) = header(&client, &state_pda);
+        assert_eq!(stored.schema(), StateSchema::of::<HeroV0>());
+        assert_eq!(size, 8);
+
+        // so do listed states
+        let entries = client.states_of_owner(&world, &player_pubkey).unwrap();
+        assert_eq!(
+            entries[0].state::<Hero>().unwrap(),
+            Hero {
+                level: 4,
+                gold: 100
+            }
+        );
+
+        // the next write persists the new layout
+        Hero {
+            level: 5,
+            gold: 100,
+        }
+        .write(&world, &client, &player)
+        .unwrap();
+        let (stored, size) = header(&client, &state_pda);
+        assert_eq!(stored.schema(), StateSchema::of::<Hero>());
+        assert_eq!(stored.version(), 1);
+        assert_eq!(size, 16);
+
+        // the old layout can not read the new one, nor write over it
+        let err = HeroV0::read(&world, &client, &player_pubkey).unwrap_err();
+        assert!(matches!(err, MojoSDKError::SchemaMismatch(_)));
+        let err = HeroV0 { level: 6 }
+            .write(&world, &client, &player)
+            .unwrap_err();
+        assert!(matches!(err, MojoSDKError::SchemaMismatch(_)));
+        assert_eq!(
+            header(&client, &state_pda).0.schema(),
+            StateSchema::of::<Hero>()
+        );
+    }
+
+    #[test]
+    fn test_migrate_state() {
+        let (client, player) = setup();
+        let player_pubkey = player.pubkey();
+
+        let world = client
+            .create_world(&player, "upgraded world", HeroV0::zeroed())
+            .unwrap();
+        HeroV0 { level: 7 }.write(&world, &client, &player).unwrap();
+
+        let hero: Hero = client.migrate_state(&world, Hero::SEED, &player).unwrap();
+        assert_eq!(
+            hero,
+            Hero {
+                level: 7,
+                gold: 100
+            }
+        );
+        let state_pda = Hero::pda(&world, &player_pubkey, &client.program_id);
+        assert_eq!(
+            header(&client, &state_pda).0.schema(),
+            StateSchema::of::<Hero>()
+        );
+
+        // migrating again is a no-op
+        client
+            .migrate_state::<Hero>(&world, Hero::SEED, &player)
+            .unwrap();
+        assert_eq!(header(&client, &state_pda).0.version(), 1);
+
+        // a state never moves back to an older version
+        let (_, seed_input, _) = world.derive_state_pda(Hero::SEED, &player_pubkey, &client);
+        let downgrade_ix = MigrateAccountBuilder::new(
+            client.program_id,
+            player_pubkey,
+            state_pda,
+            world.world_pda,
+            &seed_input,
+            StateSchema::of::<HeroV0>(),
+            HeroV0 { level: 7 }.serialize().unwrap(),
+        )
+        .build()
+        .unwrap();
+        let err = World::submit_instructions(&client, client.client(), &player, vec![downgrade_ix])
+            .unwrap_err();
+        assert_eq!(err.program_error(), Some(MojoProgramError::InvalidSchema));
+
+        // nor through a resize, which keeps the stored schema
+        let resize_ix = ResizeAccountBuilder::new(
+            client.program_id,
+            player_pubkey,
+            state_pda,
+            world.world_pda,
+            &seed_input,
+            8,
+        )
+        .state_data(HeroV0 { level: 7 }.serialize().unwrap())
+        .schema(StateSchema::of::<HeroV0>())
+        .build()
+        .unwrap();
+        let err = World::submit_instructions(&client, client.client(), &player, vec![resize_ix])
+            .unwrap_err();
+        assert_eq!(err.program_error(), Some(MojoProgramError::InvalidSchema));
+        assert_eq!(header(&client, &state_pda).1, 16);
+    }
+
+    #[test]
+    fn test_writes_keep_the_stored_schema() {
+        let (client, player) = setup();
+        let player_pubkey = player.pubkey();
+
+        let world = client
+            .create_world(&player, "typed world", Hero::zeroed())
+            .unwrap();
+        Hero { level: 2, gold: 5 }
+            .write(&world, &client, &player)
+            .unwrap();
+        let (state_pda, seed_input, _) =
+            world.derive_state_pda(Hero::SEED, &player_pubkey, &client);
+
+        // an update laid out for another schema is refused on-chain
+        let update_ix = UpdateDelegatedAccountBuilder::new(
+            client.program_id,
+            player_pubkey,
+            state_pda,
+            world.world_pda,
+            &seed_input,
+            vec![0xff; 16],
+        )
+        .schema(StateSchema::of::<HeroV0>())
+        .build()
+        .unwrap();
+        let err = World::submit_instructions(&client, client.client(), &player, vec![update_ix])
+            .unwrap_err();
+        assert_eq!(err.program_error(), Some(MojoProgramError::InvalidSchema));
+
+        // as is a patch of a field of another schema
+        let err = world
+            .patch_state(&client, Hero::SEED, &player, &[HeroV0::patch_level(9)])
+            .unwrap_err();
+        assert_eq!(err.program_error(), Some(MojoProgramError::InvalidSchema));
+
+        // segments of two schemas can not both match the state
+        let mixed_ix = PatchAccountBuilder::new(
+            client.program_id,
+            player_pubkey,
+            state_pda,
+            world.world_pda,
+            &seed_input,
+            vec![Hero::patch_gold(1), HeroV0::patch_level(1)],
+        )
+        .build();
+        assert!(matches!(mixed_ix, Err(MojoSDKError::SchemaMismatch(_))));
+
+        // the fields of the stored schema still land
+        world
+            .patch_state(&client, Hero::SEED, &player, &[Hero::patch_gold(6)])
+            .unwrap();
+        let hero: Hero = client
+            .read_delegated_state(&world, Hero::SEED, &player_pubkey)
+            .unwrap();
+        assert_eq!(hero, Hero { level: 2, gold: 6 });
+    }
+}
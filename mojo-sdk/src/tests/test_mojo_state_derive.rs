@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod tests {
+    use crate::{mojo_state, MojoState, PatchSegment, World};
+    use solana_pubkey::Pubkey;
+
+    const PROGRAM_ID: Pubkey = Pubkey::new_from_array(mojo_program::ID);
+
+    #[mojo_state(type_id = 1, seed = "board")]
+    #[derive(Debug, PartialEq)]
+    pub struct GameBoard {
+        cells: [u8; 9],
+        current_player: u8,
+        winner: u8,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, PartialEq, MojoState)]
+    pub struct Position {
+        x: u64,
+        y: u64,
+    }
+
+    #[test]
+    pub fn test_derived_state_roundtrip() {
+        let board = GameBoard {
+            cells: [0, 1, 2, 0, 1, 2, 0, 1, 2],
+            current_player: 1,
+            winner: 0,
+        };
+        let bytes = board.serialize().unwrap();
+        assert_eq!(bytes.len(), 11);
+        assert_eq!(GameBoard::deserialize(&bytes).unwrap(), board);
+
+        // account data is not guaranteed to be aligned
+        let position = Position { x: 4, y: 2 };
+        let mut unaligned = vec![0u8];
+        unaligned.extend(position.serialize().unwrap());
+        assert_eq!(Position::deserialize(&unaligned[1..]).unwrap(), position);
+
+        assert!(Position::deserialize(&unaligned[..8]).is_err());
+    }
+
+    #[test]
+    pub fn test_derived_pda_helpers() {
+        assert_eq!(GameBoard::TYPE_ID, 1);
+        assert_eq!(GameBoard::SEED, "board");
+
+        let world = World {
+            world_pda: Pubkey::new_unique(),
+            world_seed_hash: [3u8; 32],
+        };
+        let owner = Pubkey::new_unique();
+        assert_eq!(
+            GameBoard::pda(&world, &owner, &PROGRAM_ID),
+            world.state_pda("board", &owner, &PROGRAM_ID)
+        );
+    }
+
+    #[test]
+    pub fn test_derived_patch_helpers() {
+        assert_eq!(
+            GameBoard::patch_cells([1; 9]),
+            PatchSegment::new(0, vec![1; 9])
+        );
+        assert_eq!(GameBoard::patch_winner(2), PatchSegment::new(10, vec![2]));
+        assert_eq!(
+            Position::patch_y(7),
+            PatchSegment::new(8, 7u64.to_le_bytes().to_vec())
+        );
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use crate::{client::SdkClient, derive_pda, mojo_state, LiteSvmTransport, StateHeader, World};
+    use bytemuck::Zeroable;
+    use std::sync::Arc;
+
+    use solana_keypair::Keypair;
+    use solana_pubkey::Pubkey;
+    use solana_signer::Signer;
+
+    const PROGRAM_ID: Pubkey = Pubkey::new_from_array(mojo_program::ID);
+
+    /// Compute units `create_program_address` charges, once per bump the search tries
+    const BUMP_TRY_UNITS: u64 = 1_500;
+
+    #[mojo_state(seed = "miner")]
+    #[derive(Debug, PartialEq)]
+    pub struct Miner {
+        ore: u64,
+    }
+
+    fn setup() -> (Arc<LiteSvmTransport>, SdkClient, Keypair) {
+        let svm = Arc::new(
+            LiteSvmTransport::with_mojo_program(PROGRAM_ID)
+                .expect("Could not load mojo_program.so, run cargo build-sbf first"),
+        );
+        let creator = Keypair::new();
+        svm.airdrop(&creator.pubkey(), 10_000_000_000).unwrap();
+
+        let client =
+            SdkClient::with_transports(Box::new(svm.clone()), Box::new(svm.clone()), PROGRAM_ID);
+        (svm, client, creator)
+    }
+
+    /// A funded player whose miner PDA in `world` has a canonical bump accepted by `wanted`
+    fn player_with_bump(
+        svm: &LiteSvmTransport,
+        client: &SdkClient,
+        world: &World,
+        wanted: impl Fn(u8) -> bool,
+    ) -> (Keypair, u8) {
+        loop {
+            let player = Keypair::new();
+            let (_, _, seed_hash) = world.derive_state_pda(Miner::SEED, &player.pubkey(), client);
+            let (_, bump) = derive_pda(
+                &[
+                    &seed_hash,
+                    world.world_pda.as_ref(),
+                    player.pubkey().as_ref(),
+                ],
+                &PROGRAM_ID,
+            );
+            if wanted(bump) {
+                svm.airdrop(&player.pubkey(), 1_000_000_000).unwrap();
+                return (player, bump);
+            }
+        }
+    }
+
+    /// Compute units of creating the miner of `player`, then of writing it
+    fn create_and_write_units(
+        svm: &LiteSvmTransport,
+        client: &SdkClient,
+        world: &World,
+        player: &Keypair,
+    ) -> (u64, u64) {
+        Miner { ore: 1 }.write(world, client, player).unwrap();
+        let create_units = svm.last_compute_units().unwrap();
+        Miner { ore: 2 }.write(world, client, player).unwrap();
+        let write_units = svm.last_compute_units().unwrap();
+        (create_units, write_units)
+    }
+
+    #[test]
+    fn test_stored_bump_saves_compute_units() {
+        let (svm, client, creator) = setup();
+        let world = client
+            .create_world(&creator, "bump world", Miner::zeroed())
+            .unwrap();
+
+        // the search for the bump tries 255 first and one bump less for every miss
+        let (fast_player, fast_bump) = player_with_bump(&svm, &client, &world, |bump| bump == 255);
+        let (slow_player, slow_bump) = player_with_bump(&svm, &client, &world, |bump| bump <= 251);
+
+        let (fast_create, fast_write) = create_and_write_units(&svm, &client, &world, &fast_player);
+        let (slow_create, slow_write) = create_and_write_units(&svm, &client, &world, &slow_player);
+
+        // the header keeps the bump found at creation
+        for (player, bump) in [(&fast_player, fast_bump), (&slow_player, slow_bump)] {
+            let state_pda = Miner::pda(&world, &player.pubkey(), &PROGRAM_ID);
+            let account = client.client().get_account(&state_pda).unwrap().unwrap();
+            assert_eq!(
+                StateHeader::from_account_data(&account.data)
+                    .unwrap()
+                    .bump(),
+                bump
+            );
+        }
+
+        // creating still searches the bump, every miss costs another hash
+        let missed_bumps = (255 - slow_bump) as u64;
+        let search_units = slow_create.saturating_sub(fast_create);
+        assert!(search_units >= missed_bumps * BUMP_TRY_UNITS);
+
+        // writes check the stored bump with a single hash, whatever the bump
+        let write_gap = slow_write.abs_diff(fast_write);
+        assert!(write_gap < BUMP_TRY_UNITS);
+    }
+}
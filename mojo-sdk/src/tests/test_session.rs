@@ -0,0 +1,236 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::SdkClient, mojo_state, session_actions, AccessMode, LiteSvmTransport,
+        MojoProgramError, SessionOptions, StateHeader, WorldHeader,
+    };
+    use bytemuck::Zeroable;
+    use std::sync::Arc;
+
+    use solana_keypair::Keypair;
+    use solana_pubkey::Pubkey;
+    use solana_signer::Signer;
+
+    const PROGRAM_ID: Pubkey = Pubkey::new_from_array(mojo_program::ID);
+
+    #[mojo_state(seed = "stats")]
+    #[derive(Debug, PartialEq)]
+    pub struct Stats {
+        wins: u32,
+        moves: u32,
+    }
+
+    /// A client sharing its LiteSVM with the test, to warp slots
+    fn setup() -> (SdkClient, Keypair, Arc<LiteSvmTransport>) {
+        let svm = Arc::new(
+            LiteSvmTransport::with_mojo_program(PROGRAM_ID)
+                .expect("Could not load mojo_program.so, run cargo build-sbf first"),
+        );
+        let player = Keypair::new();
+        svm.airdrop(&player.pubkey(), 10_000_000_000).unwrap();
+
+        let client =
+            SdkClient::with_transports(Box::new(svm.clone()), Box::new(svm.clone()), PROGRAM_ID);
+        (client, player, svm)
+    }
+
+    /// Flag `account` as delegated the way its clone on an ephemeral rollup is, `offset` is
+    /// the position of the flag in its header
+    fn flag_delegated(svm: &LiteSvmTransport, account: &Pubkey, offset: usize) {
+        svm.with_svm(|svm| {
+            let mut account_data = svm.get_account(account).unwrap();
+            account_data.data[offset] = 1;
+            svm.set_account(*account, account_data).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_session_writes_and_limits() {
+        let (client, player, _svm) = setup();
+        let player_pubkey = player.pubkey();
+
+        let world = client
+            .create_world(&player, "session world", Stats::zeroed())
+            .unwrap();
+        Stats::zeroed().write(&world, &client, &player).unwrap();
+
+        let session = client
+            .create_session(&world, &player, SessionOptions::new().max_actions(3))
+            .unwrap();
+        let token = session.read_token(&client).unwrap();
+        assert_eq!(token.session_key(), session.keypair().pubkey());
+        assert_eq!(token.max_actions(), 3);
+
+        // the player signs nothing from here on
+        session
+            .write_state(&client, Stats::SEED, Stats { wins: 1, moves: 0 })
+            .unwrap();
+        session
+            .increment(&client, Stats::SEED, Stats::field_moves(), 5)
+            .unwrap();
+        session
+            .patch_state(&client, Stats::SEED, &[Stats::patch_wins(2)])
+            .unwrap();
+        assert_eq!(
+            Stats::read(&world, &client, &player_pubkey).unwrap(),
+            Stats { wins: 2, moves: 5 }
+        );
+        assert_eq!(session.read_token(&client).unwrap().actions_used(), 3);
+
+        let err = session
+            .increment(&client, Stats::SEED, Stats::field_moves(), 1)
+            .unwrap_err();
+        assert_eq!(
+            err.program_error(),
+            Some(MojoProgramError::SessionLimitReached)
+        );
+    }
+
+    #[test]
+    fn test_session_scope_and_expiry() {
+        let (client, player, svm) = setup();
+        let player_pubkey = player.pubkey();
+
+        let world = client
+            .create_world(&player, "scoped world", Stats::zeroed())
+            .unwrap();
+        Stats::zeroed().write(&world, &client, &player).unwrap();
+
+        let session = client
+            .create_session(
+                &world,
+                &player,
+                SessionOptions::new()
+                    .valid_for_slots(10)
+                    .allowed_actions(session_actions::ARITHMETIC),
+            )
+            .unwrap();
+
+        // only the allowed instructions are accepted
+        let err = session
+            .write_state(&client, Stats::SEED, Stats { wins: 9, moves: 9 })
+            .unwrap_err();
+        assert_eq!(err.program_error(), Some(MojoProgramError::Unauthorized));
+        session
+            .increment(&client, Stats::SEED, Stats::field_wins(), 1)
+            .unwrap();
+
+        let token = session.read_token(&client).unwrap();
+        svm.with_svm(|svm| svm.warp_to_slot(token.expires_at() + 1));
+        let err = session
+            .increment(&client, Stats::SEED, Stats::field_wins(), 1)
+            .unwrap_err();
+        assert_eq!(err.program_error(), Some(MojoProgramError::SessionExpired));
+
+        assert_eq!(
+            Stats::read(&world, &client, &player_pubkey).unwrap(),
+            Stats { wins: 1, moves: 0 }
+        );
+    }
+
+    #[test]
+    fn test_session_rotate_and_revoke() {
+        let (client, player, _svm) = setup();
+
+        let world = client
+            .create_world(&player, "rotating world", Stats::zeroed())
+            .unwrap();
+        Stats::zeroed().write(&world, &client, &player).unwrap();
+
+        let mut session = client
+            .create_session(&world, &player, SessionOptions::new())
+            .unwrap();
+        let old_key = session.keypair().pubkey();
+
+        session
+            .rotate(&client, &player, SessionOptions::new())
+            .unwrap();
+        let token = session.read_token(&client).unwrap();
+        assert_ne!(token.session_key(), old_key);
+        assert_eq!(token.session_key(), session.keypair().pubkey());
+        session
+            .increment(&client, Stats::SEED, Stats::field_moves(), 1)
+            .unwrap();
+
+        // a session key writes shared states for its owner as well
+        let board = client
+            .create_shared_state(
+                &world,
+                &player,
+                "board",
+                AccessMode::CreatorOnly,
+                Stats::zeroed(),
+            )
+            .unwrap();
+        board
+            .increment(&client, &session, Stats::field_moves(), 1)
+            .unwrap();
+        assert_eq!(board.read::<Stats>(&client).unwrap().moves, 1);
+
+        let session_pda = session.session_pda;
+        session.revoke(&client).unwrap();
+        assert!(client.client().get_account(&session_pda).unwrap().is_none());
+
+        // the player can revoke a session they no longer hold the key of
+        client
+            .create_session(&world, &player, SessionOptions::new())
+            .unwrap();
+        client.revoke_session(&world, &player).unwrap();
+        assert!(client.client().get_account(&session_pda).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_limited_session_stays_on_base_layer() {
+        let (client, player, svm) = setup();
+        let player_pubkey = player.pubkey();
+
+        let world = client
+            .create_world(&player, "limited world", Stats::zeroed())
+            .unwrap();
+        Stats::zeroed().write(&world, &client, &player).unwrap();
+        let limited = client
+            .create_session(&world, &player, SessionOptions::new().max_actions(3))
+            .unwrap();
+
+        // the session is never delegated, so it can not count writes to a delegated state
+        let state_pda = Stats::pda(&world, &player_pubkey, &PROGRAM_ID);
+        flag_delegated(
+            &svm,
+            &state_pda,
+            std::mem::offset_of!(StateHeader, is_delegated),
+        );
+        let err = limited
+            .increment(&client, Stats::SEED, Stats::field_wins(), 1)
+            .unwrap_err();
+        assert_eq!(
+            err.program_error(),
+            Some(MojoProgramError::AccountDelegated)
+        );
+        assert_eq!(limited.read_token(&client).unwrap().actions_used(), 0);
+
+        // an unlimited session writes delegated states
+        client
+            .create_session(&world, &player, SessionOptions::new())
+            .unwrap()
+            .increment(&client, Stats::SEED, Stats::field_wins(), 1)
+            .unwrap();
+        assert_eq!(
+            Stats::read(&world, &client, &player_pubkey).unwrap().wins,
+            1
+        );
+
+        // and is the only kind a delegated world accepts
+        flag_delegated(
+            &svm,
+            &world.world_pda,
+            std::mem::offset_of!(WorldHeader, is_delegated),
+        );
+        let err = client
+            .create_session(&world, &player, SessionOptions::new().max_actions(3))
+            .unwrap_err();
+        assert_eq!(
+            err.program_error(),
+            Some(MojoProgramError::AccountDelegated)
+        );
+    }
+}
@@ -0,0 +1,232 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::SdkClient, mojo_state, AccessMode, CloseAccountBuilder, MojoProgramError, World,
+    };
+    use bytemuck::Zeroable;
+
+    use solana_instruction::{AccountMeta, Instruction};
+    use solana_keypair::Keypair;
+    use solana_message::Message;
+    use solana_pubkey::Pubkey;
+    use solana_signer::Signer;
+    use solana_system_program::id as system_program_id;
+    use solana_transaction::Transaction;
+
+    #[mojo_state(seed = "board")]
+    #[derive(Debug, PartialEq)]
+    pub struct Board {
+        cells: [u8; 9],
+        turn: u8,
+        moves: u16,
+    }
+
+    fn setup() -> (SdkClient, Keypair) {
+        SdkClient::litesvm().expect("Could not load mojo_program.so, run cargo build-sbf first")
+    }
+
+    /// A second player paying for their own transactions, funded by `payer`
+    fn funded_player(client: &SdkClient, payer: &Keypair) -> Keypair {
+        let player = Keypair::new();
+
+        // system program transfer: [instruction index u32 LE][lamports u64 LE]
+        let mut data = 2u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&1_000_000_000u64.to_le_bytes());
+        let transfer = Instruction {
+            program_id: system_program_id(),
+            accounts: vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(player.pubkey(), false),
+            ],
+            data,
+        };
+
+        let message = Message::new(&[transfer], Some(&payer.pubkey()));
+        let blockhash = client.client().latest_blockhash().unwrap();
+        let transaction = Transaction::new(&[payer], message, blockhash);
+        client.client().send_transaction(&transaction).unwrap();
+        player
+    }
+
+    #[test]
+    fn test_shared_state_listed_writers() {
+        let (client, creator) = setup();
+        let player = funded_player(&client, &creator);
+
+        let world = client
+            .create_world(&creator, "shared world", Board::zeroed())
+            .unwrap();
+        let board = client
+            .create_shared_state(
+                &world,
+                &creator,
+                Board::SEED,
+                AccessMode::Listed,
+                Board::zeroed(),
+            )
+            .unwrap();
+        assert_eq!(client.read_world_header(&world).unwrap().state_count(), 1);
+
+        let acl = board.read_acl(&client).unwrap();
+        assert_eq!(acl.access_mode(), Some(AccessMode::Listed));
+        assert!(acl.writers().is_empty());
+
+        // the world creator can always write
+        let mut next = Board::zeroed();
+        next.cells[4] = 1;
+        next.turn = 2;
+        board.write(&client, &creator, next).unwrap();
+
+        // an unlisted player can not
+        let err = board
+            .increment(&client, &player, Board::field_moves(), 1)
+            .unwrap_err();
+        assert_eq!(err.program_error(), Some(MojoProgramError::Unauthorized));
+
+        board
+            .add_writer(&client, &creator, &player.pubkey())
+            .unwrap();
+        assert_eq!(
+            board.read_acl(&client).unwrap().writers(),
+            vec![player.pubkey()]
+        );
+
+        board
+            .patch(&client, &player, &[Board::patch_turn(1)])
+            .unwrap();
+        board
+            .increment(&client, &player, Board::field_moves(), 2)
+            .unwrap();
+
+        let (fetched, version) = board.read_versioned::<Board>(&client).unwrap();
+        assert_eq!(version, 3);
+        assert_eq!(
+            fetched,
+            Board {
+                cells: [0, 0, 0, 0, 1, 0, 0, 0, 0],
+                turn: 1,
+                moves: 2,
+            }
+        );
+
+        // only the world creator manages the list
+        let intruder = Pubkey::new_unique();
+        let err = board.add_writer(&client, &player, &intruder).unwrap_err();
+        assert_eq!(err.program_error(), Some(MojoProgramError::Unauthorized));
+
+        board
+            .remove_writer(&client, &creator, &player.pubkey())
+            .unwrap();
+        let err = board.write(&client, &player, fetched).unwrap_err();
+        assert_eq!(err.program_error(), Some(MojoProgramError::Unauthorized));
+    }
+
+    #[test]
+    fn test_shared_state_any_member() {
+        let (client, creator) = setup();
+        let player = funded_player(&client, &creator);
+
+        let world = client
+            .create_world(&creator, "member world", Board::zeroed())
+            .unwrap();
+        let board = client
+            .create_shared_state(
+                &world,
+                &creator,
+                Board::SEED,
+                AccessMode::AnyMember,
+                Board::zeroed(),
+            )
+            .unwrap();
+
+        // a player without a state in the world is no member
+        let err = board
+            .increment(&client, &player, Board::field_moves(), 1)
+            .unwrap_err();
+        assert_eq!(err.program_error(), Some(MojoProgramError::Unauthorized));
+
+        // holding a state makes them one, the SDK passes it along as proof
+        world
+            .write_state(&client, "player", &player, Board::zeroed())
+            .unwrap();
+        board
+            .increment(&client, &player, Board::field_moves(), 1)
+            .unwrap();
+        assert_eq!(
+            client
+                .shared_state(&world, Board::SEED)
+                .read::<Board>(&client)
+                .unwrap()
+                .moves,
+            1
+        );
+    }
+
+    #[test]
+    fn test_close_shared_state() {
+        let (client, creator) = setup();
+
+        let world = client
+            .create_world(&creator, "closing world", Board::zeroed())
+            .unwrap();
+        let board = client
+            .create_shared_state(
+                &world,
+                &creator,
+                Board::SEED,
+                AccessMode::CreatorOnly,
+                Board::zeroed(),
+            )
+            .unwrap();
+
+        // the access list goes along with the state, at its own PDA
+        let other = client
+            .create_shared_state(
+                &world,
+                &creator,
+                "other board",
+                AccessMode::CreatorOnly,
+                Board::zeroed(),
+            )
+            .unwrap();
+        for children in [vec![], vec![other.acl_pda]] {
+            let close_ix = CloseAccountBuilder::new(
+                client.program_id,
+                creator.pubkey(),
+                board.state_pda,
+                board.world_pda,
+                board.seed_hash,
+            )
+            .children(children)
+            .build()
+            .unwrap();
+            World::submit_instructions(&client, client.client(), &creator, vec![close_ix])
+                .unwrap_err();
+        }
+        assert!(client
+            .client()
+            .get_account(&board.state_pda)
+            .unwrap()
+            .is_some());
+
+        board.close(&client, &creator).unwrap();
+
+        assert!(client
+            .client()
+            .get_account(&board.state_pda)
+            .unwrap()
+            .is_none());
+        assert!(client
+            .client()
+            .get_account(&board.acl_pda)
+            .unwrap()
+            .is_none());
+        assert_eq!(client.read_world_header(&world).unwrap().state_count(), 1);
+
+        // closing the world takes the access lists of its shared states along
+        client.close_world(&world, &creator).unwrap();
+        for account in [world.world_pda, other.state_pda, other.acl_pda] {
+            assert!(client.client().get_account(&account).unwrap().is_none());
+        }
+    }
+}
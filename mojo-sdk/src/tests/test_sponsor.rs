@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests {
+    use crate::{client::SdkClient, mojo_state, LiteSvmTransport};
+    use bytemuck::Zeroable;
+    use std::{mem::size_of, sync::Arc};
+
+    use solana_keypair::Keypair;
+    use solana_pubkey::Pubkey;
+    use solana_signer::Signer;
+
+    const PROGRAM_ID: Pubkey = Pubkey::new_from_array(mojo_program::ID);
+
+    #[mojo_state(seed = "hero")]
+    #[derive(Debug, PartialEq)]
+    pub struct Hero {
+        level: u64,
+        gold: u64,
+    }
+
+    /// A game server client sponsoring players, and a plain client over the same LiteSVM
+    fn setup() -> (SdkClient, SdkClient) {
+        let svm = Arc::new(
+            LiteSvmTransport::with_mojo_program(PROGRAM_ID)
+                .expect("Could not load mojo_program.so, run cargo build-sbf first"),
+        );
+        let sponsor = Keypair::new();
+        svm.airdrop(&sponsor.pubkey(), 10_000_000_000).unwrap();
+
+        let server =
+            SdkClient::with_transports(Box::new(svm.clone()), Box::new(svm.clone()), PROGRAM_ID)
+                .with_sponsor(sponsor);
+        let player_client =
+            SdkClient::with_transports(Box::new(svm.clone()), Box::new(svm), PROGRAM_ID);
+        (server, player_client)
+    }
+
+    #[test]
+    fn test_sponsor_pays_for_player() {
+        let (server, _player_client) = setup();
+        let sponsor = server.sponsor().unwrap();
+        // the player never holds any SOL
+        let player = Keypair::new();
+
+        let world = server
+            .create_world(sponsor, "sponsored world", Hero::zeroed())
+            .unwrap();
+
+        let hero = Hero { level: 1, gold: 10 };
+        server
+            .write_state(&world, Hero::SEED, &player, hero)
+            .unwrap();
+        server
+            .write_state(&world, Hero::SEED, &player, Hero { level: 2, gold: 5 })
+            .unwrap();
+        server
+            .resize_state(&world, Hero::SEED, &player, size_of::<Hero>() + 16)
+            .unwrap();
+
+        let state_pda = world.state_pda(Hero::SEED, &player.pubkey(), &PROGRAM_ID);
+        let entry = server
+            .states_of_owner(&world, &player.pubkey())
+            .unwrap()
+            .pop()
+            .unwrap();
+        assert_eq!(entry.pubkey, state_pda);
+        assert_eq!(entry.data.len(), size_of::<Hero>() + 16);
+        assert!(server
+            .client()
+            .get_account(&player.pubkey())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_partially_signed_write() {
+        let (server, player_client) = setup();
+        let sponsor = server.sponsor().unwrap();
+        let player = Keypair::new();
+
+        let world = server
+            .create_world(sponsor, "co-signed world", Hero::zeroed())
+            .unwrap();
+
+        // the player signs on their side, the server co-signs and pays
+        let transaction = player_client
+            .sponsored_write_state(
+                &world,
+                Hero::SEED,
+                &player,
+                &sponsor.pubkey(),
+                Hero { level: 3, gold: 7 },
+            )
+            .unwrap();
+        assert_eq!(transaction.fee_payer(), sponsor.pubkey());
+
+        // only the sponsor named in the transaction can complete it
+        assert!(transaction.clone().co_sign(&Keypair::new()).is_err());
+        transaction.co_sign_and_send(&server, sponsor).unwrap();
+
+        assert_eq!(
+            Hero::read(&world, &player_client, &player.pubkey()).unwrap(),
+            Hero { level: 3, gold: 7 }
+        );
+        assert!(player_client
+            .client()
+            .get_account(&player.pubkey())
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,174 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        impl_mojo_state_pod, AccountNotification, AccountPubsub, AccountStream, Endpoint,
+        MojoSDKError, MojoState, StateSubscription, SubscriptionConfig,
+    };
+    use bytemuck::{self, Pod, Zeroable};
+    use solana_pubkey::Pubkey;
+    use std::{
+        collections::VecDeque,
+        sync::{mpsc, Arc, Mutex},
+        time::Duration,
+    };
+
+    #[repr(C)]
+    #[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+    pub struct Position {
+        x: u64,
+        y: u64,
+    }
+    impl_mojo_state_pod!(Position);
+
+    /// Scripted connection: `Some` delivers a notification, `None` drops the connection
+    type Script = Vec<Option<AccountNotification>>;
+
+    /// Local stand-in for the websocket pubsub, serving one script per connection
+    struct LocalPubsub {
+        connections: Mutex<VecDeque<Script>>,
+        subscribed: mpsc::Sender<Endpoint>,
+    }
+
+    struct LocalStream {
+        script: VecDeque<Option<AccountNotification>>,
+    }
+
+    impl AccountStream for LocalStream {
+        fn recv_timeout(
+            &mut self,
+            timeout: Duration,
+        ) -> Result<Option<AccountNotification>, MojoSDKError> {
+            match self.script.pop_front() {
+                Some(Some(notification)) => Ok(Some(notification)),
+                Some(None) => Err(MojoSDKError::Subscription("connection reset".to_string())),
+                None => {
+                    std::thread::sleep(timeout);
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    impl AccountPubsub for LocalPubsub {
+        fn account_subscribe(
+            &self,
+            endpoint: Endpoint,
+            _account: &Pubkey,
+        ) -> Result<Box<dyn AccountStream>, MojoSDKError> {
+            let _ = self.subscribed.send(endpoint);
+            let script = self
+                .connections
+                .lock()
+                .unwrap()
+                .pop_front()
+                .ok_or_else(|| MojoSDKError::Subscription("refused".to_string()))?;
+            Ok(Box::new(LocalStream {
+                script: script.into(),
+            }))
+        }
+    }
+
+    fn notification(owner: Pubkey, world: Pubkey, position: Position) -> AccountNotification {
+        AccountNotification {
+            slot: position.y,
+            owner,
+            data: [
+                world.to_bytes().to_vec(),
+                owner.to_bytes().to_vec(),
+                vec![0u8], // not delegated
+                0i64.to_le_bytes().to_vec(),
+                position.serialize().unwrap(),
+            ]
+            .concat(),
+        }
+    }
+
+    #[test]
+    fn test_subscription_resubscribes_and_decodes() {
+        let program_id = Pubkey::new_unique();
+        let world = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        let delegation_program = Pubkey::new_unique();
+
+        let first = Position { x: 1, y: 1 };
+        let second = Position { x: 2, y: 2 };
+
+        let (subscribed, subscriptions) = mpsc::channel();
+        let pubsub = LocalPubsub {
+            connections: Mutex::new(VecDeque::from(vec![
+                // first connection delivers one update then drops
+                vec![Some(notification(program_id, world, first)), None],
+                // after resubscribing: a duplicate, a foreign owned account and a new state
+                vec![
+                    Some(notification(program_id, world, first)),
+                    Some(notification(delegation_program, world, second)),
+                    Some(notification(program_id, world, second)),
+                ],
+            ])),
+            subscribed,
+        };
+
+        let subscription = StateSubscription::<Position>::open(
+            Arc::new(pubsub),
+            program_id,
+            account,
+            SubscriptionConfig {
+                endpoints: vec![Endpoint::Ephemeral],
+                reconnect_delay: Duration::from_millis(10),
+                max_reconnect_delay: Duration::from_millis(10),
+            },
+        );
+
+        let update = subscription
+            .recv_timeout(Duration::from_secs(5))
+            .expect("first update")
+            .unwrap();
+        assert_eq!(update.state, first);
+        assert_eq!(update.pubkey, account);
+        assert_eq!(update.endpoint, Endpoint::Ephemeral);
+        assert_eq!(update.header.world(), world);
+
+        let update = subscription
+            .recv_timeout(Duration::from_secs(5))
+            .expect("update after resubscribing")
+            .unwrap();
+        assert_eq!(update.state, second);
+        assert_eq!(update.slot, 2);
+
+        assert_eq!(subscriptions.try_iter().count(), 2);
+        subscription.unsubscribe();
+    }
+
+    #[test]
+    fn test_subscription_callback() {
+        let program_id = Pubkey::new_unique();
+        let world = Pubkey::new_unique();
+        let position = Position { x: 7, y: 8 };
+
+        let (subscribed, _subscriptions) = mpsc::channel();
+        let pubsub = LocalPubsub {
+            connections: Mutex::new(VecDeque::from(vec![vec![Some(notification(
+                program_id, world, position,
+            ))]])),
+            subscribed,
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let handle = StateSubscription::<Position>::open(
+            Arc::new(pubsub),
+            program_id,
+            Pubkey::new_unique(),
+            SubscriptionConfig {
+                endpoints: vec![Endpoint::Base],
+                ..Default::default()
+            },
+        )
+        .for_each(move |update| {
+            let _ = sender.send(update.map(|update| update.state));
+        });
+
+        let received = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(received.unwrap(), position);
+        handle.unsubscribe();
+    }
+}
@@ -6,8 +6,8 @@ mod tests {
     use super::*;
 
     use crate::{
-        client::{RpcType, SdkClient},
-        impl_mojo_state_pod, MojoState,
+        client::SdkClient, impl_mojo_state_pod, mojo_state, MojoProgramError, MojoSDKError,
+        MojoState, PatchSegment,
     };
     use bytemuck::{self, Pod, Zeroable};
     use solana_program::msg;
@@ -19,7 +19,7 @@ mod tests {
     use crate::types::derive_pda;
     use solana_keypair::Keypair;
     use solana_pubkey::Pubkey;
-    use solana_signer::{EncodableKey, Signer};
+    use solana_signer::Signer;
 
     // use crate::instructions::MojoInstructions::CreateAccount;
 
@@ -38,16 +38,34 @@ mod tests {
     }
     impl_mojo_state_pod!(Position);
 
+    #[repr(C)]
+    #[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+    pub struct Position3d {
+        x: u64,
+        y: u64,
+        z: u64,
+    }
+    impl_mojo_state_pod!(Position3d);
+
+    #[mojo_state(seed = "board")]
+    #[derive(Debug, PartialEq)]
+    pub struct GameBoard {
+        cells: [u8; 9],
+        current_player: u8,
+        winner: u8,
+    }
+
+    // same size as GameBoard, another layout
+    #[mojo_state(type_id = 2)]
+    #[derive(Debug, PartialEq)]
+    pub struct Banner {
+        text: [u8; 11],
+    }
+
     fn setup() -> (SdkClient, Keypair) {
-        let client = SdkClient::new(RpcType::Dev);
-        let creator =
-            Keypair::read_from_file("../dev_wallet.json").expect("Could not read keypair file");
-        return (client, creator);
+        SdkClient::litesvm().expect("Could not load mojo_program.so, run cargo build-sbf first")
     }
 
-    /**
-     * Tests assume use of a funded key
-     */
     #[test]
     pub fn test_write_state() -> Result<(), Error> {
         let (client, creator) = setup();
@@ -80,6 +98,14 @@ mod tests {
             .unwrap();
         assert_eq!(fetched_world_state, starting_position);
 
+        // Open: a second handle, as the opponent's client would get it, points at the same world
+        let opened_world = client
+            .open_world(&creator_pubkey, &new_world)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        assert_eq!(opened_world.world_pda, world.world_pda);
+        assert_eq!(opened_world.world_seed_hash, world.world_seed_hash);
+
         // Create: A new Player state is created because it does not exist yet
         // create a delegated state account manually via CreateAccount instruction
         let state_name = "player_position";
@@ -92,7 +118,7 @@ mod tests {
             .unwrap();
 
         let fetched_player_state: Position = client
-            .read_delegated_state(&world, state_name, &creator_pubkey)
+            .read_delegated_state(&opened_world, state_name, &creator_pubkey)
             .map_err(|e| e.to_string())
             .unwrap();
         assert_eq!(
@@ -120,4 +146,228 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    pub fn test_write_state_resizes() -> Result<(), Error> {
+        let (client, creator) = setup();
+        let creator_pubkey = creator.pubkey();
+
+        let world = client
+            .create_world(&creator, "Resize World", Position { x: 0, y: 0 })
+            .map_err(|e| e.to_string())
+            .unwrap();
+        let state_name = "player_position";
+        let state_pda = world.state_pda(state_name, &creator_pubkey, &client.program_id);
+        let account =
+            |client: &SdkClient| client.client().get_account(&state_pda).unwrap().unwrap();
+
+        client
+            .write_state(&world, state_name, &creator, Position { x: 1, y: 2 })
+            .map_err(|e| e.to_string())
+            .unwrap();
+        let small = account(&client);
+
+        // Grow: the schema gained a field, the account is reallocated and topped up
+        let grown = Position3d { x: 1, y: 2, z: 3 };
+        client
+            .write_state(&world, state_name, &creator, grown)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        let large = account(&client);
+        assert_eq!(large.data.len(), small.data.len() + 8);
+        assert!(large.lamports > small.lamports);
+        let fetched: Position3d = client
+            .read_delegated_state(&world, state_name, &creator_pubkey)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        assert_eq!(fetched, grown);
+
+        // Shrink: the excess rent is refunded to the owner
+        client
+            .write_state(&world, state_name, &creator, Position { x: 4, y: 5 })
+            .map_err(|e| e.to_string())
+            .unwrap();
+        let shrunk = account(&client);
+        assert_eq!(shrunk.data.len(), small.data.len());
+        assert_eq!(shrunk.lamports, small.lamports);
+        let fetched: Position = client
+            .read_delegated_state(&world, state_name, &creator_pubkey)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        assert_eq!(fetched, Position { x: 4, y: 5 });
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_patch_state() -> Result<(), Error> {
+        let (client, creator) = setup();
+        let creator_pubkey = creator.pubkey();
+
+        let world = client
+            .create_world(&creator, "Patch World", Position { x: 0, y: 0 })
+            .map_err(|e| e.to_string())
+            .unwrap();
+        let board = GameBoard {
+            cells: [0; 9],
+            current_player: 1,
+            winner: 0,
+        };
+        board
+            .write(&world, &client, &creator)
+            .map_err(|e| e.to_string())
+            .unwrap();
+
+        // only the touched bytes travel, the rest of the state is left as is
+        GameBoard::patch(
+            &world,
+            &client,
+            &creator,
+            &[
+                PatchSegment::new(4, vec![1]),
+                GameBoard::patch_current_player(2),
+            ],
+        )
+        .map_err(|e| e.to_string())
+        .unwrap();
+        client
+            .patch_state(
+                &world,
+                GameBoard::SEED,
+                &creator,
+                &[GameBoard::patch_winner(1)],
+            )
+            .map_err(|e| e.to_string())
+            .unwrap();
+
+        let fetched = GameBoard::read(&world, &client, &creator_pubkey)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        assert_eq!(
+            fetched,
+            GameBoard {
+                cells: [0, 0, 0, 0, 1, 0, 0, 0, 0],
+                current_player: 2,
+                winner: 1,
+            }
+        );
+
+        // segments may not reach past the end of the state
+        let err = client
+            .patch_state(
+                &world,
+                GameBoard::SEED,
+                &creator,
+                &[PatchSegment::new(10, vec![1, 1])],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.program_error(),
+            Some(MojoProgramError::PatchOutOfBounds)
+        );
+
+        // a patch needs at least one segment
+        assert!(client
+            .patch_state(&world, GameBoard::SEED, &creator, &[])
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_write_state_if_version() -> Result<(), Error> {
+        let (client, creator) = setup();
+        let creator_pubkey = creator.pubkey();
+
+        let world = client
+            .create_world(&creator, "Version World", Position { x: 0, y: 0 })
+            .map_err(|e| e.to_string())
+            .unwrap();
+        let state_name = "player_position";
+
+        client
+            .write_state(&world, state_name, &creator, Position { x: 1, y: 1 })
+            .map_err(|e| e.to_string())
+            .unwrap();
+        let (position, version): (Position, u64) = client
+            .read_versioned_state(&world, state_name, &creator_pubkey)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        assert_eq!(position, Position { x: 1, y: 1 });
+        assert_eq!(version, 0);
+
+        // every write moves the version forward
+        client
+            .write_state_if_version(&world, state_name, &creator, Position { x: 2, y: 2 }, 0)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        client
+            .patch_state(
+                &world,
+                state_name,
+                &creator,
+                &[PatchSegment::value(0, &3u64)],
+            )
+            .map_err(|e| e.to_string())
+            .unwrap();
+        let (position, version): (Position, u64) = client
+            .read_versioned_state(&world, state_name, &creator_pubkey)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        assert_eq!(position, Position { x: 3, y: 2 });
+        assert_eq!(version, 2);
+
+        // a writer holding a stale version loses instead of overwriting
+        let err = client
+            .write_state_if_version(&world, state_name, &creator, Position { x: 9, y: 9 }, 1)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            MojoSDKError::VersionConflict {
+                expected: 1,
+                found: Some(2)
+            }
+        ));
+        let fetched: Position = client
+            .read_delegated_state(&world, state_name, &creator_pubkey)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        assert_eq!(fetched, Position { x: 3, y: 2 });
+
+        // versioned writes never create a state
+        let err = client
+            .write_state_if_version(&world, "missing", &creator, Position { x: 1, y: 1 }, 0)
+            .unwrap_err();
+        assert!(matches!(err, MojoSDKError::AccountNotFound(_)));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_read_checks_schema() {
+        let (client, creator) = setup();
+        let creator_pubkey = creator.pubkey();
+
+        let world = client
+            .create_world(&creator, "Schema World", Position { x: 0, y: 0 })
+            .unwrap();
+        GameBoard::zeroed()
+            .write(&world, &client, &creator)
+            .unwrap();
+
+        let header = client.states_of_owner(&world, &creator_pubkey).unwrap()[0].header;
+        assert_eq!(header.state_type_id, GameBoard::STATE_TYPE_ID);
+        assert_eq!(header.schema_hash, GameBoard::SCHEMA_HASH);
+        assert_ne!(GameBoard::SCHEMA_HASH, Banner::SCHEMA_HASH);
+
+        // a reader expecting another type is stopped before deserializing
+        let err = client
+            .read_delegated_state::<Banner>(&world, GameBoard::SEED, &creator_pubkey)
+            .unwrap_err();
+        assert!(matches!(err, MojoSDKError::SchemaMismatch(_)));
+        assert_eq!(
+            GameBoard::read(&world, &client, &creator_pubkey).unwrap(),
+            GameBoard::zeroed()
+        );
+    }
 }
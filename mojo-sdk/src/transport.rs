@@ -0,0 +1,285 @@
+//! Transports the SDK talks to the chain through
+//!
+//! `RpcClient` is the transport of a live cluster, `LiteSvmTransport` runs the Mojo program
+//! in-process so worlds and states can be exercised without a network
+
+use std::sync::Arc;
+
+use solana_account::Account;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_hash::Hash;
+use solana_pubkey::Pubkey;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_signature::Signature;
+use solana_transaction::Transaction;
+
+use crate::errors::MojoSDKError;
+
+/// Minimal set of chain calls the SDK needs
+pub trait Transport: Send + Sync {
+    /// Fetch an account, `None` if it does not exist
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, MojoSDKError>;
+
+    /// Get a blockhash to sign transactions with
+    fn latest_blockhash(&self) -> Result<Hash, MojoSDKError>;
+
+    /// Send a signed transaction and wait for it to be confirmed
+    fn send_transaction(&self, transaction: &Transaction) -> Result<Signature, MojoSDKError>;
+
+    /// Fetch every account owned by `program_id` matching all `filters`
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<(Pubkey, Account)>, MojoSDKError>;
+}
+
+impl Transport for RpcClient {
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, MojoSDKError> {
+        self.get_account_with_commitment(pubkey, self.commitment())
+            .map(|response| response.value)
+            .map_err(|e| MojoSDKError::SolanaSdk(e.to_string()))
+    }
+
+    fn latest_blockhash(&self) -> Result<Hash, MojoSDKError> {
+        self.get_latest_blockhash()
+            .map_err(|_e| MojoSDKError::SolanaClient())
+    }
+
+    fn send_transaction(&self, transaction: &Transaction) -> Result<Signature, MojoSDKError> {
+        self.send_and_confirm_transaction(transaction).map_err(|e| {
+            MojoSDKError::transaction_failed(e.to_string(), e.get_transaction_error().as_ref())
+        })
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<(Pubkey, Account)>, MojoSDKError> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        self.get_program_accounts_with_config(program_id, config)
+            .map_err(|e| MojoSDKError::SolanaSdk(e.to_string()))
+    }
+}
+
+/// Lets one transport back both endpoints of a client
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, MojoSDKError> {
+        (**self).get_account(pubkey)
+    }
+
+    fn latest_blockhash(&self) -> Result<Hash, MojoSDKError> {
+        (**self).latest_blockhash()
+    }
+
+    fn send_transaction(&self, transaction: &Transaction) -> Result<Signature, MojoSDKError> {
+        (**self).send_transaction(transaction)
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<(Pubkey, Account)>, MojoSDKError> {
+        (**self).get_program_accounts(program_id, filters)
+    }
+}
+
+#[cfg(any(test, feature = "litesvm"))]
+pub use litesvm_transport::*;
+
+#[cfg(any(test, feature = "litesvm"))]
+mod litesvm_transport {
+    use std::{
+        collections::BTreeSet,
+        path::Path,
+        sync::Mutex,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use litesvm::LiteSVM;
+    use solana_sysvar::clock::Clock;
+
+    use super::*;
+
+    /// In-process [`Transport`] running the Mojo program on LiteSVM
+    ///
+    /// LiteSVM has no account index, so `get_program_accounts` scans every account that
+    /// was airdropped to or touched by a transaction sent through this transport
+    pub struct LiteSvmTransport {
+        svm: Mutex<LiteSVM>,
+        known_accounts: Mutex<BTreeSet<Pubkey>>,
+        last_compute_units: Mutex<Option<u64>>,
+    }
+
+    impl LiteSvmTransport {
+        /// Path of the program built by `cargo build-sbf` in `mojo-program`
+        pub const DEFAULT_PROGRAM_PATH: &'static str = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../mojo-program/target/deploy/mojo_program.so"
+        );
+
+        /// Start a LiteSVM with the program at `program_path` deployed under `program_id`
+        pub fn new(
+            program_id: Pubkey,
+            program_path: impl AsRef<Path>,
+        ) -> Result<Self, MojoSDKError> {
+            let program_path = program_path.as_ref();
+            let mut svm = LiteSVM::new();
+            svm.add_program_from_file(program_id, program_path)
+                .map_err(|e| {
+                    MojoSDKError::SolanaSdk(format!(
+                        "Failed to load {}: {}",
+                        program_path.display(),
+                        e
+                    ))
+                })?;
+
+            // start the clock at wall time like a live cluster, LiteSVM starts at zero
+            let mut clock = svm.get_sysvar::<Clock>();
+            clock.unix_timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs() as i64)
+                .unwrap_or_default();
+            svm.set_sysvar(&clock);
+
+            Ok(Self {
+                svm: Mutex::new(svm),
+                known_accounts: Mutex::new(BTreeSet::new()),
+                last_compute_units: Mutex::new(None),
+            })
+        }
+
+        /// Start a LiteSVM with the Mojo program, `MOJO_PROGRAM_SO` overrides its location
+        pub fn with_mojo_program(program_id: Pubkey) -> Result<Self, MojoSDKError> {
+            let program_path = std::env::var("MOJO_PROGRAM_SO")
+                .unwrap_or_else(|_| Self::DEFAULT_PROGRAM_PATH.to_string());
+            Self::new(program_id, program_path)
+        }
+
+        /// Fund `pubkey` with `lamports`
+        pub fn airdrop(&self, pubkey: &Pubkey, lamports: u64) -> Result<(), MojoSDKError> {
+            self.svm
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .airdrop(pubkey, lamports)
+                .map_err(|e| {
+                    MojoSDKError::transaction_failed(format!("{:?}", e.err), Some(&e.err))
+                })?;
+            self.remember(std::iter::once(*pubkey));
+            Ok(())
+        }
+
+        /// Run `f` against the underlying LiteSVM, e.g. to warp slots or inspect metadata
+        pub fn with_svm<R>(&self, f: impl FnOnce(&mut LiteSVM) -> R) -> R {
+            f(&mut self.svm.lock().unwrap_or_else(|e| e.into_inner()))
+        }
+
+        /// Compute units consumed by the last transaction sent, whether it landed or not
+        pub fn last_compute_units(&self) -> Option<u64> {
+            *self
+                .last_compute_units
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+        }
+
+        fn remember(&self, accounts: impl IntoIterator<Item = Pubkey>) {
+            self.known_accounts
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .extend(accounts);
+        }
+    }
+
+    impl Transport for LiteSvmTransport {
+        fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, MojoSDKError> {
+            let account = self
+                .svm
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get_account(pubkey);
+            // closed accounts linger as empty system accounts
+            Ok(account.filter(|account| account.lamports > 0))
+        }
+
+        fn latest_blockhash(&self) -> Result<Hash, MojoSDKError> {
+            Ok(self
+                .svm
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .latest_blockhash())
+        }
+
+        fn send_transaction(&self, transaction: &Transaction) -> Result<Signature, MojoSDKError> {
+            self.remember(transaction.message.account_keys.iter().copied());
+
+            let mut svm = self.svm.lock().unwrap_or_else(|e| e.into_inner());
+            let result = svm.send_transaction(transaction.clone());
+            // identical transactions would otherwise be rejected as already processed
+            svm.expire_blockhash();
+
+            let compute_units = match &result {
+                Ok(meta) => meta.compute_units_consumed,
+                Err(failed) => failed.meta.compute_units_consumed,
+            };
+            *self
+                .last_compute_units
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = Some(compute_units);
+
+            match result {
+                Ok(meta) => Ok(meta.signature),
+                Err(failed) => Err(MojoSDKError::transaction_failed(
+                    format!("{:?}\n{}", failed.err, failed.meta.logs.join("\n")),
+                    Some(&failed.err),
+                )),
+            }
+        }
+
+        fn get_program_accounts(
+            &self,
+            program_id: &Pubkey,
+            filters: Vec<RpcFilterType>,
+        ) -> Result<Vec<(Pubkey, Account)>, MojoSDKError> {
+            let known_accounts = self
+                .known_accounts
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone();
+
+            let mut accounts = Vec::new();
+            for pubkey in known_accounts {
+                let Some(account) = self.get_account(&pubkey)? else {
+                    continue;
+                };
+                if &account.owner == program_id
+                    && filters
+                        .iter()
+                        .all(|filter| filter_matches(filter, &account.data))
+                {
+                    accounts.push((pubkey, account));
+                }
+            }
+            Ok(accounts)
+        }
+    }
+
+    fn filter_matches(filter: &RpcFilterType, data: &[u8]) -> bool {
+        match filter {
+            RpcFilterType::DataSize(size) => data.len() as u64 == *size,
+            RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(data),
+            RpcFilterType::TokenAccountState => false,
+        }
+    }
+}
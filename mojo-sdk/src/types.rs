@@ -3,7 +3,7 @@
 use bytemuck::{Pod, Zeroable};
 use solana_program::pubkey::Pubkey;
 
-use crate::utils::helpers as utils;
+use crate::{errors::MojoSDKError, state::MojoState, utils::helpers as utils};
 
 /// Instruction discriminators matching the Solana program
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,6 +14,20 @@ pub enum MojoInstructionDiscriminator {
     Commit = 2,
     UpdateDelegatedAccount = 3,
     UndelegateAccount = 4,
+    CreateWorld = 5,
+    DelegateBatch = 6,
+    CommitBatch = 7,
+    UndelegateBatch = 8,
+    CloseAccount = 9,
+    ResizeAccount = 10,
+    PatchAccount = 11,
+    Arithmetic = 12,
+    CreateSharedState = 13,
+    AddWriter = 14,
+    RemoveWriter = 15,
+    CreateSession = 16,
+    RevokeSession = 17,
+    MigrateAccount = 18,
 }
 
 impl From<MojoInstructionDiscriminator> for u8 {
@@ -43,6 +57,14 @@ impl GenIxHandler {
         }
     }
 
+    /// Create a new GenIxHandler from an already hashed seed
+    pub fn from_seed_hash(seed_hash: [u8; 32], size: usize) -> Self {
+        Self {
+            seeds: seed_hash,
+            size: (size as u64).to_le_bytes(),
+        }
+    }
+
     /// Get the seed bytes
     pub fn seed_bytes(&self) -> &[u8; 32] {
         &self.seeds
@@ -54,6 +76,440 @@ impl GenIxHandler {
     }
 }
 
+/// Leads every World account, matching the Solana program
+pub const WORLD_MAGIC: [u8; 4] = *b"MOJW";
+
+/// Header stored at the start of every World account, matching the Solana program
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable, Debug, PartialEq)]
+pub struct WorldHeader {
+    pub magic: [u8; 4],
+    pub creator: [u8; 32],
+    pub name_hash: [u8; 32],  // hash of the world seed input
+    pub created_at: [u8; 8],  // i64 as le bytes
+    pub state_count: [u8; 8], // u64 as le bytes
+    pub config: [u8; 8],
+    pub is_delegated: u8,
+    pub delegated_until: [u8; 8], // i64 as le bytes, 0 when not expiring
+    pub bump: u8,                 // canonical bump of the world PDA
+}
+
+impl WorldHeader {
+    pub const LEN: usize = std::mem::size_of::<Self>(); // 102 bytes
+
+    /// Decode the header from the start of a World account's data
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MojoSDKError> {
+        let header_bytes = data.get(..Self::LEN).ok_or_else(|| {
+            MojoSDKError::InvalidStateData(format!(
+                "World account too small: expected at least {} bytes, got {}",
+                Self::LEN,
+                data.len()
+            ))
+        })?;
+        let header: Self = bytemuck::try_pod_read_unaligned(header_bytes)
+            .map_err(|e| MojoSDKError::Deserialization(format!("Invalid world header: {}", e)))?;
+
+        if header.magic != WORLD_MAGIC {
+            return Err(MojoSDKError::InvalidStateData(
+                "Account does not hold a Mojo world".to_string(),
+            ));
+        }
+        Ok(header)
+    }
+
+    /// Get the creator of the world
+    pub fn creator(&self) -> Pubkey {
+        Pubkey::new_from_array(self.creator)
+    }
+
+    /// Get the creation unix timestamp
+    pub fn created_at(&self) -> i64 {
+        i64::from_le_bytes(self.created_at)
+    }
+
+    /// Get the number of states created under this world
+    pub fn state_count(&self) -> u64 {
+        u64::from_le_bytes(self.state_count)
+    }
+
+    /// Whether the world is currently delegated to an ephemeral rollup
+    pub fn is_delegated(&self) -> bool {
+        self.is_delegated != 0
+    }
+
+    /// Unix timestamp the world delegation expires at, `None` if it does not expire
+    pub fn delegated_until(&self) -> Option<i64> {
+        Some(i64::from_le_bytes(self.delegated_until)).filter(|until| *until != 0)
+    }
+
+    /// Canonical bump of the world PDA, recorded by the program at creation
+    pub fn bump(&self) -> u8 {
+        self.bump
+    }
+
+    /// Default commit frequency of delegations in this world, 0 for the program default
+    pub fn commit_frequency_ms(&self) -> u32 {
+        u32::from_le_bytes([
+            self.config[0],
+            self.config[1],
+            self.config[2],
+            self.config[3],
+        ])
+    }
+
+    /// Default lifetime of delegations in this world, 0 for no expiry
+    pub fn delegation_lifetime_secs(&self) -> u32 {
+        u32::from_le_bytes([
+            self.config[4],
+            self.config[5],
+            self.config[6],
+            self.config[7],
+        ])
+    }
+}
+
+/// Leads every state account, matching the Solana program
+pub const STATE_MAGIC: [u8; 4] = *b"MOJO";
+
+/// Layout version of the [`StateHeader`] this SDK reads, matching the Solana program
+pub const STATE_HEADER_VERSION: u8 = 3;
+
+/// Header stored at the start of every state account, matching the Solana program
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable, Debug, PartialEq)]
+pub struct StateHeader {
+    pub magic: [u8; 4],
+    pub header_version: u8,
+    pub state_type_id: u8,
+    pub schema_version: u8,
+    pub schema_hash: [u8; 8],
+    pub world: [u8; 32],
+    pub owner: [u8; 32],
+    pub is_delegated: u8,
+    pub delegated_until: [u8; 8], // i64 as le bytes, 0 when not expiring
+    pub version: [u8; 8],         // u64 as le bytes, bumped by every write
+    pub last_updated: [u8; 8],    // u64 slot as le bytes of the last write
+    pub bump: u8,                 // canonical bump of the state PDA
+}
+
+impl StateHeader {
+    pub const LEN: usize = std::mem::size_of::<Self>(); // 105 bytes
+    /// Offset of the magic, used by `getProgramAccounts` memcmp filters
+    pub const MAGIC_OFFSET: usize = std::mem::offset_of!(Self, magic);
+    /// Offset of the world key, used by `getProgramAccounts` memcmp filters
+    pub const WORLD_OFFSET: usize = std::mem::offset_of!(Self, world);
+    /// Offset of the owner key, used by `getProgramAccounts` memcmp filters
+    pub const OWNER_OFFSET: usize = std::mem::offset_of!(Self, owner);
+
+    /// Decode the header from the start of a state account's data
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MojoSDKError> {
+        let header_bytes = data.get(..Self::LEN).ok_or_else(|| {
+            MojoSDKError::InvalidStateData(format!(
+                "State account too small: expected at least {} bytes, got {}",
+                Self::LEN,
+                data.len()
+            ))
+        })?;
+        let header: Self = bytemuck::try_pod_read_unaligned(header_bytes)
+            .map_err(|e| MojoSDKError::Deserialization(format!("Invalid state header: {}", e)))?;
+
+        if header.magic != STATE_MAGIC {
+            return Err(MojoSDKError::InvalidStateData(
+                "Account does not hold a Mojo state".to_string(),
+            ));
+        }
+        if header.header_version != STATE_HEADER_VERSION {
+            return Err(MojoSDKError::InvalidStateData(format!(
+                "Unsupported state header version {}, expected {}",
+                header.header_version, STATE_HEADER_VERSION
+            )));
+        }
+        Ok(header)
+    }
+
+    /// Split a state account's data into its header and the state bytes after it
+    pub fn split_account_data(data: &[u8]) -> Result<(Self, &[u8]), MojoSDKError> {
+        let header = Self::from_account_data(data)?;
+        Ok((header, &data[Self::LEN..]))
+    }
+
+    /// Get the world the state belongs to
+    pub fn world(&self) -> Pubkey {
+        Pubkey::new_from_array(self.world)
+    }
+
+    /// Get the owner of the state
+    pub fn owner(&self) -> Pubkey {
+        Pubkey::new_from_array(self.owner)
+    }
+
+    /// Whether the state is currently delegated to an ephemeral rollup
+    pub fn is_delegated(&self) -> bool {
+        self.is_delegated != 0
+    }
+
+    /// Unix timestamp the state delegation expires at, `None` if it does not expire
+    pub fn delegated_until(&self) -> Option<i64> {
+        Some(i64::from_le_bytes(self.delegated_until)).filter(|until| *until != 0)
+    }
+
+    /// Number of writes the state went through since it was created
+    pub fn version(&self) -> u64 {
+        u64::from_le_bytes(self.version)
+    }
+
+    /// Slot of the last write to the state, or of its creation
+    pub fn last_updated(&self) -> u64 {
+        u64::from_le_bytes(self.last_updated)
+    }
+
+    /// Canonical bump of the state PDA, recorded by the program at creation
+    pub fn bump(&self) -> u8 {
+        self.bump
+    }
+
+    /// The type and layout recorded for the state
+    pub fn schema(&self) -> StateSchema {
+        StateSchema {
+            state_type_id: self.state_type_id,
+            schema_version: self.schema_version,
+            schema_hash: self.schema_hash,
+        }
+    }
+
+    /// Check that the state holds a `T`, before deserializing it
+    pub fn check_schema<T: MojoState>(&self) -> Result<(), MojoSDKError> {
+        if self.schema() != StateSchema::of::<T>() {
+            return Err(self.schema_mismatch::<T>());
+        }
+        Ok(())
+    }
+
+    /// Decode the state bytes following this header as a `T`
+    ///
+    /// States written under an older schema version of `T` are upgraded with
+    /// [`MojoState::migrate_from`], any other schema fails with [`MojoSDKError::SchemaMismatch`]
+    pub fn decode_state<T: MojoState>(&self, state_data: &[u8]) -> Result<T, MojoSDKError> {
+        let expected = StateSchema::of::<T>();
+        if self.schema() == expected {
+            T::deserialize(state_data)
+        } else if self.state_type_id == expected.state_type_id
+            && self.schema_version < expected.schema_version
+        {
+            T::migrate_from(self.schema_version, state_data)
+        } else {
+            Err(self.schema_mismatch::<T>())
+        }
+    }
+
+    fn schema_mismatch<T: MojoState>(&self) -> MojoSDKError {
+        self.schema().mismatch(&StateSchema::of::<T>())
+    }
+}
+
+/// Flag byte telling that no [`StateSchema`] follows it in instruction data
+pub const SCHEMA_ABSENT: u8 = 0;
+
+/// Flag byte telling that a [`StateSchema`] follows it in instruction data
+pub const SCHEMA_PRESENT: u8 = 1;
+
+/// Type and layout of a state, sent ahead of the state by the instructions writing a whole
+/// state, matching the Solana program
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable, Debug, Default, PartialEq, Eq)]
+pub struct StateSchema {
+    pub state_type_id: u8,
+    pub schema_version: u8,
+    pub schema_hash: [u8; 8],
+}
+
+impl StateSchema {
+    pub const LEN: usize = std::mem::size_of::<Self>();
+
+    /// The schema of the state type `T`
+    pub fn of<T: MojoState>() -> Self {
+        Self {
+            state_type_id: T::STATE_TYPE_ID,
+            schema_version: T::SCHEMA_VERSION,
+            schema_hash: T::SCHEMA_HASH,
+        }
+    }
+
+    /// Whether a state following this schema may migrate to `next`: untyped states adopt any
+    /// schema, typed ones only newer versions of their type
+    pub fn migrates_to(&self, next: &StateSchema) -> bool {
+        self.state_type_id == 0
+            || (self.state_type_id == next.state_type_id
+                && self.schema_version < next.schema_version)
+    }
+
+    /// `schema` as sent ahead of a state: its flag byte, then the schema when there is one
+    pub fn instruction_data(schema: Option<&StateSchema>) -> Vec<u8> {
+        match schema {
+            Some(schema) => [&[SCHEMA_PRESENT], bytemuck::bytes_of(schema)].concat(),
+            None => vec![SCHEMA_ABSENT],
+        }
+    }
+
+    /// The error of a state following this schema where `expected` was
+    pub(crate) fn mismatch(&self, expected: &StateSchema) -> MojoSDKError {
+        MojoSDKError::SchemaMismatch(format!(
+            "state holds type {} v{} with schema {:02x?}, expected type {} v{} with schema {:02x?}",
+            self.state_type_id,
+            self.schema_version,
+            self.schema_hash,
+            expected.state_type_id,
+            expected.schema_version,
+            expected.schema_hash
+        ))
+    }
+}
+
+/// Who may write a shared state besides the world creator, matching the Solana program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AccessMode {
+    /// Nobody but the world creator
+    CreatorOnly = 0,
+    /// The writers listed in the access list
+    Listed = 1,
+    /// Listed writers and any player holding a state in the world
+    AnyMember = 2,
+}
+
+impl AccessMode {
+    /// Decode the mode stored in an access list
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(AccessMode::CreatorOnly),
+            1 => Some(AccessMode::Listed),
+            2 => Some(AccessMode::AnyMember),
+            _ => None,
+        }
+    }
+}
+
+/// Writers an access list holds besides the world creator
+pub const MAX_WRITERS: usize = 16;
+
+/// Access list of a shared state, matching the Solana program
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable, Debug, PartialEq)]
+pub struct StateAcl {
+    pub world: [u8; 32],
+    pub state: [u8; 32],
+    pub mode: u8,
+    pub writer_count: u8,
+    pub writers: [[u8; 32]; MAX_WRITERS],
+}
+
+impl StateAcl {
+    pub const LEN: usize = std::mem::size_of::<Self>(); // 578 bytes
+
+    /// Decode an access list account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MojoSDKError> {
+        bytemuck::try_pod_read_unaligned(data)
+            .map_err(|e| MojoSDKError::Deserialization(format!("Invalid access list: {}", e)))
+    }
+
+    /// Get the access mode, `None` for a mode this SDK does not know
+    pub fn access_mode(&self) -> Option<AccessMode> {
+        AccessMode::from_u8(self.mode)
+    }
+
+    /// Get the listed writers
+    pub fn writers(&self) -> Vec<Pubkey> {
+        self.writers[..(self.writer_count as usize).min(MAX_WRITERS)]
+            .iter()
+            .map(|writer| Pubkey::new_from_array(*writer))
+            .collect()
+    }
+
+    /// Whether `writer` is listed
+    pub fn is_listed(&self, writer: &Pubkey) -> bool {
+        self.writers().contains(writer)
+    }
+}
+
+/// Write instructions a session key may sign, as bits of [`SessionToken::allowed_actions`]
+pub mod session_actions {
+    pub const UPDATE: u8 = 1 << 0;
+    pub const PATCH: u8 = 1 << 1;
+    pub const ARITHMETIC: u8 = 1 << 2;
+    pub const ALL: u8 = UPDATE | PATCH | ARITHMETIC;
+}
+
+/// Leads every session account, matching the Solana program
+pub const SESSION_MAGIC: [u8; 4] = *b"MOJS";
+
+/// Session authorizing an ephemeral key to write the states of its owner, matching the
+/// Solana program
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable, Debug, PartialEq)]
+pub struct SessionToken {
+    pub magic: [u8; 4],
+    pub world: [u8; 32],
+    pub owner: [u8; 32],
+    pub session_key: [u8; 32],
+    pub expires_at: [u8; 8],   // u64 slot as le bytes
+    pub max_actions: [u8; 8],  // u64 as le bytes, 0 for no limit
+    pub actions_used: [u8; 8], // u64 as le bytes
+    pub allowed_actions: u8,
+    pub bump: u8,
+}
+
+impl SessionToken {
+    pub const LEN: usize = std::mem::size_of::<Self>(); // 126 bytes
+
+    /// Decode a session account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MojoSDKError> {
+        let token: Self = bytemuck::try_pod_read_unaligned(data)
+            .map_err(|e| MojoSDKError::Deserialization(format!("Invalid session: {}", e)))?;
+
+        if token.magic != SESSION_MAGIC {
+            return Err(MojoSDKError::InvalidStateData(
+                "Account does not hold a Mojo session".to_string(),
+            ));
+        }
+        Ok(token)
+    }
+
+    /// Get the authorized session key
+    pub fn session_key(&self) -> Pubkey {
+        Pubkey::new_from_array(self.session_key)
+    }
+
+    /// Get the last slot the session key is accepted
+    pub fn expires_at(&self) -> u64 {
+        u64::from_le_bytes(self.expires_at)
+    }
+
+    /// Get the number of actions the key may sign, 0 for no limit
+    pub fn max_actions(&self) -> u64 {
+        u64::from_le_bytes(self.max_actions)
+    }
+
+    /// Get the number of actions the key signed, only counted when limited
+    pub fn actions_used(&self) -> u64 {
+        u64::from_le_bytes(self.actions_used)
+    }
+}
+
+/// Delegation settings appended to the delegate instruction, matching the Solana program
+///
+/// Zeroed fields fall back to the world config, then to the program defaults
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable, Debug, PartialEq)]
+pub struct DelegationArgs {
+    pub commit_frequency_ms: [u8; 4], // u32 as le bytes
+    pub lifetime_secs: [u8; 4],       // u32 as le bytes
+    pub validator: [u8; 32],          // all zeroes lets the delegation program pick
+}
+
+impl DelegationArgs {
+    pub const LEN: usize = std::mem::size_of::<Self>(); // 40 bytes
+}
+
 /// Wrapper for derive PDA
 pub fn derive_pda(seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(seeds, program_id)